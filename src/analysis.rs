@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+
+use crate::helper::{counter_at, normal_cdf, score_tier};
+use crate::ruleset::Ruleset;
+
+/// Number of standard deviations around the aimed time beyond which stops are neglected.
+const SIGMA_RANGE: f64 = 8.0;
+
+/// Exact distribution of the score of one objective.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScoreDistribution {
+    /// Probability of every reachable score.
+    pub scores: BTreeMap<u32, f64>,
+    /// Probability of each accuracy tier, from tier 1 (perfect stop) to tier 5.
+    pub tiers: [f64; 5],
+    /// Probability that the counter wrapped at least once before the stop.
+    pub miss: f64,
+}
+
+impl ScoreDistribution {
+    /// Expected score.
+    pub fn mean(&self) -> f64 {
+        self.scores.iter().map(|(score, p)| *score as f64 * p).sum()
+    }
+
+    /// Standard deviation of the score.
+    pub fn std_dev(&self) -> f64 {
+        let mean = self.mean();
+        let variance: f64 = self.scores.iter().map(|(score, p)| p * (*score as f64 - mean).powi(2)).sum();
+        variance.sqrt()
+    }
+
+    /// Adds the outcomes of `other`, weighted by `weight`.
+    fn add(&mut self, other: &ScoreDistribution, weight: f64) {
+        for (score, p) in &other.scores {
+            *self.scores.entry(*score).or_default() += weight * p;
+        }
+        for (tier, p) in self.tiers.iter_mut().zip(other.tiers) {
+            *tier += weight * p;
+        }
+        self.miss += weight * other.miss;
+    }
+}
+
+/// Goals drawn by `create_goals`: every value from 0 to 100, equally likely.
+pub fn uniform_goals() -> Vec<(u8, f64)> {
+    (0..=100).map(|goal| (goal, 1.0 / 101.0)).collect()
+}
+
+/// Computes the score distribution of an objective with the human timing model
+/// of the simulations (`simulate_stop`): the player aims for the moment the counter
+/// reaches the goal, `goal × speed` ms after the start, and misses it by a normally
+/// distributed error of standard deviation `sigma_ms`. The counter shows tick
+/// `⌊t / speed⌋` modulo 101 at time `t`, so it wraps every 101 × speed ms, and a
+/// stop after the first wrap halves the score.
+pub fn objective_distribution(goal: u8, speed: u32, strength: u32, sigma_ms: f64, rules: &Ruleset) -> ScoreDistribution {
+    let speed_ms = speed.max(1) as f64;
+    let aimed_ms = goal as f64 * speed_ms;
+    let first_tick = ((aimed_ms - SIGMA_RANGE * sigma_ms) / speed_ms).floor().max(0.0) as u64;
+    let last_tick = ((aimed_ms + SIGMA_RANGE * sigma_ms) / speed_ms).ceil().max(0.0) as u64;
+
+    let mut distribution = ScoreDistribution::default();
+    for tick in first_tick..=last_tick {
+        // Stops before the start are clamped to it, like in the simulations.
+        let low = if tick == 0 { 0.0 } else { normal_cdf(tick as f64 * speed_ms, aimed_ms, sigma_ms) };
+        let high = normal_cdf((tick + 1) as f64 * speed_ms, aimed_ms, sigma_ms);
+        let p = high - low;
+        if p <= 0.0 {
+            continue;
+        }
+        let result = counter_at(tick * speed.max(1) as u64, speed);
+        *distribution.scores.entry(rules.score(goal, &result, strength)).or_default() += p;
+        distribution.tiers[score_tier(goal, result.counter) as usize - 1] += p;
+        if result.miss > 0 {
+            distribution.miss += p;
+        }
+    }
+    distribution
+}
+
+/// Computes the score distribution of an objective whose goal is drawn from
+/// `goals`, a list of goals with their probabilities.
+pub fn score_distribution(goals: &[(u8, f64)], speed: u32, strength: u32, sigma_ms: f64, rules: &Ruleset) -> ScoreDistribution {
+    let mut distribution = ScoreDistribution::default();
+    for (goal, weight) in goals {
+        distribution.add(&objective_distribution(*goal, speed, strength, sigma_ms, rules), *weight);
+    }
+    distribution
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::{match_rng, simulate_stop};
+
+    #[test]
+    fn test_perfect_timing() {
+        // Without timing error every stop is perfect.
+        let distribution = objective_distribution(42, 50, 30, 0.0, &Ruleset::default());
+        assert_eq!(distribution.scores.len(), 1);
+        assert!((distribution.scores[&130] - 1.0).abs() < 1e-9);
+        assert!((distribution.tiers[0] - 1.0).abs() < 1e-9);
+        assert_eq!(distribution.miss, 0.0);
+    }
+
+    #[test]
+    fn test_probabilities_add_up() {
+        let distribution = score_distribution(&uniform_goals(), 50, 50, 80.0, &Ruleset::default());
+        let total: f64 = distribution.scores.values().sum();
+        assert!((total - 1.0).abs() < 1e-6);
+        assert!((distribution.tiers.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_miss_probability_near_the_wrap() {
+        // Aiming at 100 with a fast counter, a late stop often wraps past 100.
+        let distribution = objective_distribution(100, 10, 50, 40.0, &Ruleset::default());
+        assert!(distribution.miss > 0.4 && distribution.miss < 0.6);
+        assert_eq!(objective_distribution(50, 10, 50, 40.0, &Ruleset::default()).miss, 0.0);
+    }
+
+    #[test]
+    fn test_matches_simulation() {
+        // The exact expected score agrees with a Monte Carlo estimate.
+        let rules = Ruleset::default();
+        let expected = score_distribution(&uniform_goals(), 30, 50, 80.0, &rules).mean();
+        let mut rng = match_rng(5, 0);
+        let samples = 40_000;
+        let total: u64 = (0..samples)
+            .map(|i| {
+                let goal = (i % 101) as u8;
+                rules.score(goal, &simulate_stop(goal, 30, 80.0, &mut rng), 50) as u64
+            })
+            .sum();
+        let simulated = total as f64 / samples as f64;
+        assert!((expected - simulated).abs() < 1.0, "expected {} simulated {}", expected, simulated);
+    }
+}
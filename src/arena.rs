@@ -0,0 +1,313 @@
+use std::path::Path;
+use std::time::Duration;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::bot::BotProcess;
+use crate::classes::Player;
+use crate::error::GameError;
+use crate::events::{EventBus, MatchEvents};
+use crate::ruleset::Ruleset;
+use crate::simulation::MatchOutcome;
+use crate::turns_helper::play_round;
+
+/// z-score of the 95% confidence intervals of the standings.
+const Z_95: f64 = 1.96;
+
+/// Settings of an arena.
+#[derive(Debug, Clone)]
+pub struct ArenaConfig {
+    /// Rules of every match.
+    pub rules: Ruleset,
+    /// Attributes every bot starts its matches with (the name is ignored).
+    pub template: Player,
+    /// Number of seeds played by each pair of bots; each seed is played twice,
+    /// once with each bot moving first.
+    pub series: u32,
+    /// Seed of the first series; the following ones use the next seeds.
+    pub base_seed: u64,
+    /// Number of rounds after which a match is declared a draw.
+    pub max_rounds: u32,
+    /// Time a bot has to answer each request.
+    pub timeout: Duration,
+}
+
+/// A bot entered in the arena.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entrant {
+    /// Name shown in the results.
+    pub name: String,
+    /// Executable and arguments of the bot.
+    pub command: String,
+}
+
+/// A finished arena match, as reported while the arena runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArenaMatch<'a> {
+    /// Bot moving first.
+    pub first: &'a str,
+    /// Bot moving second.
+    pub second: &'a str,
+    /// Seed of the match.
+    pub seed: u64,
+    /// Result of the match; the winner is 0 for `first`, 1 for `second`.
+    pub outcome: MatchOutcome,
+    /// Why the match was not played out: a bot that could not be started
+    /// forfeits it, and an error of the match itself ends it as a draw.
+    pub error: Option<String>,
+}
+
+/// Results of one bot over the whole arena.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Standing {
+    pub name: String,
+    pub wins: u64,
+    pub draws: u64,
+    pub losses: u64,
+}
+
+impl Standing {
+    /// Number of matches played.
+    pub fn games(&self) -> u64 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Share of the points won, a draw counting as half a win.
+    pub fn win_rate(&self) -> f64 {
+        match self.games() {
+            0 => 0.0,
+            games => (self.wins as f64 + 0.5 * self.draws as f64) / games as f64,
+        }
+    }
+
+    /// 95% confidence interval of the win rate.
+    pub fn confidence_interval(&self) -> (f64, f64) {
+        wilson_interval(self.win_rate(), self.games())
+    }
+}
+
+/// Wilson score interval of a proportion `rate` observed over `n` trials.
+/// Unlike the normal approximation, it stays within [0, 1] for small samples.
+pub fn wilson_interval(rate: f64, n: u64) -> (f64, f64) {
+    if n == 0 {
+        return (0.0, 1.0);
+    }
+    let n = n as f64;
+    let z2 = Z_95 * Z_95;
+    let center = (rate + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let margin = Z_95 * (rate * (1.0 - rate) / n + z2 / (4.0 * n * n)).sqrt() / (1.0 + z2 / n);
+    ((center - margin).max(0.0), (center + margin).min(1.0))
+}
+
+/// Names the bots after their programs (`python3 bots/greedy.py` is "greedy"),
+/// numbering those that would share a name.
+pub fn entrants(commands: &[String]) -> Vec<Entrant> {
+    let stem = |command: &str| {
+        let last = command.split_whitespace().last().unwrap_or(command);
+        Path::new(last)
+            .file_stem()
+            .map_or_else(|| last.to_string(), |stem| stem.to_string_lossy().into_owned())
+    };
+    let stems: Vec<String> = commands.iter().map(|c| stem(c)).collect();
+    commands
+        .iter()
+        .enumerate()
+        .map(|(i, command)| {
+            let shared = stems.iter().filter(|s| **s == stems[i]).count() > 1;
+            Entrant {
+                name: if shared { format!("{}#{}", stems[i], i + 1) } else { stems[i].clone() },
+                command: command.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Plays a match with the rules of `play_round` until a player has no
+/// vitality left, or `max_rounds` rounds have been played.
+pub fn play_match(
+    p1: &mut Player,
+    p2: &mut Player,
+    rules: &Ruleset,
+    seed: u64,
+    max_rounds: u32,
+    events: &MatchEvents,
+) -> Result<MatchOutcome, GameError> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    rules.equip(p1);
+    rules.equip(p2);
+    for rounds in 1..=max_rounds {
+        play_round(p1, p2, rules, events, &mut rng)?;
+        if p1.vitality() == 0 || p2.vitality() == 0 {
+            let winner = if p1.vitality() == 0 { 1 } else { 0 };
+            return Ok(MatchOutcome { winner: Some(winner), rounds });
+        }
+    }
+    Ok(MatchOutcome { winner: None, rounds: max_rounds })
+}
+
+/// Plays a round-robin between the bots: every pair plays `series` seeds, each
+/// seed once with each bot moving first. Every match starts fresh bot processes
+/// and emits its events through `events`, numbered from 1 in the order played.
+/// A failing match does not stop the arena: it is recorded as a forfeit of the
+/// bots that could not be started, or as a draw when the match itself failed.
+/// `on_match` is called after every match. Returns the standings, best first.
+pub fn run_arena(
+    entrants: &[Entrant],
+    cfg: &ArenaConfig,
+    events: &EventBus,
+    mut on_match: impl FnMut(&ArenaMatch),
+) -> Vec<Standing> {
+    let mut standings: Vec<Standing> = entrants
+        .iter()
+        .map(|e| Standing { name: e.name.clone(), ..Standing::default() })
+        .collect();
+
+    let mut match_id = 0;
+    for i in 0..entrants.len() {
+        for j in i + 1..entrants.len() {
+            for series in 0..cfg.series {
+                let seed = cfg.base_seed.wrapping_add(series as u64);
+                for (first, second) in [(i, j), (j, i)] {
+                    let spawn = |entrant: &Entrant| -> Result<Player, GameError> {
+                        let bot = BotProcess::spawn(&entrant.command, cfg.timeout)?;
+                        let mut player = cfg.template.clone().with_bot(bot);
+                        player.name = entrant.name.clone();
+                        Ok(player)
+                    };
+                    match_id += 1;
+                    let events = events.for_match(match_id);
+                    let (outcome, error) = match (spawn(&entrants[first]), spawn(&entrants[second])) {
+                        (Ok(mut p1), Ok(mut p2)) => {
+                            match play_match(&mut p1, &mut p2, &cfg.rules, seed, cfg.max_rounds, &events) {
+                                Ok(outcome) => (outcome, None),
+                                Err(e) => (MatchOutcome { winner: None, rounds: 0 }, Some(e.to_string())),
+                            }
+                        }
+                        (Err(e), Ok(_)) => (MatchOutcome { winner: Some(1), rounds: 0 }, Some(e.to_string())),
+                        (Ok(_), Err(e)) => (MatchOutcome { winner: Some(0), rounds: 0 }, Some(e.to_string())),
+                        (Err(e), Err(_)) => (MatchOutcome { winner: None, rounds: 0 }, Some(e.to_string())),
+                    };
+
+                    match outcome.winner {
+                        Some(0) => {
+                            standings[first].wins += 1;
+                            standings[second].losses += 1;
+                        }
+                        Some(_) => {
+                            standings[second].wins += 1;
+                            standings[first].losses += 1;
+                        }
+                        None => {
+                            standings[first].draws += 1;
+                            standings[second].draws += 1;
+                        }
+                    }
+                    on_match(&ArenaMatch {
+                        first: &entrants[first].name,
+                        second: &entrants[second].name,
+                        seed,
+                        outcome,
+                        error,
+                    });
+                }
+            }
+        }
+    }
+
+    standings.sort_by(|a, b| b.win_rate().total_cmp(&a.win_rate()));
+    standings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wilson_interval() {
+        let (low, high) = wilson_interval(0.5, 100);
+        assert!((low - 0.4038).abs() < 1e-3);
+        assert!((high - 0.5962).abs() < 1e-3);
+        // Even a perfect record over few games leaves room for doubt.
+        let (low, high) = wilson_interval(1.0, 5);
+        assert!(low > 0.5 && low < 0.6);
+        assert_eq!(high, 1.0);
+        assert_eq!(wilson_interval(0.0, 0), (0.0, 1.0));
+    }
+
+    #[test]
+    fn test_entrant_names() {
+        let commands = ["python3 bots/greedy.py".to_string(), "./lazy".to_string(), "bots/lazy".to_string()];
+        let names: Vec<String> = entrants(&commands).into_iter().map(|e| e.name).collect();
+        assert_eq!(names, ["greedy", "lazy#2", "lazy#3"]);
+    }
+
+    #[test]
+    fn test_standing_win_rate() {
+        let standing = Standing { name: "A".to_string(), wins: 3, draws: 2, losses: 5 };
+        assert_eq!(standing.games(), 10);
+        assert!((standing.win_rate() - 0.4).abs() < 1e-9);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_arena_round_robin() {
+        // A bot stopping exactly on the goal beats a bot that exits at once
+        // (and so overshoots every goal), whoever moves first.
+        let script = std::env::temp_dir().join(format!("duel_game_arena_bot_{}.sh", std::process::id()));
+        std::fs::write(
+            &script,
+            r#"while read l; do
+                case "$l" in
+                *objective*)
+                    goal=$(echo "$l" | sed 's/.*"goal":\([0-9]*\).*/\1/')
+                    echo "{\"stop_ms\": $((goal * 50))}";;
+                *) echo '{"penalty": "strength"}';;
+                esac
+            done"#,
+        )
+        .unwrap();
+        let commands = [format!("sh {}", script.display()), "true".to_string()];
+        let cfg = ArenaConfig {
+            rules: Ruleset::with_goal_count(2),
+            template: Player::new(String::new(), 30, 50, 50),
+            series: 2,
+            base_seed: 11,
+            max_rounds: 50,
+            timeout: Duration::from_secs(5),
+        };
+        let mut played = Vec::new();
+        let standings =
+            run_arena(&entrants(&commands), &cfg, &EventBus::default(), |m| played.push((m.first.to_string(), m.seed)));
+        std::fs::remove_file(&script).unwrap();
+
+        assert_eq!(played.len(), 4);
+        assert_eq!(played[0].1, played[1].1);
+        assert_ne!(played[0].0, played[1].0);
+        assert_eq!(standings[0].wins, 4);
+        assert_eq!(standings[1].losses, 4);
+    }
+
+    #[test]
+    fn test_arena_forfeits_bots_that_cannot_start() {
+        let commands = ["true".to_string(), "/nonexistent/duel_game_bot".to_string()];
+        let cfg = ArenaConfig {
+            rules: Ruleset::with_goal_count(2),
+            template: Player::new(String::new(), 30, 50, 50),
+            series: 1,
+            base_seed: 11,
+            max_rounds: 50,
+            timeout: Duration::from_secs(5),
+        };
+        let mut errors = 0;
+        let standings = run_arena(&entrants(&commands), &cfg, &EventBus::default(), |m| {
+            assert_eq!(m.outcome.rounds, 0);
+            errors += m.error.is_some() as u32;
+        });
+
+        assert_eq!(errors, 2);
+        assert_eq!(standings[0].name, "true");
+        assert_eq!(standings[0].wins, 2);
+        assert_eq!(standings[1].losses, 2);
+    }
+}
@@ -0,0 +1,290 @@
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::classes::{Player, PlayerError, Stat};
+use crate::error::GameError;
+use crate::events::{EventBus, GameEvent, MatchEvent};
+use crate::i18n::{tr, Msg};
+use crate::json_file;
+use crate::ruleset::Ruleset;
+use crate::stats::PlayerStats;
+
+/// Default file the match is saved to when interrupted.
+pub const DEFAULT_AUTOSAVE_PATH: &str = "duel_game_autosave.json";
+
+/// Exit code of the process after an interruption (128 + SIGINT).
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// How long the interrupt handler waits for the counter thread to stop.
+const COUNTER_STOP_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// An objective that was running when the match was interrupted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AbandonedObjective {
+    /// Name of the player whose objective was interrupted.
+    pub player: String,
+    /// Round during which the objective was interrupted (starting at 1).
+    pub round: u32,
+    /// Index of the objective in the turn (starting at 1).
+    pub objective: usize,
+    /// Target value of the objective.
+    pub goal: u8,
+}
+
+/// State of a match at the start of a round, enough to resume it later.
+/// The state is only saved between rounds: a match interrupted mid-round resumes
+/// at the start of that round, and the objectives already played in it are
+/// played again (on the same goals, as the generator is restored too).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchState {
+    /// Both players, as they were at the start of the round.
+    pub players: [Player; 2],
+    /// Statistics of every objective completed so far.
+    pub stats: [PlayerStats; 2],
+    /// Rules of the match.
+    pub rules: Ruleset,
+    /// Seed of the match's random number generator.
+    pub seed: u64,
+    /// Position of the random number generator at the start of the round.
+    pub rng_word_pos: u128,
+    /// Number of rounds completed.
+    pub rounds_played: u32,
+    /// Objectives abandoned because the match was interrupted.
+    pub abandoned: Vec<AbandonedObjective>,
+    /// Vitality points each player has taken from the other so far with the
+    /// damage of the rounds they won (see `RoundReport::damage_dealt`).
+    #[serde(default)]
+    pub damage_dealt: [u32; 2],
+}
+
+impl MatchState {
+    /// Creates the state of a new match.
+    /// The players are equipped for the rules.
+    pub fn new(mut p1: Player, mut p2: Player, rules: Ruleset, seed: u64) -> Self {
+        rules.equip(&mut p1);
+        rules.equip(&mut p2);
+        MatchState {
+            players: [p1, p2],
+            stats: [PlayerStats::default(), PlayerStats::default()],
+            rules,
+            seed,
+            rng_word_pos: 0,
+            rounds_played: 0,
+            abandoned: Vec::new(),
+            damage_dealt: [0, 0],
+        }
+    }
+
+    /// Recreates the match's random number generator at the saved position.
+    pub fn rng(&self) -> ChaCha8Rng {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        rng.set_word_pos(self.rng_word_pos);
+        rng
+    }
+
+    /// Writes the state to a JSON file.
+    pub fn save(&self, path: &Path) -> Result<(), GameError> {
+        json_file::save(self, path, GameError::InvalidSave)
+    }
+
+    /// Reads a state previously written by `save`, and checks that the match
+    /// can go on: both players are named and fast enough for the rules' limits,
+    /// and rounds have objectives.
+    pub fn load(path: &Path) -> Result<Self, GameError> {
+        let state: MatchState = json_file::load(path, GameError::InvalidSave)?;
+        if state.rules.goal_count == 0 {
+            return Err(GameError::InvalidSave("goal_count must be at least 1".to_string()));
+        }
+        let range = state.rules.progression.limits.speed;
+        for player in &state.players {
+            if player.name.trim().is_empty() {
+                return Err(GameError::InvalidSave(PlayerError::EmptyName.to_string()));
+            }
+            let value = player.stats.base(Stat::Speed);
+            if value < range.min {
+                let error = PlayerError::OutOfRange { stat: Stat::Speed, value, range };
+                return Err(GameError::InvalidSave(error.to_string()));
+            }
+        }
+        Ok(state)
+    }
+}
+
+/// Set once Ctrl-C has been pressed.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the counter thread of an objective is currently running.
+static COUNTER_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Last state saved by `checkpoint`, written to disk on interruption.
+static CHECKPOINT: Mutex<Option<MatchState>> = Mutex::new(None);
+
+/// Objective currently being played, if any.
+static CURRENT_OBJECTIVE: Mutex<Option<(String, usize, u8)>> = Mutex::new(None);
+
+/// Whether Ctrl-C has been pressed. The counter thread stops as soon as it is.
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::Relaxed)
+}
+
+/// Records whether the counter thread is running. It is set before the thread is
+/// spawned, so that an interruption right after the spawn still waits for it.
+pub fn set_counter_running(running: bool) {
+    COUNTER_RUNNING.store(running, Ordering::Relaxed);
+}
+
+/// Remembers the state to save if the match is interrupted. It is called at the
+/// start of every round, so the progress made within a round is not kept.
+pub fn checkpoint(state: &MatchState) {
+    *CHECKPOINT.lock().unwrap_or_else(|e| e.into_inner()) = Some(state.clone());
+}
+
+/// Forgets the saved state once the match is over.
+pub fn clear_checkpoint() {
+    *CHECKPOINT.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Returns the state to save after an interruption: the last checkpoint, with
+/// the objective that was running (if any) recorded as abandoned.
+pub fn interrupted_state() -> Option<MatchState> {
+    let mut state = CHECKPOINT.lock().unwrap_or_else(|e| e.into_inner()).clone()?;
+    if let Some((player, index, goal)) = CURRENT_OBJECTIVE.lock().unwrap_or_else(|e| e.into_inner()).take() {
+        state.abandoned.push(AbandonedObjective {
+            player,
+            round: state.rounds_played + 1,
+            objective: index + 1,
+            goal,
+        });
+    }
+    Some(state)
+}
+
+/// Keeps track of the objective being played, from the engine's events.
+fn track_objectives(event: &MatchEvent) {
+    let mut current = CURRENT_OBJECTIVE.lock().unwrap_or_else(|e| e.into_inner());
+    match &event.event {
+        GameEvent::ObjectiveStarted { player, index, goal } => *current = Some((player.clone(), *index, *goal)),
+        GameEvent::ObjectiveStopped { .. } => *current = None,
+        _ => {}
+    }
+}
+
+/// Installs a Ctrl-C handler that stops the counter cleanly, saves the match
+/// to `path` and prints how to resume it before exiting. The objective being played
+/// is followed through the events of `events`.
+pub fn install_interrupt_handler(path: PathBuf, events: &EventBus) -> Result<(), ctrlc::Error> {
+    events.subscribe(Arc::new(track_objectives));
+    ctrlc::set_handler(move || on_interrupt(&path))
+}
+
+/// Handles Ctrl-C: runs on the signal handling thread.
+fn on_interrupt(path: &Path) {
+    INTERRUPTED.store(true, Ordering::Relaxed);
+
+    // Let the counter thread notice the interruption and stop printing.
+    let deadline = Instant::now() + COUNTER_STOP_TIMEOUT;
+    while COUNTER_RUNNING.load(Ordering::Relaxed) && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    // Leave the counter line.
+    println!();
+
+    if let Some(state) = interrupted_state() {
+        match state.save(path) {
+            Ok(()) => {
+                let path = path.display().to_string();
+                println!("{}", tr(Msg::InterruptedSaved { path: &path }));
+                println!("{}", tr(Msg::ResumeHint { path: &path }));
+            }
+            Err(e) => eprintln!("{}", tr(Msg::InterruptedNotSaved { error: &e.to_string() })),
+        }
+    } else {
+        println!("{}", tr(Msg::Interrupted));
+    }
+    process::exit(INTERRUPTED_EXIT_CODE);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+    use std::fs;
+
+    fn sample_state() -> MatchState {
+        let p1 = Player::new("Alice".to_string(), 40, 50, 45);
+        let p2 = Player::new("Bob".to_string(), 30, 45, 50);
+        MatchState::new(p1, p2, Ruleset::with_goal_count(3), 99)
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let mut state = sample_state();
+        state.rounds_played = 2;
+        state.rng_word_pos = 48;
+        let path = std::env::temp_dir().join(format!("duel_game_autosave_test_{}.json", process::id()));
+        state.save(&path).unwrap();
+        let loaded = MatchState::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, state);
+    }
+
+    #[test]
+    fn test_load_invalid_file() {
+        let path = std::env::temp_dir().join(format!("duel_game_invalid_test_{}.json", process::id()));
+        fs::write(&path, "not json").unwrap();
+        let result = MatchState::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(GameError::InvalidSave(_))));
+    }
+
+    #[test]
+    fn test_load_rejects_unplayable_state() {
+        let path = std::env::temp_dir().join(format!("duel_game_unplayable_test_{}.json", process::id()));
+        let mut state = sample_state();
+        *state.players[1].stat_mut(Stat::Speed) = 0;
+        state.save(&path).unwrap();
+        let slow = MatchState::load(&path);
+        let mut state = sample_state();
+        state.rules.goal_count = 0;
+        state.save(&path).unwrap();
+        let no_goals = MatchState::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(slow, Err(GameError::InvalidSave(_))));
+        assert!(matches!(no_goals, Err(GameError::InvalidSave(_))));
+    }
+
+    #[test]
+    fn test_rng_resumes_at_saved_position() {
+        // A resumed match must draw the same goals as the original one.
+        let mut state = sample_state();
+        let mut rng = state.rng();
+        let _: u32 = rng.gen_range(0..100);
+        state.rng_word_pos = rng.get_word_pos();
+        let expected: u32 = rng.gen_range(0..1000);
+        assert_eq!(state.rng().gen_range(0..1000), expected);
+    }
+
+    #[test]
+    fn test_interrupted_state_records_abandoned_objective() {
+        let mut state = sample_state();
+        state.rounds_played = 1;
+        checkpoint(&state);
+        let event = GameEvent::ObjectiveStarted { player: "Bob".to_string(), index: 2, goal: 77 };
+        track_objectives(&MatchEvent { match_id: 1, event });
+        let saved = interrupted_state().unwrap();
+        clear_checkpoint();
+        assert_eq!(
+            saved.abandoned,
+            vec![AbandonedObjective { player: "Bob".to_string(), round: 2, objective: 3, goal: 77 }]
+        );
+        assert_eq!(saved.players, state.players);
+    }
+}
@@ -0,0 +1,124 @@
+use std::cmp::Reverse;
+
+use crate::classes::Player;
+use crate::i18n::{say, Msg};
+use crate::modifiers::{Modifier, RoundHook, RoundRules};
+
+/// A phase of a boss fight, with its own rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Phase {
+    /// The phase starts once the boss's vitality is at or below this share of
+    /// its starting vitality, in percent.
+    pub threshold_percent: u32,
+    /// Modifiers in effect during the phase.
+    pub modifiers: Vec<Modifier>,
+    /// Share of the damage taken by the boss that is sent back to its opponent, in percent.
+    pub reflect_percent: u32,
+}
+
+/// Round hook running the phases of a boss: the rules of each round follow the
+/// phase matching the boss's vitality at the start of the round.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BossFight {
+    /// Index of the boss in the match (0 or 1).
+    boss: usize,
+    /// Vitality of the boss at the start of the fight.
+    max_vitality: u32,
+    /// Phases, from the first (highest threshold) to the last.
+    phases: Vec<Phase>,
+    /// Phase of the last round, if any.
+    phase: Option<usize>,
+}
+
+impl BossFight {
+    /// Starts the fight of the boss at `boss` (0 or 1), whose vitality is `max_vitality`.
+    pub fn new(boss: usize, max_vitality: u32, mut phases: Vec<Phase>) -> Self {
+        phases.sort_by_key(|phase| Reverse(phase.threshold_percent));
+        BossFight {
+            boss,
+            max_vitality: max_vitality.max(1),
+            phases,
+            phase: None,
+        }
+    }
+
+    /// Phase of the last round played, if the boss has entered one.
+    pub fn current_phase(&self) -> Option<usize> {
+        self.phase
+    }
+
+    /// Phase matching a vitality of the boss: the last one whose threshold is reached.
+    pub fn phase_for(&self, vitality: u32) -> Option<usize> {
+        let percent = vitality as u64 * 100;
+        self.phases
+            .iter()
+            .rposition(|phase| percent <= phase.threshold_percent as u64 * self.max_vitality as u64)
+    }
+}
+
+impl RoundHook for BossFight {
+    fn start_round(&mut self, players: [&Player; 2], round: &mut RoundRules) {
+        let boss = players[self.boss];
+        let phase = self.phase_for(boss.vitality());
+        if phase != self.phase
+            && let Some(index) = phase
+        {
+            say(Msg::BossPhase { name: &boss.name, phase: index + 1, phases: self.phases.len() });
+            let reflect_percent = self.phases[index].reflect_percent;
+            if reflect_percent > 0 {
+                say(Msg::BossReflects { name: &boss.name, percent: reflect_percent });
+            }
+        }
+        self.phase = phase;
+        if let Some(phase) = phase.map(|index| &self.phases[index]) {
+            round.modifiers.extend(&phase.modifiers);
+            round.reflect_percent[self.boss] = phase.reflect_percent;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classes::Stat;
+
+    fn phases() -> Vec<Phase> {
+        vec![
+            Phase { threshold_percent: 30, modifiers: vec![Modifier::HiddenGoals], reflect_percent: 50 },
+            Phase { threshold_percent: 100, modifiers: vec![], reflect_percent: 0 },
+            Phase { threshold_percent: 60, modifiers: vec![Modifier::DoubleSpeed], reflect_percent: 0 },
+        ]
+    }
+
+    #[test]
+    fn test_phase_thresholds() {
+        let fight = BossFight::new(1, 200, phases());
+        assert_eq!(fight.phase_for(200), Some(0));
+        assert_eq!(fight.phase_for(121), Some(0));
+        assert_eq!(fight.phase_for(120), Some(1));
+        assert_eq!(fight.phase_for(60), Some(2));
+        assert_eq!(fight.phase_for(0), Some(2));
+        // Without a phase at full vitality, the fight starts with normal rounds.
+        let fight = BossFight::new(0, 100, vec![Phase { threshold_percent: 50, modifiers: vec![], reflect_percent: 10 }]);
+        assert_eq!(fight.phase_for(80), None);
+    }
+
+    #[test]
+    fn test_hook_applies_phase_rules() {
+        let challenger = Player::new("Challenger".to_string(), 50, 50, 50);
+        let mut boss = Player::new("Boss".to_string(), 200, 40, 60);
+        let mut fight = BossFight::new(1, boss.vitality(), phases());
+
+        let mut round = RoundRules::default();
+        fight.start_round([&challenger, &boss], &mut round);
+        assert_eq!(round, RoundRules::default());
+        assert_eq!(fight.current_phase(), Some(0));
+
+        *boss.stat_mut(Stat::Vitality) = 50;
+        let mut round = RoundRules::default();
+        fight.start_round([&challenger, &boss], &mut round);
+        assert_eq!(fight.current_phase(), Some(2));
+        assert_eq!(round.modifiers, vec![Modifier::HiddenGoals]);
+        assert_eq!(round.reflect_percent, [0, 50]);
+    }
+}
@@ -0,0 +1,323 @@
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::classes::{Player, PlayerRes};
+use crate::error::GameError;
+use crate::helper::{counter_at, Penalty};
+use crate::i18n::{say, tr, Msg};
+use crate::modifiers::{Modifier, RoundRules};
+use crate::turns_helper::MIN_STOP_DELAY_MS;
+use crate::wager::Wagering;
+
+/// Default time a bot has to answer a request.
+pub const DEFAULT_BOT_TIMEOUT_MS: u64 = 1000;
+
+/// Penalties a bot can choose from, in the order they are offered.
+const PENALTY_CHOICES: [Penalty; 2] = [Penalty::Speed, Penalty::Strength];
+
+/// Message sent by the engine to a bot written as an external program.
+///
+/// The engine starts the bot's executable and talks to it over its standard
+/// input and output, one JSON object per line:
+///
+/// - before each objective the engine sends
+///   `{"type":"objective","goal":42,"speed":50,"strength":50}` and the bot
+///   answers `{"stop_ms":2100}`, the time after which it stops the counter;
+///   when the counter runs from 100 down to 0 during the round, the request
+///   also has `"backwards":true`;
+/// - when the bot wins a round the engine sends
+///   `{"type":"penalty","opponent":{"name":"Bob","vitality":40,"speed":50,"strength":50,"stamina":3},"choices":["speed","strength"]}`
+///   and the bot answers `{"penalty":"strength"}`;
+/// - when the rules have a betting phase, before each round the engine sends
+///   `{"type":"wager","vitality":50,"max_stake":25,"opponent":{...}}` and the bot
+///   answers `{"stake":10}`, the vitality it stakes on the round;
+/// - when the rules let the loser of a round block, a bot with enough stamina
+///   always tries, and its block is played like an objective.
+///
+/// Anything the bot writes to its standard error is left to the terminal.
+/// A bot that does not answer in time, answers something else or exits is not
+/// fatal: its stop lands half a counter cycle away from the goal after a full
+/// cycle, and it inflicts the first penalty offered; a bot that stakes too much
+/// stakes nothing.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BotRequest<'a> {
+    /// The bot must stop the counter of an objective.
+    Objective {
+        goal: u8,
+        speed: u32,
+        strength: u32,
+        #[serde(skip_serializing_if = "std::ops::Not::not")]
+        backwards: bool,
+    },
+    /// The bot won the round and must choose the penalty of its opponent.
+    Penalty { opponent: &'a Player, choices: [Penalty; 2] },
+    /// The bot must choose how much vitality it stakes on the round.
+    Wager { vitality: u32, max_stake: u32, opponent: &'a Player },
+}
+
+/// Answer to an `objective` request.
+#[derive(Debug, Deserialize)]
+struct StopReply {
+    stop_ms: u64,
+}
+
+/// Answer to a `wager` request.
+#[derive(Debug, Deserialize)]
+struct StakeReply {
+    stake: u32,
+}
+
+/// Answer to a `penalty` request.
+#[derive(Debug, Deserialize)]
+struct PenaltyReply {
+    penalty: Penalty,
+}
+
+/// Ways a bot can fail to answer a request.
+#[derive(Debug)]
+pub enum BotError {
+    /// The request could not be sent to the bot.
+    Io(io::Error),
+    /// The bot did not answer in time.
+    Timeout,
+    /// The bot closed its output, usually because it exited.
+    Closed,
+    /// The bot's answer is not what the protocol expects.
+    Malformed(String),
+}
+
+impl fmt::Display for BotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BotError::Io(e) => write!(f, "{}", tr(Msg::ErrIo { error: &e.to_string() })),
+            BotError::Timeout => write!(f, "{}", tr(Msg::ErrBotTimeout)),
+            BotError::Closed => write!(f, "{}", tr(Msg::ErrBotClosed)),
+            BotError::Malformed(reply) => write!(f, "{}", tr(Msg::ErrBotMalformed { reply })),
+        }
+    }
+}
+
+impl std::error::Error for BotError {}
+
+/// A running bot program.
+#[derive(Debug)]
+pub struct BotProcess {
+    child: Child,
+    stdin: ChildStdin,
+    replies: Receiver<String>,
+    timeout: Duration,
+}
+
+impl BotProcess {
+    /// Starts a bot. `command` is the path of the executable followed by its
+    /// arguments, separated by spaces.
+    pub fn spawn(command: &str, timeout: Duration) -> Result<Self, GameError> {
+        let mut words = command.split_whitespace();
+        let program = words.next().ok_or_else(|| GameError::Bot(command.to_string()))?;
+        let mut child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| GameError::Bot(format!("{}: {}", command, e)))?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+
+        // Read the bot's answers on a separate thread, so that waiting for them can time out.
+        let (lines, replies) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                if lines.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(BotProcess { child, stdin, replies, timeout })
+    }
+
+    /// Asks the bot after how many milliseconds it stops the counter of an objective.
+    pub fn stop_time(&mut self, goal: u8, speed: u32, strength: u32, backwards: bool) -> Result<u64, BotError> {
+        let reply: StopReply = self.request(&BotRequest::Objective { goal, speed, strength, backwards })?;
+        Ok(reply.stop_ms)
+    }
+
+    /// Asks the bot which penalty to inflict on its opponent.
+    pub fn choose_penalty(&mut self, opponent: &Player) -> Result<Penalty, BotError> {
+        let reply: PenaltyReply = self.request(&BotRequest::Penalty { opponent, choices: PENALTY_CHOICES })?;
+        Ok(reply.penalty)
+    }
+
+    /// Asks the bot how much vitality it stakes on the round.
+    pub fn stake(&mut self, vitality: u32, max_stake: u32, opponent: &Player) -> Result<u32, BotError> {
+        let reply: StakeReply = self.request(&BotRequest::Wager { vitality, max_stake, opponent })?;
+        Ok(reply.stake)
+    }
+
+    /// Sends a request and waits for the answer.
+    fn request<T: DeserializeOwned>(&mut self, request: &BotRequest) -> Result<T, BotError> {
+        // Drop answers that arrived after an earlier request timed out.
+        while self.replies.try_recv().is_ok() {}
+
+        let json = serde_json::to_string(request).map_err(|e| BotError::Io(io::Error::other(e)))?;
+        writeln!(self.stdin, "{}", json).map_err(BotError::Io)?;
+        self.stdin.flush().map_err(BotError::Io)?;
+
+        let reply = match self.replies.recv_timeout(self.timeout) {
+            Ok(reply) => reply,
+            Err(RecvTimeoutError::Timeout) => return Err(BotError::Timeout),
+            Err(RecvTimeoutError::Disconnected) => return Err(BotError::Closed),
+        };
+        serde_json::from_str(&reply).map_err(|_| BotError::Malformed(reply))
+    }
+}
+
+impl Drop for BotProcess {
+    fn drop(&mut self) {
+        // The bot may already have exited: nothing more to do then.
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Plays an objective for a bot player, whose counter increments every `speed` ms
+/// (the player's speed, unless the round changes it), in the direction set by the round.
+/// Like human stops, bot stops cannot come sooner than `MIN_STOP_DELAY_MS`.
+/// A failing bot stops a full counter cycle late and half a cycle away from the
+/// goal, so that its stop can never score well.
+pub fn bot_objective(bot: &Mutex<BotProcess>, player: &Player, goal: u8, speed: u32, round: &RoundRules) -> PlayerRes {
+    let backwards = round.has(Modifier::CounterBackwards);
+    let reply = bot.lock().unwrap_or_else(|e| e.into_inner()).stop_time(goal, speed, player.strength(), backwards);
+    let elapsed_ms = match reply {
+        Ok(stop_ms) => stop_ms.max(MIN_STOP_DELAY_MS),
+        Err(e) => {
+            eprintln!("{}", tr(Msg::BotFailed { name: &player.name, error: &e.to_string() }));
+            (round.shown_counter(goal) as u64 + 101 + 50) * speed.max(1) as u64
+        }
+    };
+    let mut result = counter_at(elapsed_ms, speed);
+    result.counter = round.shown_counter(result.counter);
+    say(Msg::ObjectiveStopped { counter: result.counter, miss: result.miss });
+    result
+}
+
+/// Lets a bot that won the round choose its opponent's penalty.
+/// A failing bot inflicts the first penalty offered.
+pub fn bot_penalty(bot: &Mutex<BotProcess>, winner: &Player, opponent: &Player) -> Penalty {
+    let reply = bot.lock().unwrap_or_else(|e| e.into_inner()).choose_penalty(opponent);
+    reply.unwrap_or_else(|e| {
+        eprintln!("{}", tr(Msg::BotFailed { name: &winner.name, error: &e.to_string() }));
+        PENALTY_CHOICES[0]
+    })
+}
+
+/// Lets a bot stake vitality on the round. A failing bot, or one staking
+/// more than allowed, stakes nothing.
+pub fn bot_stake(bot: &Mutex<BotProcess>, player: &Player, opponent: &Player, wagering: &Wagering) -> u32 {
+    let max_stake = wagering.max_stake(player.vitality());
+    let reply = bot.lock().unwrap_or_else(|e| e.into_inner()).stake(player.vitality(), max_stake, opponent);
+    let stake = reply.map_err(|e| e.to_string()).and_then(|stake| {
+        wagering.validate(stake, player.vitality()).map_err(|e| e.to_string())
+    });
+    stake.unwrap_or_else(|error| {
+        eprintln!("{}", tr(Msg::BotFailed { name: &player.name, error: &error }));
+        0
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    /// Script file of a bot, removed once the test is over.
+    struct ScriptFile(std::path::PathBuf);
+
+    impl Drop for ScriptFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    /// Starts a shell script as a bot. The script stays on disk as long as the
+    /// returned file is kept.
+    fn script_bot(name: &str, script: &str, timeout_ms: u64) -> (BotProcess, ScriptFile) {
+        let command = std::env::temp_dir().join(format!("duel_game_bot_{}_{}.sh", name, std::process::id()));
+        std::fs::write(&command, script).unwrap();
+        let bot = BotProcess::spawn(&format!("sh {}", command.display()), Duration::from_millis(timeout_ms));
+        (bot.unwrap(), ScriptFile(command))
+    }
+
+    #[test]
+    fn test_request_serialization() {
+        let json = serde_json::to_string(&BotRequest::Objective { goal: 42, speed: 50, strength: 60, backwards: false }).unwrap();
+        assert_eq!(json, r#"{"type":"objective","goal":42,"speed":50,"strength":60}"#);
+        let json = serde_json::to_string(&BotRequest::Objective { goal: 42, speed: 50, strength: 60, backwards: true }).unwrap();
+        assert_eq!(json, r#"{"type":"objective","goal":42,"speed":50,"strength":60,"backwards":true}"#);
+    }
+
+    #[test]
+    fn test_bot_answers() {
+        let (mut bot, _script) = script_bot(
+            "answers",
+            "read l; echo '{\"stop_ms\": 2100}'; read l; echo '{\"penalty\": \"strength\"}'; read l",
+            5000,
+        );
+        assert_eq!(bot.stop_time(42, 50, 50, false).unwrap(), 2100);
+        let opponent = Player::new("Bob".to_string(), 40, 50, 50);
+        assert_eq!(bot.choose_penalty(&opponent).unwrap(), Penalty::Strength);
+    }
+
+    #[test]
+    fn test_bot_failures() {
+        let (mut bot, _script) = script_bot("slow", "read l; echo 'not json'; read l; sleep 5", 200);
+        assert!(matches!(bot.stop_time(42, 50, 50, false), Err(BotError::Malformed(reply)) if reply == "not json"));
+        assert!(matches!(bot.stop_time(42, 50, 50, false), Err(BotError::Timeout)));
+
+        let (mut bot, _script) = script_bot("exits", "exit 0", 5000);
+        assert!(matches!(bot.stop_time(42, 50, 50, false), Err(BotError::Closed) | Err(BotError::Io(_))));
+    }
+
+    #[test]
+    fn test_failing_bot_overshoots() {
+        // A failing bot's stop is never near the goal, whatever the goal and direction.
+        let (bot, _script) = script_bot("overshoots", "exit 0", 5000);
+        let bot = Mutex::new(bot);
+        let player = Player::new("Robot".to_string(), 50, 50, 50);
+        let backwards = RoundRules { modifiers: vec![Modifier::CounterBackwards], ..RoundRules::default() };
+        for round in [RoundRules::default(), backwards] {
+            for goal in [0, 42, 100] {
+                let result = bot_objective(&bot, &player, goal, player.speed(), &round);
+                assert_eq!(crate::helper::score_tier(goal, result.counter), 5);
+                assert!(result.miss >= 1);
+            }
+        }
+        assert_eq!(bot_penalty(&bot, &player, &player), Penalty::Speed);
+    }
+
+    #[test]
+    fn test_bot_stakes() {
+        let wagering = Wagering::default();
+        let player = Player::new("Robot".to_string(), 40, 50, 50);
+        let (bot, _script) = script_bot("stakes", "read l; echo '{\"stake\": 20}'; read l; echo '{\"stake\": 21}'; read l", 5000);
+        let bot = Mutex::new(bot);
+        assert_eq!(bot_stake(&bot, &player, &player, &wagering), 20);
+        // Staking more than half of its vitality is refused.
+        assert_eq!(bot_stake(&bot, &player, &player, &wagering), 0);
+    }
+
+    #[test]
+    fn test_spawn_unknown_program() {
+        let result = BotProcess::spawn("/nonexistent/duel_bot", Duration::from_millis(100));
+        assert!(matches!(result, Err(GameError::Bot(_))));
+    }
+}
@@ -0,0 +1,525 @@
+use std::fmt;
+use std::path::Path;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::boss::{BossFight, Phase};
+use crate::cards::Card;
+use crate::classes::{AiSkill, Player, PlayerRes};
+use crate::error::GameError;
+use crate::events::{EventBus, MatchEvents};
+use crate::helper::{counter_at, sample_normal, score_tier, Penalty};
+use crate::i18n::{is_yes, say, tr, Msg};
+use crate::input::prompt;
+use crate::json_file;
+use crate::modifiers::{Modifier, RoundRules};
+use crate::ruleset::Ruleset;
+use crate::turns_helper::{play_round_with, player_summary, MIN_STOP_DELAY_MS};
+
+/// Default file the campaign progress is saved to.
+pub const DEFAULT_CAMPAIGN_PATH: &str = "duel_game_campaign.json";
+
+/// Condition to face the opponent of a stage. Stages are numbered from 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unlock {
+    /// Open from the start.
+    Start,
+    /// Beat the opponent of a stage.
+    Beat(usize),
+    /// Beat the opponent of a stage without losing a round.
+    Flawless(usize),
+}
+
+/// A stage of the campaign: an opponent, the rules of the match against it,
+/// and what it takes to face it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stage {
+    /// Name of the opponent.
+    pub name: &'static str,
+    pub vitality: u32,
+    pub speed: u32,
+    pub strength: u32,
+    /// How the opponent stops the counter.
+    pub skill: AiSkill,
+    /// Rules of the match against the opponent.
+    pub rules: Ruleset,
+    pub unlock: Unlock,
+    /// Phases of a boss fight, with the rules they add; empty for a regular opponent.
+    pub phases: Vec<Phase>,
+}
+
+impl Stage {
+    /// Creates the opponent of the stage.
+    pub fn opponent(&self) -> Player {
+        Player::new(self.name.to_string(), self.vitality, self.speed, self.strength).with_ai(self.skill)
+    }
+}
+
+/// The campaign's opponents, from the weakest to the strongest. Later opponents
+/// stop closer to the goal and play under rules that reward exact stops more;
+/// the last one is a boss whose rules get harsher as it weakens.
+pub fn ladder() -> Vec<Stage> {
+    let skill = |sigma_ms, bias_ms| AiSkill { sigma_ms, bias_ms };
+    vec![
+        Stage {
+            name: "Timid Tom",
+            vitality: 30,
+            speed: 60,
+            strength: 20,
+            skill: skill(180, 60),
+            rules: Ruleset::with_goal_count(3),
+            unlock: Unlock::Start,
+            phases: Vec::new(),
+        },
+        Stage {
+            name: "Quick Quentin",
+            vitality: 40,
+            speed: 35,
+            strength: 40,
+            skill: skill(120, -40),
+            rules: Ruleset::default(),
+            unlock: Unlock::Beat(0),
+            phases: Vec::new(),
+        },
+        Stage {
+            name: "Steady Stella",
+            vitality: 50,
+            speed: 45,
+            strength: 50,
+            skill: skill(70, 0),
+            rules: Ruleset { tier_scores: [120, 80, 50, 20, 0], ..Ruleset::default() },
+            unlock: Unlock::Beat(1),
+            phases: Vec::new(),
+        },
+        Stage {
+            name: "The Metronome",
+            vitality: 60,
+            speed: 40,
+            strength: 60,
+            skill: skill(40, 0),
+            rules: Ruleset { tier_scores: [150, 60, 30, 10, 0], ..Ruleset::default() },
+            unlock: Unlock::Flawless(2),
+            phases: Vec::new(),
+        },
+        Stage {
+            name: "Grandmaster Chronos",
+            vitality: 200,
+            speed: 30,
+            strength: 70,
+            skill: skill(25, 0),
+            rules: Ruleset::with_goal_count(7),
+            unlock: Unlock::Beat(3),
+            phases: vec![
+                Phase { threshold_percent: 100, modifiers: Vec::new(), reflect_percent: 0 },
+                Phase { threshold_percent: 66, modifiers: vec![Modifier::DoubleSpeed], reflect_percent: 0 },
+                Phase {
+                    threshold_percent: 33,
+                    modifiers: vec![Modifier::DoubleSpeed, Modifier::HiddenGoals],
+                    reflect_percent: 50,
+                },
+            ],
+        },
+    ]
+}
+
+/// Lets a computer opponent stop a counter incrementing every `speed` ms,
+/// in the direction set by the round.
+pub fn ai_objective<R: Rng + ?Sized>(skill: &AiSkill, goal: u8, speed: u32, round: &RoundRules, rng: &mut R) -> PlayerRes {
+    let ideal_ms = round.shown_counter(goal) as f64 * speed.max(1) as f64 + skill.bias_ms as f64;
+    let elapsed_ms = sample_normal(rng, ideal_ms, skill.sigma_ms as f64).max(MIN_STOP_DELAY_MS as f64);
+    let mut result = counter_at(elapsed_ms as u64, speed);
+    result.counter = round.shown_counter(result.counter);
+    say(Msg::ObjectiveStopped { counter: result.counter, miss: result.miss });
+    result
+}
+
+/// Penalty a computer opponent inflicts after winning a round: it weakens
+/// the higher of its victim's speed and strength.
+pub fn ai_penalty(victim: &Player) -> Penalty {
+    if victim.speed() > victim.strength() {
+        Penalty::Speed
+    } else {
+        Penalty::Strength
+    }
+}
+
+/// Card a computer opponent plays before an objective: the first one in its hand.
+/// Its cards never lose their use by waiting, so it plays them as soon as it can.
+pub fn ai_card(hand: &[Card]) -> Option<usize> {
+    (!hand.is_empty()).then_some(0)
+}
+
+/// Whether a computer opponent pays to play an objective again: only when its stop
+/// is more than 10 away from the goal or came after the counter wrapped.
+pub fn ai_retry(goal: u8, res: &PlayerRes) -> bool {
+    res.miss > 0 || score_tier(goal, res.counter) > 3
+}
+
+/// Result of a campaign match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StageResult {
+    /// Whether the player beat the opponent and is still standing: a round knocking
+    /// both out, e.g. through the damage a boss reflects, is a loss.
+    pub won: bool,
+    /// Number of rounds played.
+    pub rounds: u32,
+    /// Number of rounds the player lost.
+    pub rounds_lost: u32,
+}
+
+/// Plays a match against the opponent of a stage with the rules of `play_round`:
+/// rounds follow each other until a player has no vitality left, and the player wins
+/// only with some vitality left (see `StageResult::won`). A boss changes
+/// the rules of the rounds as its vitality goes down. The events of the match are
+/// emitted through `events`.
+pub fn play_stage<R: Rng + ?Sized>(
+    player: &Player,
+    stage: &Stage,
+    events: &MatchEvents,
+    rng: &mut R,
+) -> Result<StageResult, GameError> {
+    let mut player = player.clone();
+    let mut opponent = stage.opponent();
+    stage.rules.equip(&mut player);
+    stage.rules.equip(&mut opponent);
+    let mut boss = BossFight::new(1, opponent.vitality(), stage.phases.clone());
+    let mut result = StageResult { won: false, rounds: 0, rounds_lost: 0 };
+    while player.vitality() > 0 && opponent.vitality() > 0 {
+        let report = play_round_with(&mut player, &mut opponent, &stage.rules, &mut boss, events, rng)?;
+        result.rounds += 1;
+        if report.winner == Some(1) {
+            result.rounds_lost += 1;
+        }
+    }
+    result.won = opponent.vitality() == 0 && player.vitality() > 0;
+    Ok(result)
+}
+
+/// Results of the player against the opponent of a stage.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StageRecord {
+    pub wins: u32,
+    pub losses: u32,
+    /// Whether the opponent has been beaten without losing a round.
+    pub flawless: bool,
+}
+
+/// Where a stage stands for the player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StageStatus {
+    /// The unlock condition is not met yet.
+    Locked(Unlock),
+    /// The opponent can be faced and has not been beaten yet.
+    Open,
+    /// The opponent has been beaten, flawlessly or not.
+    Beaten { flawless: bool },
+}
+
+/// Progress of the player through the campaign, saved between sessions.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CampaignProgress {
+    /// Record of every stage played, by stage number.
+    pub stages: Vec<StageRecord>,
+}
+
+impl CampaignProgress {
+    /// Reads the progress from a JSON file. A missing file means a new campaign.
+    pub fn load(path: &Path) -> Result<Self, GameError> {
+        json_file::load_or_default(path, |e| CampaignError::InvalidProgress(e).into())
+    }
+
+    /// Writes the progress to a JSON file.
+    pub fn save(&self, path: &Path) -> Result<(), GameError> {
+        json_file::save(self, path, |e| CampaignError::InvalidProgress(e).into())
+    }
+
+    /// Returns the record of a stage.
+    pub fn record(&self, stage: usize) -> StageRecord {
+        self.stages.get(stage).copied().unwrap_or_default()
+    }
+
+    /// Records the result of a match against the opponent of a stage.
+    pub fn add_result(&mut self, stage: usize, result: &StageResult) {
+        if self.stages.len() <= stage {
+            self.stages.resize(stage + 1, StageRecord::default());
+        }
+        let record = &mut self.stages[stage];
+        if result.won {
+            record.wins += 1;
+            record.flawless |= result.rounds_lost == 0;
+        } else {
+            record.losses += 1;
+        }
+    }
+
+    /// Whether a stage's unlock condition is met.
+    pub fn is_unlocked(&self, unlock: Unlock) -> bool {
+        match unlock {
+            Unlock::Start => true,
+            Unlock::Beat(stage) => self.record(stage).wins > 0,
+            Unlock::Flawless(stage) => self.record(stage).flawless,
+        }
+    }
+
+    /// Where a stage of the ladder stands.
+    pub fn status(&self, stage: usize, ladder: &[Stage]) -> StageStatus {
+        let record = self.record(stage);
+        if record.wins > 0 {
+            StageStatus::Beaten { flawless: record.flawless }
+        } else if self.is_unlocked(ladder[stage].unlock) {
+            StageStatus::Open
+        } else {
+            StageStatus::Locked(ladder[stage].unlock)
+        }
+    }
+
+    /// First stage of the ladder that is open and not beaten yet.
+    pub fn next_stage(&self, ladder: &[Stage]) -> Option<usize> {
+        (0..ladder.len()).find(|stage| self.status(*stage, ladder) == StageStatus::Open)
+    }
+
+    /// Checks that a stage exists and can be played.
+    pub fn check_playable(&self, stage: usize, ladder: &[Stage]) -> Result<(), CampaignError> {
+        match ladder.get(stage) {
+            None => Err(CampaignError::UnknownStage { stage, stages: ladder.len() }),
+            Some(s) if !self.is_unlocked(s.unlock) => Err(CampaignError::Locked { stage, unlock: s.unlock }),
+            Some(_) => Ok(()),
+        }
+    }
+}
+
+/// Reasons the campaign cannot go on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CampaignError {
+    /// The stage does not exist.
+    UnknownStage { stage: usize, stages: usize },
+    /// The stage's unlock condition is not met yet.
+    Locked { stage: usize, unlock: Unlock },
+    /// The saved progress could not be written or read back.
+    InvalidProgress(String),
+}
+
+impl fmt::Display for CampaignError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            CampaignError::UnknownStage { stage, stages } => Msg::ErrUnknownStage { stage: stage + 1, stages: *stages },
+            CampaignError::Locked { stage, unlock } => Msg::ErrStageLocked { stage: stage + 1, unlock: *unlock },
+            CampaignError::InvalidProgress(error) => Msg::ErrInvalidProgress { error },
+        };
+        write!(f, "{}", tr(msg))
+    }
+}
+
+impl std::error::Error for CampaignError {}
+
+/// Prints every stage of the ladder with where it stands.
+pub fn print_ladder(path: &Path) -> Result<(), GameError> {
+    let ladder = ladder();
+    let progress = CampaignProgress::load(path)?;
+    for (i, stage) in ladder.iter().enumerate() {
+        let status = progress.status(i, &ladder);
+        println!("{}", tr(Msg::CampaignStageRow { stage: i + 1, name: stage.name, status }));
+    }
+    Ok(())
+}
+
+/// Runs the campaign: the player faces the opponents of the ladder one after
+/// the other, from `start` or the first stage not beaten yet, with the story
+/// told between the matches. The progress is saved after every match. The matches
+/// emit their events through `events`, numbered from 1 in the order played.
+pub fn run_campaign(player: &Player, start: Option<usize>, path: &Path, seed: u64, events: &EventBus) -> Result<(), GameError> {
+    let ladder = ladder();
+    let mut progress = CampaignProgress::load(path)?;
+    let mut stage = match start {
+        Some(stage) => {
+            progress.check_playable(stage, &ladder)?;
+            stage
+        }
+        None => match progress.next_stage(&ladder) {
+            Some(stage) => stage,
+            None => {
+                println!("{}", tr(Msg::CampaignComplete));
+                return Ok(());
+            }
+        },
+    };
+    println!("{}", tr(Msg::Seed(seed)));
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+    let mut match_id = 0;
+    loop {
+        match_id += 1;
+        let current = &ladder[stage];
+        println!();
+        println!("{}", tr(Msg::CampaignStageTitle { stage: stage + 1, name: current.name }));
+        println!("{}", tr(Msg::CampaignIntro { stage }));
+        println!("{}", tr(player_summary(&current.opponent())));
+        println!(
+            "{}",
+            tr(Msg::CampaignRules { goals: current.rules.goal_count, perfect: current.rules.tier_scores[0] })
+        );
+
+        let result = play_stage(player, current, &events.for_match(match_id), &mut rng)?;
+        progress.add_result(stage, &result);
+        progress.save(path)?;
+
+        println!();
+        if result.won {
+            println!("{}", tr(Msg::CampaignWon { name: current.name, rounds: result.rounds }));
+            if result.rounds_lost == 0 {
+                println!("{}", tr(Msg::CampaignFlawless));
+            }
+            println!("{}", tr(Msg::CampaignVictory { stage }));
+        } else {
+            println!("{}", tr(Msg::CampaignLost { name: current.name }));
+        }
+
+        // Go on with the next opponent, or try the current one again while
+        // the next stages are locked.
+        let next = match progress.next_stage(&ladder) {
+            Some(next) => next,
+            None if (0..ladder.len()).all(|s| progress.record(s).wins > 0) => {
+                println!("{}", tr(Msg::CampaignComplete));
+                return Ok(());
+            }
+            None => {
+                if let Some(locked) = (0..ladder.len()).find(|s| !progress.is_unlocked(ladder[*s].unlock)) {
+                    println!("{}", tr(Msg::CampaignLockedHint { stage: locked + 1, unlock: ladder[locked].unlock }));
+                }
+                stage
+            }
+        };
+        let question = if next == stage { Msg::CampaignRetryPrompt } else { Msg::CampaignNextPrompt };
+        if !is_yes(&prompt(&tr(question))?) {
+            println!("{}", tr(Msg::ThanksForPlaying));
+            return Ok(());
+        }
+        stage = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::match_rng;
+
+    #[test]
+    fn test_ladder_escalates() {
+        let ladder = ladder();
+        assert_eq!(ladder[0].unlock, Unlock::Start);
+        for pair in ladder.windows(2) {
+            assert!(pair[1].vitality > pair[0].vitality);
+            assert!(pair[1].skill.sigma_ms < pair[0].skill.sigma_ms);
+        }
+        // Every stage is unlocked by the one before it.
+        for (i, stage) in ladder.iter().enumerate().skip(1) {
+            assert!(matches!(stage.unlock, Unlock::Beat(s) | Unlock::Flawless(s) if s == i - 1));
+        }
+    }
+
+    #[test]
+    fn test_ai_objective() {
+        let mut rng = match_rng(1, 0);
+        // Without timing error, the opponent stops on the goal, or one tick late with a bias of a tick.
+        let exact = ai_objective(&AiSkill { sigma_ms: 0, bias_ms: 0 }, 30, 40, &RoundRules::default(), &mut rng);
+        assert_eq!((exact.counter, exact.miss), (30, 0));
+        let late = ai_objective(&AiSkill { sigma_ms: 0, bias_ms: 40 }, 30, 40, &RoundRules::default(), &mut rng);
+        assert_eq!(late.counter, 31);
+        // Stops are never faster than a human could react.
+        let early = ai_objective(&AiSkill { sigma_ms: 0, bias_ms: -5000 }, 30, 40, &RoundRules::default(), &mut rng);
+        assert_eq!(early.elapsed_ms, MIN_STOP_DELAY_MS);
+        // A counter running backwards is stopped after as many ticks as it takes to show the goal.
+        let backwards = RoundRules { modifiers: vec![Modifier::CounterBackwards], ..RoundRules::default() };
+        let exact = ai_objective(&AiSkill { sigma_ms: 0, bias_ms: 0 }, 30, 40, &backwards, &mut rng);
+        assert_eq!((exact.counter, exact.miss, exact.elapsed_ms), (30, 0, 70 * 40));
+    }
+
+    #[test]
+    fn test_ai_penalty() {
+        assert_eq!(ai_penalty(&Player::new("A".to_string(), 50, 60, 40)), Penalty::Speed);
+        assert_eq!(ai_penalty(&Player::new("A".to_string(), 50, 40, 60)), Penalty::Strength);
+    }
+
+    #[test]
+    fn test_ai_retry() {
+        let stop = |counter, miss| PlayerRes { counter, miss, ..PlayerRes::default() };
+        assert!(!ai_retry(50, &stop(60, 0)));
+        assert!(ai_retry(50, &stop(61, 0)));
+        assert!(ai_retry(50, &stop(50, 1)));
+    }
+
+    #[test]
+    fn test_ai_card() {
+        assert_eq!(ai_card(&[]), None);
+        let shield = Card { name: "Shield".to_string(), effect: crate::helper::Effect::Shield };
+        assert_eq!(ai_card(&[shield.clone(), shield]), Some(0));
+    }
+
+    #[test]
+    fn test_play_stage() {
+        // In tests, human stops are always perfect: the first opponent stands no chance.
+        let player = Player::new("CampaignTest".to_string(), 50, 50, 50);
+        let result = play_stage(&player, &ladder()[0], &MatchEvents::default(), &mut match_rng(4, 0)).unwrap();
+        assert!(result.won);
+        assert_eq!(result.rounds_lost, 0);
+        assert!(result.rounds > 0);
+    }
+
+    #[test]
+    fn test_boss_stage() {
+        // Perfect stops still beat the boss, through every phase.
+        let stage = ladder().pop().unwrap();
+        assert!(stage.vitality > 100);
+        let player = Player::new("BossTest".to_string(), 100, 50, 100);
+        let result = play_stage(&player, &stage, &MatchEvents::default(), &mut match_rng(8, 0)).unwrap();
+        assert!(result.won);
+        // The damage the boss reflects does not turn the rounds the player won into losses.
+        assert_eq!(result.rounds_lost, 0);
+    }
+
+    #[test]
+    fn test_double_knockout_is_a_loss() {
+        // The opponent sends back all the damage it takes: the player's last hit knocks
+        // both out, which neither unlocks the next stage nor counts as a win.
+        let mut stage = ladder().remove(0);
+        stage.phases = vec![Phase { threshold_percent: 100, modifiers: Vec::new(), reflect_percent: 100 }];
+        let player = Player::new("ReflectTest".to_string(), stage.vitality, 50, 50);
+        let result = play_stage(&player, &stage, &MatchEvents::default(), &mut match_rng(4, 0)).unwrap();
+        assert!(!result.won);
+    }
+
+    #[test]
+    fn test_progress_unlocks() {
+        let ladder = ladder();
+        let mut progress = CampaignProgress::default();
+        assert_eq!(progress.next_stage(&ladder), Some(0));
+        assert_eq!(progress.status(1, &ladder), StageStatus::Locked(Unlock::Beat(0)));
+        assert_eq!(
+            progress.check_playable(1, &ladder),
+            Err(CampaignError::Locked { stage: 1, unlock: Unlock::Beat(0) })
+        );
+        assert_eq!(
+            progress.check_playable(9, &ladder),
+            Err(CampaignError::UnknownStage { stage: 9, stages: ladder.len() })
+        );
+
+        let lost = StageResult { won: false, rounds: 3, rounds_lost: 3 };
+        let won = StageResult { won: true, rounds: 4, rounds_lost: 1 };
+        progress.add_result(0, &lost);
+        assert_eq!(progress.next_stage(&ladder), Some(0));
+        progress.add_result(0, &won);
+        progress.add_result(1, &won);
+        progress.add_result(2, &won);
+        assert_eq!(progress.record(0), StageRecord { wins: 1, losses: 1, flawless: false });
+        assert_eq!(progress.status(2, &ladder), StageStatus::Beaten { flawless: false });
+        // The fourth opponent only faces those who beat the third without losing a round.
+        assert_eq!(progress.next_stage(&ladder), None);
+        progress.add_result(2, &StageResult { won: true, rounds: 3, rounds_lost: 0 });
+        assert_eq!(progress.status(2, &ladder), StageStatus::Beaten { flawless: true });
+        assert_eq!(progress.next_stage(&ladder), Some(3));
+    }
+}
@@ -0,0 +1,183 @@
+use std::fs;
+use std::path::Path;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::error::GameError;
+use crate::helper::Effect;
+
+/// Number of cards a player can hold when the deck does not say.
+pub const DEFAULT_HAND_SIZE: usize = 3;
+
+/// Largest hand a deck may allow.
+pub const MAX_HAND_SIZE: usize = 10;
+
+/// Largest amount of a stat a card may lower or steal.
+pub const MAX_CARD_AMOUNT: u32 = 100;
+
+/// Largest share a card may slow the counter down by, in percent.
+pub const MAX_SLOW_PERCENT: u32 = 400;
+
+/// An ability card, played by its owner before one of their objectives.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Card {
+    /// Name of the card, as written in the deck file.
+    pub name: String,
+    /// What playing the card does.
+    #[serde(flatten)]
+    pub effect: Effect,
+}
+
+/// A player's ability cards: those left to draw and those in hand.
+/// Decks are read from JSON files such as `decks/standard.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Deck {
+    /// Cards left to draw.
+    pub cards: Vec<Card>,
+    /// Cards drawn and not played yet.
+    #[serde(default)]
+    pub hand: Vec<Card>,
+    /// Maximum number of cards in hand: nothing is drawn while the hand is full.
+    #[serde(default = "default_hand_size")]
+    pub hand_size: usize,
+}
+
+fn default_hand_size() -> usize {
+    DEFAULT_HAND_SIZE
+}
+
+impl Default for Deck {
+    /// An empty deck, with the default hand size.
+    fn default() -> Self {
+        Deck {
+            cards: Vec::new(),
+            hand: Vec::new(),
+            hand_size: DEFAULT_HAND_SIZE,
+        }
+    }
+}
+
+impl Deck {
+    /// Reads a deck from a JSON file. Hand sizes and card effects beyond the
+    /// `MAX_*` bounds are refused.
+    pub fn load(path: &Path) -> Result<Self, GameError> {
+        let invalid = |e: String| GameError::InvalidDeck(format!("{}: {}", path.display(), e));
+        let json = fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+        let deck: Deck = serde_json::from_str(&json).map_err(|e| invalid(e.to_string()))?;
+        deck.check().map_err(invalid)?;
+        Ok(deck)
+    }
+
+    /// Checks the hand size and the effects of the cards against the `MAX_*` bounds.
+    fn check(&self) -> Result<(), String> {
+        if self.hand_size > MAX_HAND_SIZE {
+            return Err(format!("hand_size {} is over {}", self.hand_size, MAX_HAND_SIZE));
+        }
+        for card in self.cards.iter().chain(&self.hand) {
+            match card.effect {
+                Effect::LowerStat { amount, .. } | Effect::StealStat { amount, .. } if amount > MAX_CARD_AMOUNT => {
+                    return Err(format!("{}: amount {} is over {}", card.name, amount, MAX_CARD_AMOUNT));
+                }
+                Effect::SlowCounter { percent } if percent > MAX_SLOW_PERCENT => {
+                    return Err(format!("{}: percent {} is over {}", card.name, percent, MAX_SLOW_PERCENT));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the player has no card left, in hand or to draw.
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty() && self.hand.is_empty()
+    }
+
+    /// Draws a random card into the hand, unless the hand is full or nothing is left to draw.
+    /// `rng` is left untouched when no card is drawn, so that matches played without
+    /// cards replay the same goals for a seed.
+    pub fn draw<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Option<&Card> {
+        if self.cards.is_empty() || self.hand.len() >= self.hand_size {
+            return None;
+        }
+        let card = self.cards.swap_remove(rng.gen_range(0..self.cards.len()));
+        self.hand.push(card);
+        self.hand.last()
+    }
+
+    /// Takes a card out of the hand to play it.
+    pub fn play(&mut self, index: usize) -> Option<Card> {
+        (index < self.hand.len()).then(|| self.hand.remove(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classes::Stat;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_standard_deck() {
+        let deck: Deck = serde_json::from_str(include_str!("../decks/standard.json")).unwrap();
+        assert_eq!(deck.cards.len(), 8);
+        assert_eq!(deck.hand_size, 3);
+        assert!(deck.hand.is_empty());
+        let pickpocket = Card {
+            name: "Pickpocket".to_string(),
+            effect: Effect::StealStat { stat: Stat::Strength, amount: 5 },
+        };
+        assert!(deck.cards.contains(&pickpocket));
+    }
+
+    #[test]
+    fn test_draw_and_play() {
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        let mut deck: Deck = serde_json::from_str(
+            r#"{"hand_size": 2, "cards": [{"name": "A", "effect": "shield"}, {"name": "B", "effect": "reroll_goal"}, {"name": "C", "effect": "shield"}]}"#,
+        )
+        .unwrap();
+        assert!(deck.draw(&mut rng).is_some());
+        assert!(deck.draw(&mut rng).is_some());
+        // The hand is full: nothing is drawn and no randomness is consumed.
+        let pos = rng.get_word_pos();
+        assert!(deck.draw(&mut rng).is_none());
+        assert_eq!(rng.get_word_pos(), pos);
+
+        assert_eq!(deck.play(2), None);
+        let played = deck.play(0).unwrap();
+        assert!(!deck.hand.contains(&played));
+        assert_eq!(deck.hand.len(), 1);
+        assert!(deck.draw(&mut rng).is_some());
+        assert_eq!(deck.cards.len(), 0);
+        assert!(!deck.is_empty());
+    }
+
+    #[test]
+    fn test_load_invalid_deck() {
+        let path = std::env::temp_dir().join(format!("duel_game_deck_{}.json", std::process::id()));
+        fs::write(&path, r#"{"cards": [{"name": "A", "effect": "fireball"}]}"#).unwrap();
+        assert!(matches!(Deck::load(&path), Err(GameError::InvalidDeck(_))));
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(Deck::load(&path), Err(GameError::InvalidDeck(_))));
+    }
+
+    #[test]
+    fn test_load_out_of_bounds_deck() {
+        let path = std::env::temp_dir().join(format!("duel_game_bounds_deck_{}.json", std::process::id()));
+        let load = |json: &str| {
+            fs::write(&path, json).unwrap();
+            Deck::load(&path)
+        };
+        let slow = load(r#"{"cards": [{"name": "A", "effect": "slow_counter", "percent": 100000000}]}"#);
+        let steal = load(r#"{"cards": [{"name": "A", "effect": "steal_stat", "stat": "speed", "amount": 101}]}"#);
+        let hand = load(r#"{"hand_size": 11, "cards": []}"#);
+        let standard = load(include_str!("../decks/standard.json"));
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(slow, Err(GameError::InvalidDeck(_))));
+        assert!(matches!(steal, Err(GameError::InvalidDeck(_))));
+        assert!(matches!(hand, Err(GameError::InvalidDeck(_))));
+        assert!(standard.is_ok());
+    }
+}
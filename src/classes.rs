@@ -1,14 +1,140 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::bot::BotProcess;
+use crate::cards::Deck;
+use crate::helper::score_tier;
+use crate::i18n::{tr, Msg};
+use crate::stat_map::{StatKey, StatMap};
+
+/// Stamina of a player at the start of a match.
+pub const DEFAULT_STAMINA: u32 = 3;
+
+/// Lowest speed a player plays with, whatever penalties, cards or modifiers do
+/// during a match: below 10 ms the counter is too fast to follow, and at 0 the
+/// counter thread would spin without sleeping.
+pub const MIN_SPEED: u32 = 10;
+
+/// A core attribute of a player, which points can be spent on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stat {
+    Vitality,
+    Speed,
+    Strength,
+}
+
+impl Stat {
+    /// Every stat, in display order.
+    pub const ALL: [Stat; 3] = [Stat::Vitality, Stat::Speed, Stat::Strength];
+
+    /// Name of the stat in rulesets and save files.
+    pub fn name(self) -> &'static str {
+        match self {
+            Stat::Vitality => "vitality",
+            Stat::Speed => "speed",
+            Stat::Strength => "strength",
+        }
+    }
+}
+
+impl FromStr for Stat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "vitality" => Ok(Stat::Vitality),
+            "speed" => Ok(Stat::Speed),
+            "strength" => Ok(Stat::Strength),
+            other => Err(format!("unknown stat '{}' (expected 'vitality', 'speed' or 'strength')", other)),
+        }
+    }
+}
+
+/// Who plays for a player.
+#[derive(Debug, Clone, Default)]
+pub enum Controller {
+    /// A human at the terminal.
+    #[default]
+    Human,
+    /// An external bot program.
+    Bot(Arc<Mutex<BotProcess>>),
+    /// A computer opponent built into the game.
+    Ai(AiSkill),
+}
+
+/// How a computer opponent stops the counter: it aims for the goal and misses
+/// the ideal moment by a normally distributed error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AiSkill {
+    /// Standard deviation of the timing error, in milliseconds.
+    pub sigma_ms: u32,
+    /// Average timing error, in milliseconds: negative stops early, positive stops late.
+    pub bias_ms: i32,
+}
+
+impl PartialEq for Controller {
+    /// Two bot controllers are equal when they drive the same process.
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Controller::Human, Controller::Human) => true,
+            (Controller::Bot(a), Controller::Bot(b)) => Arc::ptr_eq(a, b),
+            (Controller::Ai(a), Controller::Ai(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Controller {}
+
 /// Structure representing a player in the duel game.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Player {
     /// The player's name.
     pub name: String,
-    /// The player's vitality (health points).
-    pub vitality: u32,
-    /// The player's speed, used to determine the counter increment rate.
-    pub speed: u32,
-    /// The player's strength, which affects score calculation.
-    pub strength: u32,
+    /// The player's stats: vitality (health points), speed (the counter increment
+    /// rate), strength (which affects score calculation), and any extra stat.
+    #[serde(flatten)]
+    pub stats: StatMap,
+    /// The player's stamina, regenerated every round and spent on retries, ability cards,
+    /// slowing the counter down and blocks.
+    #[serde(default = "default_stamina")]
+    pub stamina: u32,
+    /// Who plays for the player. Not saved: a resumed match is played by humans
+    /// unless bots are attached again.
+    #[serde(skip)]
+    pub controller: Controller,
+    /// Effects waiting for the player's next objective or damage.
+    #[serde(default, skip_serializing_if = "Status::is_clear")]
+    pub status: Status,
+    /// The player's ability cards, if any.
+    #[serde(default, skip_serializing_if = "Deck::is_empty")]
+    pub deck: Deck,
+}
+
+fn default_stamina() -> u32 {
+    DEFAULT_STAMINA
+}
+
+/// Effects of ability cards waiting to take place.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Status {
+    /// The next damage taken is absorbed.
+    pub shield: bool,
+    /// The counter of the next objective runs this much slower, in percent.
+    pub slow_percent: u32,
+    /// The goal of the next objective is drawn again.
+    pub reroll_goal: bool,
+}
+
+impl Status {
+    /// Whether no effect is waiting.
+    pub fn is_clear(&self) -> bool {
+        *self == Status::default()
+    }
 }
 
 impl Player {
@@ -16,48 +142,325 @@ impl Player {
     pub fn new(name: String, vitality: u32, speed: u32, strength: u32) -> Self {
         Player {
             name,
-            vitality,
-            speed,
-            strength,
+            stats: Stat::ALL.into_iter().map(StatKey::from).zip([vitality, speed, strength]).collect(),
+            stamina: DEFAULT_STAMINA,
+            controller: Controller::Human,
+            status: Status::default(),
+            deck: Deck::default(),
         }
     }
+
+    /// Lets a bot program play for the player.
+    pub fn with_bot(mut self, bot: BotProcess) -> Self {
+        self.controller = Controller::Bot(Arc::new(Mutex::new(bot)));
+        self
+    }
+
+    /// Lets a computer opponent play for the player.
+    pub fn with_ai(mut self, skill: AiSkill) -> Self {
+        self.controller = Controller::Ai(skill);
+        self
+    }
+
+    /// Gives the player a deck of ability cards.
+    pub fn with_deck(mut self, deck: Deck) -> Self {
+        self.deck = deck;
+        self
+    }
+
+    /// Gives the player a stat, or changes the base value of one of their stats.
+    pub fn with_stat(mut self, key: impl Into<StatKey>, value: u32) -> Self {
+        self.stats.set(key, value);
+        self
+    }
+}
+
+impl Player {
+    /// Returns the value of one of the player's stats, modifiers included.
+    pub fn stat(&self, key: impl Into<StatKey>) -> u32 {
+        self.stats.get(key)
+    }
+
+    /// Returns a mutable reference to the base value of one of the player's stats.
+    pub fn stat_mut(&mut self, key: impl Into<StatKey>) -> &mut u32 {
+        self.stats.base_mut(key)
+    }
+
+    /// Removes up to `amount` from the base value of one of the player's stats,
+    /// without taking speed below `MIN_SPEED`. Returns the amount removed.
+    pub fn lower_stat(&mut self, key: impl Into<StatKey>, amount: u32) -> u32 {
+        let key = key.into();
+        let floor = if key == StatKey::Core(Stat::Speed) { MIN_SPEED } else { 0 };
+        let value = self.stat_mut(key);
+        let removed = amount.min(value.saturating_sub(floor));
+        *value -= removed;
+        removed
+    }
+
+    /// Adds `amount` to the base value of one of the player's stats.
+    pub fn raise_stat(&mut self, key: impl Into<StatKey>, amount: u32) {
+        let value = self.stat_mut(key);
+        *value = value.saturating_add(amount);
+    }
+
+    /// The player's vitality (health points).
+    pub fn vitality(&self) -> u32 {
+        self.stat(Stat::Vitality)
+    }
+
+    /// The player's speed, used to determine the counter increment rate.
+    /// It never goes below `MIN_SPEED`, even when modifiers lower it further.
+    pub fn speed(&self) -> u32 {
+        self.stat(Stat::Speed).max(MIN_SPEED)
+    }
+
+    /// The player's strength, which affects score calculation.
+    pub fn strength(&self) -> u32 {
+        self.stat(Stat::Strength)
+    }
 }
 
 impl Default for Player {
     /// Default values for a player:
     /// name: "Joueur", vitality: 50, speed: 50, strength: 50.
     fn default() -> Self {
-        Player {
-            name: String::from("Joueur"),
-            vitality: 50,
-            speed: 50,
-            strength: 50,
+        Player::new(String::from("Joueur"), 50, 50, 50)
+    }
+}
+
+/// Allowed values of a stat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl StatRange {
+    /// Whether `value` is allowed.
+    pub fn contains(&self, value: u32) -> bool {
+        self.min <= value && value <= self.max
+    }
+}
+
+/// Limits a player's stats must respect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerLimits {
+    pub vitality: StatRange,
+    /// Speed is the counter's increment delay; a player never plays faster
+    /// than `MIN_SPEED`, whatever this range allows.
+    pub speed: StatRange,
+    pub strength: StatRange,
+    /// Maximum total of the three stats.
+    pub budget: u32,
+}
+
+impl PlayerLimits {
+    /// Returns the allowed values of a stat.
+    pub fn range(&self, stat: Stat) -> StatRange {
+        match stat {
+            Stat::Vitality => self.vitality,
+            Stat::Speed => self.speed,
+            Stat::Strength => self.strength,
+        }
+    }
+}
+
+impl Default for PlayerLimits {
+    /// Default limits:
+    /// vitality: 1 to 100, speed: 10 to 100, strength: 0 to 100, budget: 200.
+    fn default() -> Self {
+        PlayerLimits {
+            vitality: StatRange { min: 1, max: 100 },
+            speed: StatRange { min: MIN_SPEED, max: 100 },
+            strength: StatRange { min: 0, max: 100 },
+            budget: 200,
+        }
+    }
+}
+
+/// Reasons a player cannot be built.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlayerError {
+    /// The player has no name.
+    EmptyName,
+    /// A stat is outside its allowed range.
+    OutOfRange { stat: Stat, value: u32, range: StatRange },
+    /// The stats add up to more than the point budget.
+    OverBudget { total: u32, budget: u32 },
+    /// More stat points are spent than the player has earned.
+    NotEnoughPoints { points: u32, available: u32 },
+}
+
+impl fmt::Display for PlayerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            PlayerError::EmptyName => Msg::ErrEmptyName,
+            PlayerError::OutOfRange { stat, value, range } => Msg::ErrStatOutOfRange {
+                stat: *stat,
+                value: *value,
+                min: range.min,
+                max: range.max,
+            },
+            PlayerError::OverBudget { total, budget } => Msg::ErrOverBudget { total: *total, budget: *budget },
+            PlayerError::NotEnoughPoints { points, available } => Msg::ErrNotEnoughPoints {
+                points: *points,
+                available: *available,
+            },
+        };
+        write!(f, "{}", tr(msg))
+    }
+}
+
+impl std::error::Error for PlayerError {}
+
+/// Builds a player whose stats respect `PlayerLimits`.
+/// Stats that are not set take the default player's values.
+#[derive(Debug, Clone, Default)]
+pub struct PlayerBuilder {
+    name: Option<String>,
+    vitality: Option<u32>,
+    speed: Option<u32>,
+    strength: Option<u32>,
+    limits: PlayerLimits,
+}
+
+impl PlayerBuilder {
+    /// Creates a builder with the default limits.
+    pub fn new() -> Self {
+        PlayerBuilder::default()
+    }
+
+    /// Sets the player's name.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Sets one of the player's stats.
+    pub fn stat(mut self, stat: Stat, value: u32) -> Self {
+        *self.slot(stat) = Some(value);
+        self
+    }
+
+    /// Sets the player's vitality.
+    pub fn vitality(self, value: u32) -> Self {
+        self.stat(Stat::Vitality, value)
+    }
+
+    /// Sets the player's speed.
+    pub fn speed(self, value: u32) -> Self {
+        self.stat(Stat::Speed, value)
+    }
+
+    /// Sets the player's strength.
+    pub fn strength(self, value: u32) -> Self {
+        self.stat(Stat::Strength, value)
+    }
+
+    /// Replaces the limits the stats must respect.
+    pub fn limits(mut self, limits: PlayerLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Returns the name set so far, if any.
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the value set so far for a stat, if any.
+    pub fn get_stat(&self, stat: Stat) -> Option<u32> {
+        match stat {
+            Stat::Vitality => self.vitality,
+            Stat::Speed => self.speed,
+            Stat::Strength => self.strength,
+        }
+    }
+
+    /// Returns the limits the stats must respect.
+    pub fn get_limits(&self) -> &PlayerLimits {
+        &self.limits
+    }
+
+    /// Whether the name and every stat have been set.
+    pub fn is_complete(&self) -> bool {
+        self.name.is_some() && Stat::ALL.iter().all(|stat| self.get_stat(*stat).is_some())
+    }
+
+    /// Checks the name, each stat and the total, and creates the player.
+    pub fn build(self) -> Result<Player, PlayerError> {
+        let defaults = Player::default();
+        let name = self.name.clone().unwrap_or(defaults.name.clone());
+        if name.trim().is_empty() {
+            return Err(PlayerError::EmptyName);
+        }
+        let mut total = 0u32;
+        for stat in Stat::ALL {
+            let value = self.get_stat(stat).unwrap_or(defaults.stat(stat));
+            let range = self.limits.range(stat);
+            if !range.contains(value) {
+                return Err(PlayerError::OutOfRange { stat, value, range });
+            }
+            total = total.saturating_add(value);
+        }
+        if total > self.limits.budget {
+            return Err(PlayerError::OverBudget { total, budget: self.limits.budget });
+        }
+        Ok(Player::new(
+            name.trim().to_string(),
+            self.vitality.unwrap_or(defaults.vitality()),
+            self.speed.unwrap_or(defaults.speed()),
+            self.strength.unwrap_or(defaults.strength()),
+        ))
+    }
+
+    /// Returns the field holding a stat.
+    fn slot(&mut self, stat: Stat) -> &mut Option<u32> {
+        match stat {
+            Stat::Vitality => &mut self.vitality,
+            Stat::Speed => &mut self.speed,
+            Stat::Strength => &mut self.strength,
         }
     }
 }
 
 /// Structure representing the result of stopping the counter during an objective.
+#[derive(Debug, Clone)]
 pub struct PlayerRes {
     /// The counter value at the moment of stopping.
     pub counter: u8,
     /// The number of times the counter exceeded 100 and was reset (miss count).
     pub miss: u32,
+    /// The time between starting and stopping the counter, in milliseconds.
+    pub elapsed_ms: u64,
+    /// The number of stops rejected for being too fast to be human.
+    pub rejected: u32,
 }
 
 impl Default for PlayerRes {
     /// Default values for PlayerRes:
-    /// counter: 0, miss: 0.
+    /// counter: 0, miss: 0, elapsed_ms: 0, rejected: 0.
     fn default() -> Self {
         PlayerRes {
             counter: 0,
             miss: 0,
+            elapsed_ms: 0,
+            rejected: 0,
         }
     }
 }
 
+impl PlayerRes {
+    /// Whether the stop is perfect: on the goal, before the counter ever wrapped.
+    pub fn is_perfect(&self, goal: u8) -> bool {
+        self.miss == 0 && score_tier(goal, self.counter) == 1
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::stat_map::{Bonus, StatModifier};
 
     #[test]
     fn test_new_player() {
@@ -67,18 +470,88 @@ mod tests {
         let strength = 70;
         let player = Player::new(name.clone(), vitality, speed, strength);
         assert_eq!(player.name, name);
-        assert_eq!(player.vitality, vitality);
-        assert_eq!(player.speed, speed);
-        assert_eq!(player.strength, strength);
+        assert_eq!(player.vitality(), vitality);
+        assert_eq!(player.speed(), speed);
+        assert_eq!(player.strength(), strength);
+        assert_eq!(player.controller, Controller::Human);
     }
 
     #[test]
     fn test_default_player() {
         let player = Player::default();
         assert_eq!(player.name, "Joueur");
-        assert_eq!(player.vitality, 50);
-        assert_eq!(player.speed, 50);
-        assert_eq!(player.strength, 50);
+        assert_eq!(player.vitality(), 50);
+        assert_eq!(player.speed(), 50);
+        assert_eq!(player.strength(), 50);
+        assert_eq!(player.stamina, DEFAULT_STAMINA);
+    }
+
+    #[test]
+    fn test_player_json() {
+        // Stats are saved next to the other fields, and extra stats come back with them.
+        let player = Player::new("Alice".to_string(), 60, 40, 70).with_stat("armor".parse::<StatKey>().unwrap(), 5);
+        let json = serde_json::to_string(&player).unwrap();
+        assert_eq!(json, r#"{"name":"Alice","vitality":60,"speed":40,"strength":70,"armor":5,"stamina":3}"#);
+        assert_eq!(serde_json::from_str::<Player>(&json).unwrap(), player);
+    }
+
+    #[test]
+    fn test_builder_valid_player() {
+        let player = PlayerBuilder::new().name(" Alice ").vitality(60).speed(40).strength(70).build().unwrap();
+        assert_eq!(player, Player::new("Alice".to_string(), 60, 40, 70));
+        // Stats that are not set take the default values.
+        assert_eq!(PlayerBuilder::new().name("Bob").build().unwrap().speed(), 50);
+    }
+
+    #[test]
+    fn test_builder_errors() {
+        assert_eq!(PlayerBuilder::new().name("  ").build(), Err(PlayerError::EmptyName));
+        assert_eq!(
+            PlayerBuilder::new().name("Alice").speed(0).build(),
+            Err(PlayerError::OutOfRange { stat: Stat::Speed, value: 0, range: StatRange { min: 10, max: 100 } })
+        );
+        assert!(matches!(
+            PlayerBuilder::new().name("Alice").vitality(4_000_000_000).build(),
+            Err(PlayerError::OutOfRange { stat: Stat::Vitality, .. })
+        ));
+        assert_eq!(
+            PlayerBuilder::new().name("Alice").vitality(100).speed(100).strength(100).build(),
+            Err(PlayerError::OverBudget { total: 300, budget: 200 })
+        );
+    }
+
+    #[test]
+    fn test_builder_custom_limits() {
+        let limits = PlayerLimits { budget: 300, ..PlayerLimits::default() };
+        let builder = PlayerBuilder::new().name("Alice").vitality(100).speed(100).strength(100).limits(limits);
+        assert!(builder.is_complete());
+        assert!(builder.build().is_ok());
+        assert!(!PlayerBuilder::new().vitality(10).is_complete());
+    }
+
+    #[test]
+    fn test_speed_stays_above_minimum() {
+        let mut player = Player::new("Alice".to_string(), 50, 12, 50);
+        // A penalty only removes what is left above the minimum.
+        assert_eq!(player.lower_stat(Stat::Speed, 5), 2);
+        assert_eq!(player.lower_stat(Stat::Speed, 5), 0);
+        assert_eq!(player.speed(), MIN_SPEED);
+        // Modifiers cannot take it lower either.
+        player.stats.add_modifier(StatModifier {
+            stat: Stat::Speed.into(),
+            bonus: Bonus::Percent(-100),
+            source: "test".to_string(),
+            rounds: None,
+        });
+        assert_eq!(player.speed(), MIN_SPEED);
+        assert_eq!(Player::new("Bob".to_string(), 50, 0, 50).speed(), MIN_SPEED);
+    }
+
+    #[test]
+    fn test_parse_stat() {
+        assert_eq!("Speed".parse::<Stat>(), Ok(Stat::Speed));
+        assert_eq!("vitality".parse::<Stat>(), Ok(Stat::Vitality));
+        assert!("luck".parse::<Stat>().is_err());
     }
 
     #[test]
@@ -86,5 +559,15 @@ mod tests {
         let res = PlayerRes::default();
         assert_eq!(res.counter, 0);
         assert_eq!(res.miss, 0);
+        assert_eq!(res.elapsed_ms, 0);
+        assert_eq!(res.rejected, 0);
+    }
+
+    #[test]
+    fn test_perfect_stop() {
+        let stop = |counter, miss| PlayerRes { counter, miss, ..PlayerRes::default() };
+        assert!(stop(50, 0).is_perfect(50));
+        assert!(!stop(51, 0).is_perfect(50));
+        assert!(!stop(50, 1).is_perfect(50));
     }
 }
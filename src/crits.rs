@@ -0,0 +1,94 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Rules of critical hits and perfect streaks: a perfect stop may be a critical hit
+/// that raises the damage of the round, and perfect stops in a row within a turn
+/// score more and more.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CritRules {
+    /// Chance of a perfect stop to be a critical hit, in percent.
+    pub chance_percent: u32,
+    /// Damage added by each critical hit of the winner's turn, in percent of the round's damage.
+    pub damage_bonus_percent: u32,
+    /// Score bonus of each perfect stop after the first of a streak, in percent.
+    pub streak_bonus_percent: u32,
+    /// Largest score bonus a streak may give, in percent.
+    pub max_streak_bonus_percent: u32,
+}
+
+impl Default for CritRules {
+    /// Default rules: 10% of perfect stops are critical hits adding 50% to the damage,
+    /// and each perfect stop of a streak adds 10% to the score, up to 50%.
+    fn default() -> Self {
+        CritRules {
+            chance_percent: 10,
+            damage_bonus_percent: 50,
+            streak_bonus_percent: 10,
+            max_streak_bonus_percent: 50,
+        }
+    }
+}
+
+impl CritRules {
+    /// Whether a stop is a critical hit, with `bonus_percent` added to the chance.
+    /// Only perfect stops draw from `rng`, so other stops leave a seeded replay unchanged.
+    pub fn roll<R: Rng + ?Sized>(&self, perfect: bool, bonus_percent: u32, rng: &mut R) -> bool {
+        perfect && rng.gen_range(0..100) < self.chance_percent.saturating_add(bonus_percent)
+    }
+
+    /// Score bonus of the `streak`-th perfect stop in a row, in percent.
+    pub fn streak_bonus(&self, streak: u32) -> u32 {
+        streak
+            .saturating_sub(1)
+            .saturating_mul(self.streak_bonus_percent)
+            .min(self.max_streak_bonus_percent)
+    }
+
+    /// Score of an objective scoring `score` as the `streak`-th perfect stop in a row.
+    pub fn streak_score(&self, score: u32, streak: u32) -> u32 {
+        (score as u64 * (100 + self.streak_bonus(streak)) as u64 / 100) as u32
+    }
+
+    /// Damage of a round won with `crits` critical hits.
+    pub fn damage(&self, damage: u32, crits: u32) -> u32 {
+        let bonus = crits as u64 * self.damage_bonus_percent as u64;
+        (damage as u64 * (100 + bonus) / 100).min(u32::MAX as u64) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_roll() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let always = CritRules { chance_percent: 100, ..CritRules::default() };
+        let never = CritRules { chance_percent: 0, ..CritRules::default() };
+        assert!(always.roll(true, 0, &mut rng));
+        assert!(!never.roll(true, 0, &mut rng));
+        assert!(never.roll(true, 100, &mut rng));
+        // A stop short of perfect leaves the generator untouched.
+        let before = rng.clone();
+        assert!(!always.roll(false, 0, &mut rng));
+        assert_eq!(rng, before);
+    }
+
+    #[test]
+    fn test_streak_score() {
+        let rules = CritRules::default();
+        assert_eq!(rules.streak_score(150, 1), 150);
+        assert_eq!(rules.streak_score(150, 3), 180);
+        assert_eq!(rules.streak_score(150, 10), 225);
+    }
+
+    #[test]
+    fn test_crit_damage() {
+        let rules = CritRules::default();
+        assert_eq!(rules.damage(20, 0), 20);
+        assert_eq!(rules.damage(20, 1), 30);
+        assert_eq!(rules.damage(20, 2), 40);
+    }
+}
@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+use crate::classes::PlayerRes;
+use crate::helper::score_tier;
+
+/// Rules of the block the loser of a round may attempt: one counter stop on a
+/// random goal, where a close stop takes off part of the damage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Defense {
+    /// Share of the damage blocked at each accuracy tier, in percent, from tier 1 to tier 5.
+    /// It is separate from the attack's tier scores.
+    pub tier_block_percent: [u32; 5],
+    /// Stamina spent on each block attempt.
+    pub block_cost: u32,
+}
+
+impl Default for Defense {
+    /// Default defense: a perfect stop blocks all the damage, and the blocked share
+    /// halves with each tier down to nothing at tier 4. Each block costs 1 stamina.
+    fn default() -> Self {
+        Defense {
+            tier_block_percent: [100, 50, 25, 0, 0],
+            block_cost: 1,
+        }
+    }
+}
+
+impl Defense {
+    /// Damage blocked by a stop on `goal`: nothing once the counter has wrapped.
+    pub fn blocked(&self, damage: u32, goal: u8, res: &PlayerRes) -> u32 {
+        if res.miss > 0 {
+            return 0;
+        }
+        let percent = self.tier_block_percent[score_tier(goal, res.counter) as usize - 1];
+        (damage as u64 * percent.min(100) as u64 / 100) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocked_damage() {
+        let defense = Defense::default();
+        let stop = |counter, miss| PlayerRes { counter, miss, ..PlayerRes::default() };
+        assert_eq!(defense.blocked(30, 50, &stop(50, 0)), 30);
+        assert_eq!(defense.blocked(30, 50, &stop(53, 0)), 15);
+        assert_eq!(defense.blocked(30, 50, &stop(42, 0)), 7);
+        assert_eq!(defense.blocked(30, 50, &stop(65, 0)), 0);
+        // A stop after the counter wrapped blocks nothing, even on the goal.
+        assert_eq!(defense.blocked(30, 50, &stop(50, 1)), 0);
+    }
+}
@@ -0,0 +1,454 @@
+use std::fmt;
+use std::time::Instant;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::classes::{Player, PlayerRes, Stat};
+use crate::events::{GameEvent, MatchEvents};
+use crate::helper::{average_score, counter_at, create_goals, round_outcome, split_players, Penalty, RoundOutcome};
+use crate::luck::luck;
+use crate::ruleset::Ruleset;
+use crate::stats::{ObjectiveStat, PlayerStats};
+use crate::turns_helper::{is_human_stop, MIN_STOP_DELAY_MS};
+
+/// Step a duel is waiting for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    /// Waiting for the current player to start the next objective.
+    AwaitingStart,
+    /// The counter of the current objective is running.
+    CounterRunning,
+    /// The winner of the round must choose a penalty for the loser.
+    AwaitingPenalty,
+    /// The match is over.
+    Finished,
+}
+
+/// Error returned when an action is not allowed in the duel's current phase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PhaseError {
+    /// Phase the action requires.
+    pub expected: Phase,
+    /// Phase the duel is in.
+    pub actual: Phase,
+}
+
+impl fmt::Display for PhaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "action not allowed: the duel is {:?}, expected {:?}", self.actual, self.expected)
+    }
+}
+
+impl std::error::Error for PhaseError {}
+
+/// Error returned when a duel cannot be played, or an action is refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuelError {
+    /// The rules have no objective per round: every round would be a tie, and the
+    /// duel would never wait for an action.
+    NoGoals,
+    /// The action is not allowed in the duel's current phase.
+    Phase(PhaseError),
+    /// The counter was stopped faster than a human could react. It keeps running,
+    /// and the stop is counted as rejected.
+    TooFast { elapsed_ms: u64 },
+}
+
+impl fmt::Display for DuelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DuelError::NoGoals => write!(f, "a round needs at least one goal"),
+            DuelError::Phase(e) => write!(f, "{}", e),
+            DuelError::TooFast { elapsed_ms } => write!(
+                f,
+                "stop ignored: {} ms after the start is too fast (at least {} ms), the counter is still running",
+                elapsed_ms, MIN_STOP_DELAY_MS
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DuelError {}
+
+impl From<PhaseError> for DuelError {
+    fn from(e: PhaseError) -> Self {
+        DuelError::Phase(e)
+    }
+}
+
+/// Snapshot of a duel, as exposed to front ends.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DuelState {
+    /// Both players, with their current attributes.
+    pub players: [Player; 2],
+    /// Current round, starting at 1.
+    pub round: u32,
+    /// Index of the player whose turn it is.
+    pub current_player: usize,
+    /// Objectives of the current turn.
+    pub goals: Vec<u8>,
+    /// Index of the next objective to play in `goals`.
+    pub objective: usize,
+    /// Step the duel is waiting for.
+    pub phase: Phase,
+    /// Outcome of the last completed round, if any.
+    pub last_outcome: Option<RoundOutcome>,
+    /// Index of the winner once the match is over.
+    pub winner: Option<usize>,
+    /// Statistics of every objective played so far.
+    pub stats: [PlayerStats; 2],
+}
+
+/// A duel driven one action at a time, for front ends that do not run the counter
+/// themselves (e.g. the HTTP API). It follows the same rules as `play_round`,
+/// but the counter value is computed from the time elapsed between the start and
+/// the stop of an objective, so the caller owning the clock is authoritative.
+#[derive(Debug, Clone)]
+pub struct Duel {
+    players: [Player; 2],
+    rules: Ruleset,
+    rng: ChaCha8Rng,
+    phase: Phase,
+    round: u32,
+    current: usize,
+    goals: Vec<u8>,
+    objective: usize,
+    total_scores: [u32; 2],
+    avg_scores: [u32; 2],
+    started_at: Option<Instant>,
+    rejected: u32,
+    stats: [PlayerStats; 2],
+    last_outcome: Option<RoundOutcome>,
+    winner: Option<usize>,
+    events: MatchEvents,
+}
+
+impl Duel {
+    /// Creates a duel and starts its first round. Its events are emitted through `events`.
+    /// The rules must have at least one objective per round.
+    pub fn new(mut p1: Player, mut p2: Player, rules: Ruleset, seed: u64, events: MatchEvents) -> Result<Self, DuelError> {
+        if rules.goal_count == 0 {
+            return Err(DuelError::NoGoals);
+        }
+        rules.equip(&mut p1);
+        rules.equip(&mut p2);
+        let mut duel = Duel {
+            players: [p1, p2],
+            rules,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            phase: Phase::AwaitingStart,
+            round: 0,
+            current: 0,
+            goals: Vec::new(),
+            objective: 0,
+            total_scores: [0, 0],
+            avg_scores: [0, 0],
+            started_at: None,
+            rejected: 0,
+            stats: [PlayerStats::default(), PlayerStats::default()],
+            last_outcome: None,
+            winner: None,
+            events,
+        };
+        duel.next_round();
+        Ok(duel)
+    }
+
+    /// Returns the step the duel is waiting for.
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// Returns a snapshot of the duel.
+    pub fn state(&self) -> DuelState {
+        DuelState {
+            players: self.players.clone(),
+            round: self.round,
+            current_player: self.current,
+            goals: self.goals.clone(),
+            objective: self.objective,
+            phase: self.phase,
+            last_outcome: self.last_outcome,
+            winner: self.winner,
+            stats: self.stats.clone(),
+        }
+    }
+
+    /// Starts the counter of the current player's next objective at time `now`.
+    /// Returns the objective's goal.
+    pub fn start_objective(&mut self, now: Instant) -> Result<u8, PhaseError> {
+        self.expect(Phase::AwaitingStart)?;
+        let goal = self.goals[self.objective];
+        self.started_at = Some(now);
+        self.rejected = 0;
+        self.phase = Phase::CounterRunning;
+        self.events.emit(GameEvent::ObjectiveStarted {
+            player: self.players[self.current].name.clone(),
+            index: self.objective,
+            goal,
+        });
+        Ok(goal)
+    }
+
+    /// Stops the running counter at time `now` and scores the objective. Stops arriving
+    /// sooner than `MIN_STOP_DELAY_MS` after the start are refused, as on the terminal.
+    pub fn stop_objective(&mut self, now: Instant) -> Result<ObjectiveStat, DuelError> {
+        self.expect(Phase::CounterRunning)?;
+        let started_at = self.started_at.unwrap_or(now);
+        let elapsed_ms = now.saturating_duration_since(started_at).as_millis() as u64;
+        if !is_human_stop(elapsed_ms) {
+            self.rejected += 1;
+            return Err(DuelError::TooFast { elapsed_ms });
+        }
+        self.started_at = None;
+
+        let player = &self.players[self.current];
+        let goal = self.goals[self.objective];
+        let mut result = PlayerRes { rejected: self.rejected, ..counter_at(elapsed_ms, player.speed()) };
+        self.rules.luck.forgive_miss(luck(player), &mut result, &mut self.rng);
+        let score = self.rules.score(goal, &result, player.strength());
+        let stat = ObjectiveStat::new(goal, &result, player.speed(), score);
+        self.events.emit(GameEvent::ObjectiveStopped {
+            player: player.name.clone(),
+            goal,
+            counter: result.counter,
+            miss: result.miss,
+            score,
+        });
+
+        self.total_scores[self.current] += score;
+        self.stats[self.current].record(stat.clone());
+        self.objective += 1;
+        self.phase = Phase::AwaitingStart;
+        self.advance();
+        Ok(stat)
+    }
+
+    /// Applies the penalty chosen by the winner of the round to the loser,
+    /// then starts the next round.
+    pub fn choose_penalty(&mut self, penalty: Penalty) -> Result<(), PhaseError> {
+        self.expect(Phase::AwaitingPenalty)?;
+        if let Some(loser) = self.last_outcome.and_then(|outcome| outcome.loser()) {
+            let (victim, winner) = split_players(&mut self.players, loser);
+            penalty.apply(winner, victim);
+            self.events.emit(GameEvent::PenaltyApplied {
+                player: victim.name.clone(),
+                penalty,
+            });
+        }
+        self.next_round();
+        Ok(())
+    }
+
+    /// Checks that the duel is in the phase an action requires.
+    fn expect(&self, expected: Phase) -> Result<(), PhaseError> {
+        if self.phase == expected {
+            Ok(())
+        } else {
+            Err(PhaseError { expected, actual: self.phase })
+        }
+    }
+
+    /// Starts a new round, and plays on until the duel waits for an action.
+    fn next_round(&mut self) {
+        self.start_round();
+        self.advance();
+    }
+
+    /// Starts a new round with the first player's turn.
+    fn start_round(&mut self) {
+        self.round += 1;
+        self.total_scores = [0, 0];
+        self.avg_scores = [0, 0];
+        self.phase = Phase::AwaitingStart;
+        self.events.emit(GameEvent::RoundStarted);
+        self.start_turn(0);
+    }
+
+    /// Starts a player's turn by drawing its objectives.
+    fn start_turn(&mut self, player: usize) {
+        self.current = player;
+        self.goals = create_goals(self.rules.goal_count, &mut self.rng);
+        self.rules.luck.pull_goals(luck(&self.players[player]), &mut self.goals);
+        self.objective = 0;
+        self.events.emit(GameEvent::TurnStarted {
+            player: self.players[player].name.clone(),
+            goals: self.goals.clone(),
+        });
+    }
+
+    /// Ends the turns whose objectives have all been played, and the rounds they
+    /// complete, until the duel waits for an action. Tied rounds are followed by the
+    /// next one in this loop rather than by recursion.
+    fn advance(&mut self) {
+        while self.phase == Phase::AwaitingStart && self.objective >= self.goals.len() {
+            let avg_score = average_score(self.total_scores[self.current], self.rules.goal_count);
+            self.avg_scores[self.current] = avg_score;
+            self.events.emit(GameEvent::TurnEnded {
+                player: self.players[self.current].name.clone(),
+                avg_score,
+            });
+            if self.current == 0 {
+                self.start_turn(1);
+            } else if self.end_round() {
+                self.start_round();
+            }
+        }
+    }
+
+    /// Compares the turn scores of both players and applies the damage.
+    /// Returns whether the round was a tie, to be followed by the next one at once.
+    fn end_round(&mut self) -> bool {
+        for player in &mut self.players {
+            player.stats.tick();
+        }
+        let outcome = round_outcome(self.avg_scores);
+        self.last_outcome = Some(outcome);
+        let RoundOutcome::Won { winner, damage } = outcome else {
+            self.events.emit(GameEvent::RoundTied);
+            return true;
+        };
+
+        let [winner_player, loser_player] = if winner == 0 {
+            [&self.players[0], &self.players[1]]
+        } else {
+            [&self.players[1], &self.players[0]]
+        };
+        self.events.emit(GameEvent::RoundWon {
+            winner: winner_player.name.clone(),
+            loser: loser_player.name.clone(),
+            damage,
+        });
+        let loser = &mut self.players[1 - winner];
+        loser.lower_stat(Stat::Vitality, damage);
+        self.events.emit(GameEvent::VitalityChanged {
+            player: loser.name.clone(),
+            vitality: loser.vitality(),
+        });
+
+        if loser.vitality() == 0 {
+            self.phase = Phase::Finished;
+            self.winner = Some(winner);
+            self.events.emit(GameEvent::MatchEnded);
+        } else {
+            self.phase = Phase::AwaitingPenalty;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Plays the current objective with a perfect stop.
+    fn perfect_stop(duel: &mut Duel, now: Instant) -> ObjectiveStat {
+        let goal = duel.start_objective(now).unwrap();
+        let speed = duel.state().players[duel.state().current_player].speed();
+        let stop = now + Duration::from_millis(goal as u64 * speed as u64);
+        duel.stop_objective(stop).unwrap()
+    }
+
+    #[test]
+    fn test_stop_uses_elapsed_time() {
+        let p1 = Player::new("Alice".to_string(), 50, 50, 10);
+        let p2 = Player::new("Bob".to_string(), 50, 50, 10);
+        let mut duel = Duel::new(p1, p2, Ruleset::with_goal_count(2), 1, MatchEvents::default()).unwrap();
+        let now = Instant::now();
+        let goal = duel.start_objective(now).unwrap();
+        // Stopping 5 ticks after the goal is a tier 2 hit.
+        let stat = duel.stop_objective(now + Duration::from_millis((goal as u64 + 5) * 50)).unwrap();
+        assert_eq!(stat.goal, goal);
+        assert_eq!(stat.tier, 2);
+        assert_eq!(duel.state().objective, 1);
+    }
+
+    #[test]
+    fn test_actions_require_the_right_phase() {
+        let p1 = Player::new("Alice".to_string(), 50, 50, 10);
+        let p2 = Player::new("Bob".to_string(), 50, 50, 10);
+        let mut duel = Duel::new(p1, p2, Ruleset::with_goal_count(1), 1, MatchEvents::default()).unwrap();
+        let err = duel.stop_objective(Instant::now()).unwrap_err();
+        assert_eq!(err, DuelError::Phase(PhaseError { expected: Phase::CounterRunning, actual: Phase::AwaitingStart }));
+        assert!(duel.choose_penalty(Penalty::Speed).is_err());
+    }
+
+    #[test]
+    fn test_full_round_with_penalty() {
+        // Both players stop perfectly, so the stronger one wins by the strength difference.
+        let p1 = Player::new("Alice".to_string(), 50, 50, 20);
+        let p2 = Player::new("Bob".to_string(), 50, 50, 10);
+        let mut duel = Duel::new(p1, p2, Ruleset::with_goal_count(2), 3, MatchEvents::default()).unwrap();
+        let now = Instant::now();
+        for _ in 0..4 {
+            perfect_stop(&mut duel, now);
+        }
+        let state = duel.state();
+        assert_eq!(state.phase, Phase::AwaitingPenalty);
+        assert_eq!(state.last_outcome, Some(RoundOutcome::Won { winner: 0, damage: 10 }));
+        assert_eq!(state.players[1].vitality(), 40);
+
+        duel.choose_penalty(Penalty::Strength).unwrap();
+        let state = duel.state();
+        assert_eq!(state.players[1].strength(), 5);
+        assert_eq!(state.round, 2);
+        assert_eq!(state.current_player, 0);
+        assert_eq!(state.phase, Phase::AwaitingStart);
+    }
+
+    #[test]
+    fn test_tied_round_starts_the_next_one() {
+        let p1 = Player::new("Alice".to_string(), 50, 50, 10);
+        let p2 = Player::new("Bob".to_string(), 50, 50, 10);
+        let mut duel = Duel::new(p1, p2, Ruleset::with_goal_count(1), 3, MatchEvents::default()).unwrap();
+        let now = Instant::now();
+        perfect_stop(&mut duel, now);
+        perfect_stop(&mut duel, now);
+        let state = duel.state();
+        assert_eq!(state.last_outcome, Some(RoundOutcome::Tie));
+        assert_eq!(state.round, 2);
+        assert_eq!(state.phase, Phase::AwaitingStart);
+        assert_eq!(state.current_player, 0);
+    }
+
+    #[test]
+    fn test_match_finishes_when_vitality_runs_out() {
+        let p1 = Player::new("Alice".to_string(), 50, 50, 40);
+        let p2 = Player::new("Bob".to_string(), 5, 50, 0);
+        let mut duel = Duel::new(p1, p2, Ruleset::with_goal_count(1), 3, MatchEvents::default()).unwrap();
+        let now = Instant::now();
+        perfect_stop(&mut duel, now);
+        perfect_stop(&mut duel, now);
+        assert_eq!(duel.phase(), Phase::Finished);
+        assert_eq!(duel.state().winner, Some(0));
+        assert!(duel.start_objective(now).is_err());
+    }
+
+    #[test]
+    fn test_too_fast_stops_are_rejected() {
+        let p1 = Player::new("Alice".to_string(), 50, 50, 10);
+        let p2 = Player::new("Bob".to_string(), 50, 50, 10);
+        let mut duel = Duel::new(p1, p2, Ruleset::with_goal_count(2), 1, MatchEvents::default()).unwrap();
+        let now = Instant::now();
+        duel.start_objective(now).unwrap();
+        assert_eq!(duel.stop_objective(now), Err(DuelError::TooFast { elapsed_ms: 0 }));
+        let early = now + Duration::from_millis(MIN_STOP_DELAY_MS - 1);
+        assert!(duel.stop_objective(early).is_err());
+        // The counter keeps running from the start, and the stop counts the rejected ones.
+        assert_eq!(duel.phase(), Phase::CounterRunning);
+        let stat = duel.stop_objective(now + Duration::from_millis(MIN_STOP_DELAY_MS)).unwrap();
+        assert_eq!((stat.rejected, stat.elapsed_ms), (2, MIN_STOP_DELAY_MS));
+    }
+
+    #[test]
+    fn test_duel_needs_goals() {
+        let p1 = Player::new("Alice".to_string(), 50, 50, 10);
+        let p2 = Player::new("Bob".to_string(), 50, 50, 10);
+        let duel = Duel::new(p1, p2, Ruleset::with_goal_count(0), 1, MatchEvents::default());
+        assert_eq!(duel.unwrap_err(), DuelError::NoGoals);
+    }
+}
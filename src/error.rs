@@ -0,0 +1,126 @@
+use std::fmt;
+use std::io;
+
+use crate::campaign::CampaignError;
+use crate::classes::PlayerError;
+use crate::i18n::{tr, Msg};
+
+/// Errors that can interrupt a match.
+#[derive(Debug)]
+pub enum GameError {
+    /// Standard input was closed (end of file) while the game was waiting for the player.
+    InputClosed,
+    /// Reading from stdin or writing to stdout failed.
+    Io(io::Error),
+    /// The counter thread stopped unexpectedly.
+    CounterThread,
+    /// A saved match could not be written or read back.
+    InvalidSave(String),
+    /// A bot program could not be started.
+    Bot(String),
+    /// A player's name or stats are not allowed.
+    InvalidPlayer(PlayerError),
+    /// The players' profiles could not be written or read back.
+    InvalidProfiles(String),
+    /// The campaign cannot go on.
+    Campaign(CampaignError),
+    /// A deck of ability cards could not be read.
+    InvalidDeck(String),
+}
+
+impl GameError {
+    /// Exit code of the process when the game ends with this error.
+    /// Closed input ends the match cleanly, with 0; the other errors follow the
+    /// BSD `sysexits.h` conventions where one applies.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            GameError::InputClosed => 0,
+            GameError::Io(_) => 74,
+            GameError::CounterThread => 70,
+            GameError::InvalidSave(_) => 65,
+            GameError::Bot(_) => 69,
+            GameError::InvalidPlayer(_) => 64,
+            GameError::InvalidProfiles(_) => 78,
+            GameError::Campaign(_) => 77,
+            GameError::InvalidDeck(_) => 66,
+        }
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::InputClosed => write!(f, "{}", tr(Msg::ErrInputClosed)),
+            GameError::Io(e) => write!(f, "{}", tr(Msg::ErrIo { error: &e.to_string() })),
+            GameError::CounterThread => write!(f, "{}", tr(Msg::ErrCounterThread)),
+            GameError::InvalidSave(e) => write!(f, "{}", tr(Msg::ErrInvalidSave { error: e })),
+            GameError::Bot(e) => write!(f, "{}", tr(Msg::ErrBot { error: e })),
+            GameError::InvalidPlayer(e) => write!(f, "{}", e),
+            GameError::InvalidProfiles(e) => write!(f, "{}", tr(Msg::ErrInvalidProfiles { error: e })),
+            GameError::Campaign(e) => write!(f, "{}", e),
+            GameError::InvalidDeck(e) => write!(f, "{}", tr(Msg::ErrInvalidDeck { error: e })),
+        }
+    }
+}
+
+impl std::error::Error for GameError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GameError::Io(e) => Some(e),
+            GameError::InvalidPlayer(e) => Some(e),
+            GameError::Campaign(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for GameError {
+    fn from(e: io::Error) -> Self {
+        GameError::Io(e)
+    }
+}
+
+impl From<CampaignError> for GameError {
+    fn from(e: CampaignError) -> Self {
+        GameError::Campaign(e)
+    }
+}
+
+impl From<PlayerError> for GameError {
+    fn from(e: PlayerError) -> Self {
+        GameError::InvalidPlayer(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes_are_distinct() {
+        // Closed input is not a failure, and must not be mistaken for a usage error (2).
+        assert_eq!(GameError::InputClosed.exit_code(), 0);
+        let errors = [
+            GameError::Io(io::Error::from(io::ErrorKind::BrokenPipe)),
+            GameError::CounterThread,
+            GameError::InvalidSave("truncated".to_string()),
+            GameError::Bot("missing".to_string()),
+            GameError::InvalidPlayer(PlayerError::EmptyName),
+            GameError::InvalidProfiles("truncated".to_string()),
+            GameError::Campaign(CampaignError::UnknownStage { stage: 9, stages: 5 }),
+            GameError::InvalidDeck("unknown effect".to_string()),
+        ];
+        let mut codes: Vec<u8> = errors.iter().map(GameError::exit_code).collect();
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), errors.len());
+        assert!(codes.iter().all(|c| *c != 0 && *c != 2));
+    }
+
+    #[test]
+    fn test_from_io_error() {
+        let err: GameError = io::Error::from(io::ErrorKind::BrokenPipe).into();
+        assert!(matches!(err, GameError::Io(_)));
+        assert!(err.to_string().starts_with("I/O error"));
+    }
+}
@@ -0,0 +1,166 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+use serde::Serialize;
+
+use crate::helper::Penalty;
+
+/// Something that happened during a match.
+/// Events are emitted by the match engine as the game progresses, so that
+/// front ends other than the terminal can follow a match.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum GameEvent {
+    /// A new round has started.
+    RoundStarted,
+    /// A player's turn has started with the given objectives, or none when they are
+    /// hidden: each one is then only known from `ObjectiveStarted`.
+    TurnStarted { player: String, goals: Vec<u8> },
+    /// The counter of an objective has been started.
+    ObjectiveStarted { player: String, index: usize, goal: u8 },
+    /// The running counter has been incremented.
+    CounterTick { counter: u8, miss: u32 },
+    /// A player has stopped the counter of an objective.
+    ObjectiveStopped { player: String, goal: u8, counter: u8, miss: u32, score: u32 },
+    /// A player's turn has ended with the given average score.
+    TurnEnded { player: String, avg_score: u32 },
+    /// A round has been won: the loser takes `damage` vitality points.
+    RoundWon { winner: String, loser: String, damage: u32 },
+    /// A round ended in a tie.
+    RoundTied,
+    /// A player's vitality has changed.
+    VitalityChanged { player: String, vitality: u32 },
+    /// A penalty has been applied to a player.
+    PenaltyApplied { player: String, penalty: Penalty },
+    /// A player has played an ability card.
+    CardPlayed { player: String, card: String },
+    /// A player has staked vitality on the round.
+    StakePlaced { player: String, stake: u32 },
+    /// The loser of a round has tried to block `blocked` points of its damage by stopping the counter at `goal`.
+    BlockAttempted { player: String, goal: u8, blocked: u32 },
+    /// The match is over.
+    MatchEnded,
+}
+
+/// An event, with the id of the match it happened in, so that the events of
+/// matches played at the same time can be told apart.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MatchEvent {
+    /// Id of the match, given by whoever started it (e.g. the HTTP API).
+    pub match_id: u64,
+    #[serde(flatten)]
+    pub event: GameEvent,
+}
+
+/// Receiver of the events emitted by the match engine.
+pub trait EventSink: Send + Sync {
+    /// Called for every emitted event, from the thread emitting it.
+    fn on_event(&self, event: &MatchEvent);
+}
+
+impl<F: Fn(&MatchEvent) + Send + Sync> EventSink for F {
+    fn on_event(&self, event: &MatchEvent) {
+        self(event)
+    }
+}
+
+/// Token returned by `EventBus::subscribe`, to unsubscribe the sink later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Subscription(u64);
+
+/// Sinks registered on a bus, with their subscription.
+type Sinks = Arc<RwLock<Vec<(Subscription, Arc<dyn EventSink>)>>>;
+
+/// Sinks receiving the events of the matches reporting to the bus.
+/// Cloning the bus shares its sinks.
+#[derive(Clone, Default)]
+pub struct EventBus {
+    sinks: Sinks,
+    next_id: Arc<AtomicU64>,
+}
+
+impl EventBus {
+    /// Registers a sink that will receive every event emitted on the bus from now on.
+    pub fn subscribe(&self, sink: Arc<dyn EventSink>) -> Subscription {
+        let subscription = Subscription(self.next_id.fetch_add(1, Ordering::Relaxed));
+        self.sinks.write().unwrap_or_else(|e| e.into_inner()).push((subscription, sink));
+        subscription
+    }
+
+    /// Stops sending events to a sink.
+    pub fn unsubscribe(&self, subscription: Subscription) {
+        self.sinks.write().unwrap_or_else(|e| e.into_inner()).retain(|(s, _)| *s != subscription);
+    }
+
+    /// Returns the handle a match emits its events through.
+    pub fn for_match(&self, match_id: u64) -> MatchEvents {
+        MatchEvents { match_id, bus: self.clone() }
+    }
+
+    /// Sends an event to every sink of the bus.
+    fn emit(&self, event: &MatchEvent) {
+        let sinks = self.sinks.read().unwrap_or_else(|e| e.into_inner());
+        for (_, sink) in sinks.iter() {
+            sink.on_event(event);
+        }
+    }
+}
+
+impl fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sinks = self.sinks.read().unwrap_or_else(|e| e.into_inner()).len();
+        f.debug_struct("EventBus").field("sinks", &sinks).finish()
+    }
+}
+
+/// Handle a match emits its events through: they are tagged with the match's id
+/// and sent to the sinks of its bus. The default handle sends them nowhere.
+#[derive(Debug, Clone, Default)]
+pub struct MatchEvents {
+    match_id: u64,
+    bus: EventBus,
+}
+
+impl MatchEvents {
+    /// Returns the id of the match.
+    pub fn match_id(&self) -> u64 {
+        self.match_id
+    }
+
+    /// Sends an event of the match to every sink of the bus.
+    pub fn emit(&self, event: GameEvent) {
+        self.bus.emit(&MatchEvent { match_id: self.match_id, event });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_subscribe_and_emit() {
+        let bus = EventBus::default();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let received_sink = Arc::clone(&received);
+        let subscription = bus.subscribe(Arc::new(move |event: &MatchEvent| {
+            received_sink.lock().unwrap().push(event.clone());
+        }));
+
+        let event = GameEvent::TurnEnded { player: "Alice".to_string(), avg_score: 42 };
+        bus.for_match(7).emit(event.clone());
+        bus.unsubscribe(subscription);
+        bus.for_match(8).emit(event.clone());
+        // A handle without a bus sends its events nowhere.
+        MatchEvents::default().emit(event.clone());
+        assert_eq!(*received.lock().unwrap(), vec![MatchEvent { match_id: 7, event }]);
+    }
+
+    #[test]
+    fn test_match_event_serialization() {
+        let event = MatchEvent { match_id: 3, event: GameEvent::CounterTick { counter: 42, miss: 1 } };
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(json, r#"{"match_id":3,"event":"counter_tick","counter":42,"miss":1}"#);
+    }
+}
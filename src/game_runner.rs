@@ -1,57 +1,99 @@
-use std::io::{self, Write};
-use crate::{classes::Player, turns_helper::play_round};
+use crate::{
+    autosave::{checkpoint, clear_checkpoint, MatchState},
+    classes::Player,
+    error::GameError,
+    events::{GameEvent, MatchEvents},
+    i18n::{is_yes, tr, Msg},
+    input::prompt,
+    ruleset::Ruleset,
+    turns_helper::{play_round, player_summary},
+};
 
 /// Runs the game by initializing players and managing the rounds and turns.
-pub fn run_game(p1: Player, p2: Player, goal_count: u8) {
-    println!("Players have been initialized:");
-    println!("{}: {:?}", p1.name, p1);
-    println!("{}: {:?}", p2.name, p2);
+/// Objectives are drawn from a generator seeded with `seed`, and the events of
+/// the match are emitted through `events`.
+/// An accuracy report of each player is printed at the end of the match,
+/// even when the match is cut short by an error such as closed input.
+/// Returns the final state of the match.
+pub fn run_game(p1: Player, p2: Player, rules: &Ruleset, seed: u64, events: &MatchEvents) -> Result<MatchState, GameError> {
+    resume_game(MatchState::new(p1, p2, rules.clone(), seed), events)
+}
+
+/// Runs a match from a saved state, starting with the round that was in progress.
+/// The state is checkpointed at the start of every round so that an interrupted
+/// match can be saved and resumed. Returns the final state of the match.
+pub fn resume_game(mut state: MatchState, events: &MatchEvents) -> Result<MatchState, GameError> {
+    let [p1, p2] = &state.players;
+    println!("{}", tr(Msg::PlayersInitialized));
+    println!("{}", tr(player_summary(p1)));
+    println!("{}", tr(player_summary(p2)));
+    println!("{}", tr(Msg::Seed(state.seed)));
+    if state.rounds_played > 0 || !state.abandoned.is_empty() {
+        println!(
+            "{}",
+            tr(Msg::Resuming {
+                round: state.rounds_played + 1,
+                abandoned: state.abandoned.len(),
+            })
+        );
+    }
 
-    let mut turns: bool = true;
-    let mut rounds: bool = true;
+    let mut rng = state.rng();
 
     // Continue running rounds until the game ends.
-    while rounds {
-        // Run individual turns within the current round.
-        while turns {
-            // Clone the players for each turn to preserve their original state.
-            play_round(&mut p1.clone(), &mut p2.clone(), goal_count);
-            turns = re_run_turn();
+    let result = loop {
+        // Remember where the round starts, so that an interrupted match resumes from here.
+        state.rng_word_pos = rng.get_word_pos();
+        checkpoint(&state);
+
+        // Play the round on copies of the players so that an interrupted round is replayed from the same state.
+        let [mut round_p1, mut round_p2] = state.players.clone();
+        let report = match play_round(&mut round_p1, &mut round_p2, &state.rules, events, &mut rng) {
+            Ok(report) => report,
+            Err(e) => break Err(e),
+        };
+
+        // Keep the outcome of the round.
+        let [stats1, stats2] = report.stats;
+        state.damage_dealt[0] += report.damage_dealt[0];
+        state.damage_dealt[1] += report.damage_dealt[1];
+        state.players = [round_p1, round_p2];
+        state.stats[0].extend(stats1);
+        state.stats[1].extend(stats2);
+        state.rounds_played += 1;
+        match re_run_round(&state.players[0], &state.players[1]) {
+            Ok(true) => {}
+            Ok(false) => break Ok(()),
+            Err(e) => break Err(e),
         }
-        rounds = re_run_round(&p1, &p2);
-    }
-}
+    };
 
-/// Prompts the user to decide whether to replay the current turn.
-/// Returns `true` if the user chooses to replay, `false` otherwise.
-fn re_run_turn() -> bool {
-    print!("Replay the current turn? [Y/N]: ");
-    io::stdout().flush().unwrap();
-    let mut input = String::new();
-    io::stdin().read_line(&mut input).unwrap();
-    input.trim().eq_ignore_ascii_case("y")
+    clear_checkpoint();
+    events.emit(GameEvent::MatchEnded);
+    println!();
+    for (player, stats) in state.players.iter().zip(&state.stats) {
+        print!("{}", stats.report(&player.name));
+    }
+    result.map(|()| state)
 }
 
 /// Prompts the user to decide whether to start a new round.
 /// Automatically ends the game if any player has zero vitality.
 /// Returns `true` to start a new round, `false` to end the game.
-fn re_run_round(p1: &Player, p2: &Player) -> bool {
-    print!("Start a new round? [Y/N]: ");
-    io::stdout().flush().unwrap();
-    let mut new_game = String::new();
-    io::stdin().read_line(&mut new_game).unwrap();
+fn re_run_round(p1: &Player, p2: &Player) -> Result<bool, GameError> {
+    let new_game = prompt(&tr(Msg::NewRoundPrompt))?;
 
-    if p1.vitality == 0 || p2.vitality == 0 {
+    if p1.vitality() == 0 || p2.vitality() == 0 {
         // If a player has run out of vitality, end the game.
-        println!("A player has no vitality left. Game over! Sorry, but that's it!");
-        false
-    } else if new_game.trim().eq_ignore_ascii_case("y") {
+        println!("{}", tr(Msg::NoVitalityLeft));
+        Ok(false)
+    } else if is_yes(&new_game) {
         // User opted to start a new round.
-        true
+        Ok(true)
     } else {
         // User chose to stop playing.
-        println!("Thanks for playing!");
-        false
+        println!("{}", tr(Msg::ThanksForPlaying));
+        Ok(false)
     }
 }
 
@@ -61,33 +103,13 @@ mod tests {
     use std::io::Cursor;
     use std::io::BufRead;
 
-    // Helper function to simulate the logic of `re_run_turn` by reading from a provided input.
-    fn simulate_re_run_turn(input: &str) -> bool {
-        let mut cursor = Cursor::new(input);
-        let mut line = String::new();
-        cursor.read_line(&mut line).unwrap();
-        line.trim().eq_ignore_ascii_case("y")
-    }
-
-    #[test]
-    fn test_re_run_turn_yes() {
-        // Simulate user input "y"
-        assert!(simulate_re_run_turn("y\n"));
-    }
-
-    #[test]
-    fn test_re_run_turn_no() {
-        // Simulate user input "n"
-        assert!(!simulate_re_run_turn("n\n"));
-    }
-
     // Helper function to simulate the logic of `re_run_round` by reading from provided input.
     // Note that if either player has zero vitality, the function should return false regardless of input.
     fn simulate_re_run_round(input: &str, p1: &Player, p2: &Player) -> bool {
         let mut cursor = Cursor::new(input);
         let mut line = String::new();
         cursor.read_line(&mut line).unwrap();
-        if p1.vitality == 0 || p2.vitality == 0 {
+        if p1.vitality() == 0 || p2.vitality() == 0 {
             false
         } else {
             line.trim().eq_ignore_ascii_case("y")
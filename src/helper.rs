@@ -1,5 +1,129 @@
-use crate::classes::PlayerRes;
+use crate::classes::{Player, PlayerRes, Stat};
 use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Amount of speed or strength removed by a penalty.
+pub const PENALTY_AMOUNT: u32 = 5;
+
+/// Penalty the winner of a round can inflict on the loser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Penalty {
+    /// Removes `PENALTY_AMOUNT` from the victim's speed.
+    Speed,
+    /// Removes `PENALTY_AMOUNT` from the victim's strength.
+    Strength,
+}
+
+impl Penalty {
+    /// Returns the stat the penalty lowers.
+    pub fn stat(self) -> Stat {
+        match self {
+            Penalty::Speed => Stat::Speed,
+            Penalty::Strength => Stat::Strength,
+        }
+    }
+
+    /// Returns the effect of the penalty.
+    pub fn effect(self) -> Effect {
+        Effect::LowerStat { stat: self.stat(), amount: PENALTY_AMOUNT }
+    }
+
+    /// Applies the penalty chosen by `winner` to the given player.
+    pub fn apply(self, winner: &mut Player, victim: &mut Player) {
+        self.effect().apply(winner, victim);
+    }
+}
+
+/// Change to the players of a duel, caused by a penalty or an ability card.
+/// The player causing the effect is its user, and the other one its target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "effect", rename_all = "snake_case")]
+pub enum Effect {
+    /// Removes `amount` from one of the target's stats.
+    LowerStat { stat: Stat, amount: u32 },
+    /// Moves up to `amount` points of one of the target's stats to the user.
+    StealStat { stat: Stat, amount: u32 },
+    /// Absorbs the next damage the user takes.
+    Shield,
+    /// Slows the counter of the user's next objective down by `percent`.
+    SlowCounter { percent: u32 },
+    /// Draws the goal of the user's next objective again.
+    RerollGoal,
+}
+
+impl Effect {
+    /// Applies the effect caused by `user` to both players.
+    pub fn apply(self, user: &mut Player, target: &mut Player) {
+        match self {
+            Effect::LowerStat { stat, amount } => {
+                target.lower_stat(stat, amount);
+            }
+            Effect::StealStat { stat, amount } => {
+                let stolen = target.lower_stat(stat, amount);
+                user.raise_stat(stat, stolen);
+            }
+            Effect::Shield => user.status.shield = true,
+            Effect::SlowCounter { percent } => user.status.slow_percent += percent,
+            Effect::RerollGoal => user.status.reroll_goal = true,
+        }
+    }
+}
+
+/// Splits the players of a match into the one at `first` and the other one.
+pub fn split_players(players: &mut [Player; 2], first: usize) -> (&mut Player, &mut Player) {
+    let [p1, p2] = players;
+    if first == 0 { (p1, p2) } else { (p2, p1) }
+}
+
+/// Outcome of a round, decided by the players' average scores.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum RoundOutcome {
+    /// Both players have the same average score: nobody loses vitality.
+    Tie,
+    /// The player at index `winner` (0 or 1) wins, and the other one loses `damage` vitality.
+    Won { winner: usize, damage: u32 },
+}
+
+impl RoundOutcome {
+    /// Index of the player winning the round, if any.
+    pub fn winner(&self) -> Option<usize> {
+        match self {
+            RoundOutcome::Tie => None,
+            RoundOutcome::Won { winner, .. } => Some(*winner),
+        }
+    }
+
+    /// Index of the player losing the round, if any.
+    pub fn loser(&self) -> Option<usize> {
+        match self {
+            RoundOutcome::Tie => None,
+            RoundOutcome::Won { winner, .. } => Some(1 - winner),
+        }
+    }
+}
+
+/// Decides the outcome of a round: the best average score wins,
+/// and the loser takes the score difference as damage.
+///
+/// # Arguments
+///
+/// * `avg_scores` - The average score of each player.
+///
+/// # Returns
+///
+/// The outcome of the round.
+pub fn round_outcome(avg_scores: [u32; 2]) -> RoundOutcome {
+    let [avg_score1, avg_score2] = avg_scores;
+    if avg_score1 == avg_score2 {
+        RoundOutcome::Tie
+    } else if avg_score1 > avg_score2 {
+        RoundOutcome::Won { winner: 0, damage: avg_score1 - avg_score2 }
+    } else {
+        RoundOutcome::Won { winner: 1, damage: avg_score2 - avg_score1 }
+    }
+}
 
 /// Generates a list of random objectives.
 /// Each objective is a random number between 0 and 100.
@@ -7,67 +131,215 @@ use rand::Rng;
 /// # Arguments
 ///
 /// * `count` - The number of objectives to generate.
+/// * `rng` - The random number generator to draw the objectives from.
 ///
 /// # Returns
 ///
 /// A vector containing `count` random numbers.
-pub fn create_goals(count: u8) -> Vec<u8> {
-    let mut rng = rand::thread_rng();
+pub fn create_goals<R: Rng + ?Sized>(count: u8, rng: &mut R) -> Vec<u8> {
     (0..count).map(|_| rng.gen_range(0..=100)).collect()
 }
 
-/// Computes the score for a given objective.
+/// Computes the counter state after the counter has been running for a given time.
+/// The counter thread increments the value every `speed` milliseconds and wraps
+/// back to 0 (recording a miss) once it exceeds 100.
 ///
 /// # Arguments
 ///
-/// * `goal` - The target value for the objective.
-/// * `res` - The result for this objective (includes the counter value and miss count).
-/// * `strength` - The player's strength attribute.
+/// * `elapsed_ms` - Time elapsed since the counter was started, in milliseconds.
+/// * `speed` - The player's speed (increment delay in ms).
 ///
 /// # Returns
 ///
-/// The computed score for the objective.
-pub fn compute_score(goal: u8, res: &PlayerRes, strength: u32) -> u32 {
-    // Calculate the absolute difference between the target and the current counter.
-    let raw_diff = if goal >= res.counter {
-        goal - res.counter
+/// The counter value, miss count and elapsed time at that time.
+pub fn counter_at(elapsed_ms: u64, speed: u32) -> PlayerRes {
+    let ticks = elapsed_ms / speed.max(1) as u64;
+    PlayerRes {
+        counter: (ticks % 101) as u8,
+        miss: (ticks / 101) as u32,
+        elapsed_ms,
+        rejected: 0,
+    }
+}
+
+/// Estimates how far from the ideal moment a stop was, in milliseconds.
+/// The ideal moment is the middle of the tick during which the counter shows the goal.
+///
+/// # Arguments
+///
+/// * `offset` - The signed offset between the counter and the goal (see `signed_offset`).
+/// * `elapsed_ms` - Time elapsed between starting and stopping the counter.
+/// * `speed` - The player's speed (increment delay in ms).
+///
+/// # Returns
+///
+/// The signed timing error: negative when early, positive when late.
+pub fn timing_error_ms(offset: i8, elapsed_ms: u64, speed: u32) -> i64 {
+    let speed = speed.max(1) as i64;
+    offset as i64 * speed + (elapsed_ms as i64 % speed) - speed / 2
+}
+
+/// Computes the average score of a turn, rounded up.
+///
+/// # Arguments
+///
+/// * `total_score` - The sum of the scores of every objective.
+/// * `goal_count` - The number of objectives in the turn.
+///
+/// # Returns
+///
+/// The average score, or 0 if there were no objectives.
+pub fn average_score(total_score: u32, goal_count: u8) -> u32 {
+    if goal_count == 0 {
+        0
     } else {
-        res.counter - goal
-    };
+        (total_score as f32 / goal_count as f32).ceil() as u32
+    }
+}
 
-    // Calculate the circular difference to account for wrap-around when the difference is large.
-    let circ_diff = if raw_diff > 50 {
-        100 - raw_diff
+/// Draws a value from a normal distribution using the Box-Muller transform.
+///
+/// # Arguments
+///
+/// * `rng` - The random number generator to draw from.
+/// * `mean` - The mean of the distribution.
+/// * `sigma` - The standard deviation of the distribution.
+///
+/// # Returns
+///
+/// A normally distributed random value.
+pub fn sample_normal<R: Rng + ?Sized>(rng: &mut R, mean: f64, sigma: f64) -> f64 {
+    // `u1` is drawn from (0, 1] to keep `ln` finite.
+    let u1: f64 = 1.0 - rng.gen_range(0.0..1.0);
+    let u2: f64 = rng.gen_range(0.0..1.0);
+    let z = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+    mean + sigma * z
+}
+
+/// Probability that a normally distributed value is below `x`.
+/// Uses the Abramowitz and Stegun approximation of `erf` (error below 1.5e-7).
+///
+/// # Arguments
+///
+/// * `x` - The value.
+/// * `mean` - The mean of the distribution.
+/// * `sigma` - The standard deviation of the distribution.
+///
+/// # Returns
+///
+/// The cumulative probability, between 0 and 1.
+pub fn normal_cdf(x: f64, mean: f64, sigma: f64) -> f64 {
+    if sigma <= 0.0 {
+        return if x <= mean { 0.0 } else { 1.0 };
+    }
+    let z = (x - mean) / (sigma * std::f64::consts::SQRT_2);
+    let t = 1.0 / (1.0 + 0.3275911 * z.abs());
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-z * z).exp();
+    0.5 * (1.0 + erf.copysign(z))
+}
+
+/// Base score of each accuracy tier, from tier 1 (perfect stop) to tier 5.
+pub const TIER_BASE_SCORES: [u32; 5] = [100, 80, 60, 40, 20];
+
+/// Computes the signed offset between the counter and the goal.
+/// The offset is circular (between -50 and 50): it is negative when the player
+/// stopped early and positive when the player stopped late.
+///
+/// # Arguments
+///
+/// * `goal` - The target value for the objective.
+/// * `counter` - The counter value at the moment of stopping.
+///
+/// # Returns
+///
+/// The signed circular offset.
+pub fn signed_offset(goal: u8, counter: u8) -> i8 {
+    let diff = counter as i16 - goal as i16;
+    let circ = if diff > 50 {
+        diff - 100
+    } else if diff < -50 {
+        diff + 100
     } else {
-        raw_diff
+        diff
     };
+    circ as i8
+}
 
-    // Determine the base score according to the circular difference.
-    let base = if circ_diff == 0 {
-        100
+/// Determines the accuracy tier reached by a stop.
+///
+/// # Arguments
+///
+/// * `goal` - The target value for the objective.
+/// * `counter` - The counter value at the moment of stopping.
+///
+/// # Returns
+///
+/// The tier, from 1 (perfect stop) to 5 (more than 20 away from the goal).
+pub fn score_tier(goal: u8, counter: u8) -> u8 {
+    // Calculate the circular difference to account for wrap-around when the difference is large.
+    let circ_diff = signed_offset(goal, counter).unsigned_abs();
+    if circ_diff == 0 {
+        1
     } else if circ_diff <= 5 {
-        80
+        2
     } else if circ_diff <= 10 {
-        60
+        3
     } else if circ_diff <= 20 {
-        40
+        4
     } else {
-        20
-    };
+        5
+    }
+}
+
+/// Computes the score for a given objective.
+///
+/// # Arguments
+///
+/// * `goal` - The target value for the objective.
+/// * `res` - The result for this objective (includes the counter value and miss count).
+/// * `strength` - The player's strength attribute.
+///
+/// # Returns
+///
+/// The computed score for the objective.
+pub fn compute_score(goal: u8, res: &PlayerRes, strength: u32) -> u32 {
+    compute_score_with(goal, res, strength, &TIER_BASE_SCORES)
+}
+
+/// Computes the score for a given objective with a custom tier table.
+///
+/// # Arguments
+///
+/// * `goal` - The target value for the objective.
+/// * `res` - The result for this objective (includes the counter value and miss count).
+/// * `strength` - The player's strength attribute.
+/// * `tier_scores` - The base score of each tier, from tier 1 to tier 5.
+///
+/// # Returns
+///
+/// The computed score for the objective.
+pub fn compute_score_with(goal: u8, res: &PlayerRes, strength: u32, tier_scores: &[u32; 5]) -> u32 {
+    // Determine the base score according to the accuracy tier.
+    let base = tier_scores[score_tier(goal, res.counter) as usize - 1];
 
     // Compute and return the final score,
     // taking into account the player's strength and adjusting for any misses.
-    (base as u32 + strength) / (res.miss + 1)
+    (base + strength) / (res.miss + 1)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::classes::MIN_SPEED;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
 
     #[test]
     fn test_create_goals_zero() {
         // When count is zero, the resulting vector should be empty.
-        let goals = create_goals(0);
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let goals = create_goals(0, &mut rng);
         assert!(goals.is_empty());
     }
 
@@ -76,21 +348,120 @@ mod tests {
         // When a positive count is given, the vector should have that many elements,
         // and every element must be between 0 and 100 (inclusive).
         let count = 10;
-        let goals = create_goals(count);
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let goals = create_goals(count, &mut rng);
         assert_eq!(goals.len(), count as usize);
         for goal in goals {
             assert!(goal <= 100, "Goal {} is greater than 100", goal);
         }
     }
 
+    #[test]
+    fn test_create_goals_seeded() {
+        // The same seed must always produce the same objectives.
+        let goals1 = create_goals(10, &mut ChaCha8Rng::seed_from_u64(42));
+        let goals2 = create_goals(10, &mut ChaCha8Rng::seed_from_u64(42));
+        assert_eq!(goals1, goals2);
+    }
+
+    #[test]
+    fn test_counter_at() {
+        // 10 ticks of 50 ms: counter at 10, no miss.
+        let res = counter_at(520, 50);
+        assert_eq!(res.counter, 10);
+        assert_eq!(res.miss, 0);
+        // 101 ticks wrap the counter back to 0 and record a miss.
+        let res = counter_at(101 * 50 + 3 * 50, 50);
+        assert_eq!(res.counter, 3);
+        assert_eq!(res.miss, 1);
+    }
+
+    #[test]
+    fn test_signed_offset() {
+        assert_eq!(signed_offset(50, 50), 0);
+        assert_eq!(signed_offset(50, 45), -5);
+        assert_eq!(signed_offset(50, 58), 8);
+        // Stopping at 2 for a goal of 98 is 4 steps late once wrapped around.
+        assert_eq!(signed_offset(98, 2), 4);
+        assert_eq!(signed_offset(2, 98), -4);
+    }
+
+    #[test]
+    fn test_timing_error_ms() {
+        // Stopping in the middle of the goal tick is a perfect timing.
+        assert_eq!(timing_error_ms(0, 2525, 50), 0);
+        // Two ticks late, at the start of the tick.
+        assert_eq!(timing_error_ms(2, 2600, 50), 75);
+        assert_eq!(timing_error_ms(-1, 2460, 50), -65);
+    }
+
+    #[test]
+    fn test_score_tier() {
+        assert_eq!(score_tier(50, 50), 1);
+        assert_eq!(score_tier(50, 55), 2);
+        assert_eq!(score_tier(50, 40), 3);
+        assert_eq!(score_tier(10, 90), 4);
+        assert_eq!(score_tier(0, 50), 5);
+    }
+
+    #[test]
+    fn test_average_score() {
+        assert_eq!(average_score(0, 0), 0);
+        assert_eq!(average_score(10, 3), 4);
+        assert_eq!(average_score(12, 3), 4);
+    }
+
+    #[test]
+    fn test_round_outcome() {
+        assert_eq!(round_outcome([80, 80]), RoundOutcome::Tie);
+        assert_eq!(round_outcome([90, 75]), RoundOutcome::Won { winner: 0, damage: 15 });
+        assert_eq!(round_outcome([60, 75]), RoundOutcome::Won { winner: 1, damage: 15 });
+        assert_eq!(round_outcome([60, 75]).loser(), Some(0));
+        assert_eq!(RoundOutcome::Tie.loser(), None);
+        assert_eq!(round_outcome([60, 75]).winner(), Some(1));
+    }
+
+    #[test]
+    fn test_apply_penalty() {
+        let mut winner = Player::new("Bob".to_string(), 50, 50, 50);
+        let mut player = Player::new("Alice".to_string(), 50, 12, 50);
+        Penalty::Speed.apply(&mut winner, &mut player);
+        Penalty::Strength.apply(&mut winner, &mut player);
+        // Speed stops at its minimum.
+        assert_eq!(player.speed(), MIN_SPEED);
+        assert_eq!(player.strength(), 45);
+        assert_eq!(winner, Player::new("Bob".to_string(), 50, 50, 50));
+    }
+
+    #[test]
+    fn test_apply_effects() {
+        let mut user = Player::new("Alice".to_string(), 50, 50, 20);
+        let mut target = Player::new("Bob".to_string(), 50, 50, 3);
+        Effect::StealStat { stat: Stat::Strength, amount: 5 }.apply(&mut user, &mut target);
+        assert_eq!((user.strength(), target.strength()), (23, 0));
+        Effect::Shield.apply(&mut user, &mut target);
+        Effect::SlowCounter { percent: 50 }.apply(&mut user, &mut target);
+        Effect::RerollGoal.apply(&mut user, &mut target);
+        assert!(user.status.shield && user.status.reroll_goal);
+        assert_eq!(user.status.slow_percent, 50);
+        assert!(target.status.is_clear());
+    }
+
+    #[test]
+    fn test_split_players() {
+        let mut players = [Player::new("Alice".to_string(), 50, 50, 50), Player::new("Bob".to_string(), 50, 50, 50)];
+        let (first, other) = split_players(&mut players, 1);
+        assert_eq!((first.name.as_str(), other.name.as_str()), ("Bob", "Alice"));
+    }
+
     #[test]
     fn test_compute_score_perfect_match() {
         // When the goal equals the counter, raw_diff is 0 so base = 100.
         // With no misses, score = (100 + strength) / 1.
         let strength = 20;
-        let res = PlayerRes { counter: 50, miss: 0 };
+        let res = PlayerRes { counter: 50, miss: 0, elapsed_ms: 2500, rejected: 0 };
         let goal = 50;
-        let expected = (100 + strength) / 1; // 120
+        let expected = 100 + strength; // 120
         let score = compute_score(goal, &res, strength);
         assert_eq!(score, expected);
     }
@@ -100,9 +471,9 @@ mod tests {
         // When the difference is small and non-zero (<= 5), base should be 80.
         // For example, goal = 55 and counter = 50 gives raw_diff = 5 (and circ_diff = 5).
         let strength = 10;
-        let res = PlayerRes { counter: 50, miss: 0 };
+        let res = PlayerRes { counter: 50, miss: 0, elapsed_ms: 2500, rejected: 0 };
         let goal = 55;
-        let expected = (80 + strength) / 1; // 90
+        let expected = 80 + strength; // 90
         let score = compute_score(goal, &res, strength);
         assert_eq!(score, expected);
     }
@@ -113,9 +484,9 @@ mod tests {
         // For example, goal = 10 and counter = 90 gives raw_diff = 80,
         // so circ_diff = 100 - 80 = 20, thus base should be 40.
         let strength = 15;
-        let res = PlayerRes { counter: 90, miss: 0 };
+        let res = PlayerRes { counter: 90, miss: 0, elapsed_ms: 4500, rejected: 0 };
         let goal = 10;
-        let expected = (40 + strength) / 1; // 55
+        let expected = 40 + strength; // 55
         let score = compute_score(goal, &res, strength);
         assert_eq!(score, expected);
     }
@@ -126,10 +497,20 @@ mod tests {
         // For example, with goal = 50, counter = 50 (perfect match) and miss = 2:
         // score = (100 + strength) / (2 + 1)
         let strength = 30;
-        let res = PlayerRes { counter: 50, miss: 2 };
+        let res = PlayerRes { counter: 50, miss: 2, elapsed_ms: 12600, rejected: 0 };
         let goal = 50;
         let expected = (100 + strength) / 3; // (130 / 3) = 43 (integer division)
         let score = compute_score(goal, &res, strength);
         assert_eq!(score, expected);
     }
+
+    #[test]
+    fn test_normal_cdf() {
+        assert!((normal_cdf(0.0, 0.0, 1.0) - 0.5).abs() < 1e-7);
+        assert!((normal_cdf(1.96, 0.0, 1.0) - 0.975).abs() < 1e-4);
+        assert!((normal_cdf(80.0, 100.0, 20.0) - 0.158_655).abs() < 1e-5);
+        // Without spread, the distribution is a step at the mean.
+        assert_eq!(normal_cdf(10.0, 10.0, 0.0), 0.0);
+        assert_eq!(normal_cdf(10.1, 10.0, 0.0), 1.0);
+    }
 }
@@ -0,0 +1,1093 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use crate::campaign::{StageStatus, Unlock};
+use crate::classes::Stat;
+use crate::cards::Card;
+use crate::helper::{Effect, Penalty, PENALTY_AMOUNT};
+use crate::modifiers::Modifier;
+use crate::stat_map::{StatKey, StatMap};
+
+/// Language of the game's text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Lang {
+    /// English (default).
+    #[default]
+    En,
+    /// French.
+    Fr,
+}
+
+impl FromStr for Lang {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "en" => Ok(Lang::En),
+            "fr" => Ok(Lang::Fr),
+            other => Err(format!("unsupported language '{}' (expected 'en' or 'fr')", other)),
+        }
+    }
+}
+
+impl fmt::Display for Lang {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Lang::En => write!(f, "en"),
+            Lang::Fr => write!(f, "fr"),
+        }
+    }
+}
+
+/// Language used by `tr`, stored as the index of a `Lang` variant.
+static LANG: AtomicU8 = AtomicU8::new(0);
+
+/// Sets the language of every message displayed from now on.
+pub fn set_lang(lang: Lang) {
+    LANG.store(lang as u8, Ordering::Relaxed);
+}
+
+/// Returns the language currently in use.
+pub fn lang() -> Lang {
+    match LANG.load(Ordering::Relaxed) {
+        1 => Lang::Fr,
+        _ => Lang::En,
+    }
+}
+
+/// Set when the match engine must not print its progress (e.g. in the bot arena).
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Silences (or restores) the progress messages printed with `say`.
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+/// Prints a progress message of the match engine in the current language,
+/// unless the engine has been silenced.
+pub fn say(msg: Msg) {
+    if !QUIET.load(Ordering::Relaxed) {
+        println!("{}", tr(msg));
+    }
+}
+
+/// Every user-facing message of the game, with its parameters.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Msg<'a> {
+    // Match flow.
+    PlayersInitialized,
+    PlayerSummary { name: &'a str, stats: &'a StatMap },
+    Seed(u64),
+    Resuming { round: u32, abandoned: usize },
+    NewRound,
+    RoundTied,
+    RoundWon { winner: &'a str },
+    VitalityLost { name: &'a str, amount: u32 },
+    EndOfRound,
+    TurnStarted { name: &'a str, stats: &'a StatMap },
+    ModifierExpired { name: &'a str, stat: &'a StatKey, source: &'a str },
+    Goals(&'a [u8]),
+    ObjectiveHeader { name: &'a str, index: usize, goal: u8 },
+    ObjectiveScore { score: u32, offset: i8, tier: u8, elapsed_ms: u64 },
+    PerfectStreak { streak: u32, bonus: u32 },
+    CriticalHit { name: &'a str },
+    CriticalDamage { name: &'a str, crits: usize, damage: u32 },
+    AverageScore { name: &'a str, score: u32 },
+    NewRoundPrompt,
+    NoVitalityLeft,
+    ThanksForPlaying,
+
+    // Objectives.
+    PressEnterToStart,
+    PressEnterToStop,
+    Counter { counter: u8, miss: u32 },
+    StopIgnored { elapsed_ms: u64 },
+    ObjectiveStopped { counter: u8, miss: u32 },
+
+    // Round rules.
+    RoundModifiers(&'a [Modifier]),
+    GoalsHidden,
+    DamageReflected { name: &'a str, amount: u32 },
+    BossPhase { name: &'a str, phase: usize, phases: usize },
+    BossReflects { name: &'a str, percent: u32 },
+    MissCost { name: &'a str, amount: u32 },
+    MissForgiven { name: &'a str },
+
+    // Penalties.
+    ChoosePenalty { name: &'a str },
+    PenaltyOption { choice: u8, penalty: Penalty },
+    PenaltyPrompt,
+    PenaltyApplied { name: &'a str, penalty: Penalty },
+    InvalidPenaltyChoice,
+
+    // Ability cards.
+    CardDrawn { name: &'a str, card: &'a Card },
+    StaminaLeft { name: &'a str, stamina: u32 },
+    CardOption { choice: usize, card: &'a Card, cost: Option<u32> },
+    SlowOption { choice: usize, percent: u32, cost: u32 },
+    ActionPrompt,
+    InvalidActionChoice,
+    CounterSlowed { name: &'a str, percent: u32 },
+    RetryPrompt { cost: u32, stamina: u32 },
+    ObjectiveRetried { name: &'a str, stamina: u32 },
+    CardPlayed { name: &'a str, card: &'a Card },
+    GoalRerolled { goal: u8 },
+    ShieldAbsorbs { name: &'a str, amount: u32 },
+
+    // Wagers.
+    StakePrompt { name: &'a str, vitality: u32, max: u32 },
+    StakePlaced { name: &'a str, stake: u32 },
+    StakeWon { name: &'a str, amount: u32 },
+    StakeLost { name: &'a str, stake: u32 },
+    StakesRefunded,
+
+    // Blocks.
+    BlockPrompt { name: &'a str, cost: u32, stamina: u32 },
+    BlockAttempt { name: &'a str, goal: u8 },
+    Blocked { name: &'a str, blocked: u32, damage: u32 },
+    BlockFailed { name: &'a str },
+
+    // Accuracy report.
+    ReportTitle { name: &'a str },
+    ReportNoObjective,
+    ReportObjectives(usize),
+    ReportPerfectHits(usize),
+    ReportCrits(usize),
+    ReportBestStreak(u32),
+    ReportMeanAbsError(f64),
+    ReportBias { bias: f64, early: usize, late: usize },
+    ReportMeanTime(f64),
+    ReportSpread(f64),
+    ReportRejected(u32),
+    ReportSuspicious,
+
+    // Interruption.
+    InterruptedSaved { path: &'a str },
+    ResumeHint { path: &'a str },
+    InterruptedNotSaved { error: &'a str },
+    Interrupted,
+
+    // Errors.
+    GameEnded { error: &'a str },
+    ErrInputClosed,
+    ErrIo { error: &'a str },
+    ErrCounterThread,
+    ErrInvalidSave { error: &'a str },
+    ErrBot { error: &'a str },
+    ErrBotTimeout,
+    ErrBotClosed,
+    ErrBotMalformed { reply: &'a str },
+    ErrEmptyName,
+    ErrStatOutOfRange { stat: Stat, value: u32, min: u32, max: u32 },
+    ErrOverBudget { total: u32, budget: u32 },
+    ErrNotEnoughPoints { points: u32, available: u32 },
+    ErrInvalidProfiles { error: &'a str },
+    ErrInvalidDeck { error: &'a str },
+    ErrStakeTooHigh { stake: u32, max: u32 },
+    ErrUnknownStage { stage: usize, stages: usize },
+    ErrStageLocked { stage: usize, unlock: Unlock },
+    ErrInvalidProgress { error: &'a str },
+
+    // Simulation.
+    SimSummary { matches: u64, seed: u64, threads: usize },
+    SimWins { name: &'a str, wins: u64, percent: f64 },
+    SimDraws { draws: u64, percent: f64 },
+    SimAverageRounds(f64),
+
+    // HTTP API.
+    ServerListening { addr: &'a str },
+
+    // Spectators.
+    SpectatorListening { addr: &'a str },
+
+    // Bots.
+    BotFailed { name: &'a str, error: &'a str },
+    BotWithDeck { name: &'a str },
+
+    // Arena.
+    ArenaStarted { entrants: usize, series: u32, seed: u64 },
+    ArenaMatchWon { first: &'a str, second: &'a str, seed: u64, winner: &'a str, rounds: u32 },
+    ArenaMatchDrawn { first: &'a str, second: &'a str, seed: u64, rounds: u32 },
+    ArenaMatchForfeited { first: &'a str, second: &'a str, seed: u64, winner: &'a str, error: &'a str },
+    ArenaMatchFailed { first: &'a str, second: &'a str, seed: u64, error: &'a str },
+    ArenaTableHeader,
+    ArenaRow { rank: usize, name: &'a str, games: u64, wins: u64, draws: u64, losses: u64, rate: f64, low: f64, high: f64 },
+
+    // Build optimizer.
+    OptimizeStarted { builds: usize, budget: u32, matches: u64, seed: u64 },
+    OptimizeReference { vitality: u32, speed: u32, strength: u32 },
+    OptimizeTableHeader,
+    OptimizeRow { rank: usize, vitality: u32, speed: u32, strength: u32, rate: f64, low: f64, high: f64 },
+    OptimizeShares { top: usize, vitality: f64, speed: f64, strength: f64 },
+    OptimizeInvalidBudget { budget: u32, step: u32 },
+    HeadlessUnsupported { rules: &'a str },
+
+    // Score analysis.
+    AnalyzeModel { speed: u32, strength: u32, sigma_ms: f64, wrap_ms: u64 },
+    AnalyzeUniformGoals,
+    AnalyzeGoal(u8),
+    AnalyzeExpected { mean: f64, std_dev: f64 },
+    AnalyzeMiss(f64),
+    AnalyzeTier { tier: usize, probability: f64 },
+    AnalyzeScoresTitle,
+    AnalyzeScore { score: u32, probability: f64 },
+
+    // Player creation.
+    WizardTitle { index: usize, budget: u32 },
+    WizardName { index: usize },
+    WizardStat { stat: Stat, min: u32, max: u32, left: u32, default: u32 },
+    WizardInvalidNumber,
+
+    // Profiles.
+    ProfileLevel { name: &'a str, level: u32, xp: u64 },
+    ProfileSummary { name: &'a str, level: u32, xp: u64, next_xp: Option<u64> },
+    ProfileStats { vitality: u32, speed: u32, strength: u32, points: u32 },
+    ProfileRecord { matches: u32, wins: u32 },
+    ProfileAllocated { name: &'a str, stat: Stat, points: u32 },
+    XpGained { name: &'a str, xp: u64, level: u32 },
+    LevelUp { name: &'a str, level: u32, points: u32 },
+
+    // Campaign.
+    CampaignStageRow { stage: usize, name: &'a str, status: StageStatus },
+    CampaignStageTitle { stage: usize, name: &'a str },
+    CampaignIntro { stage: usize },
+    CampaignRules { goals: u8, perfect: u32 },
+    CampaignWon { name: &'a str, rounds: u32 },
+    CampaignFlawless,
+    CampaignVictory { stage: usize },
+    CampaignLost { name: &'a str },
+    CampaignLockedHint { stage: usize, unlock: Unlock },
+    CampaignNextPrompt,
+    CampaignRetryPrompt,
+    CampaignComplete,
+}
+
+impl Msg<'_> {
+    /// Returns the text of the message in the given language.
+    pub fn text(&self, lang: Lang) -> String {
+        match lang {
+            Lang::En => english(self),
+            Lang::Fr => french(self),
+        }
+    }
+}
+
+/// Returns the text of a message in the current language.
+pub fn tr(msg: Msg) -> String {
+    msg.text(lang())
+}
+
+/// Whether the player's answer to a yes/no prompt is "yes" in the current language
+/// ("y" in English, "o" for "oui" in French).
+pub fn is_yes(answer: &str) -> bool {
+    is_yes_in(answer, lang())
+}
+
+/// Whether the player's answer to a yes/no prompt is "yes" in the given language.
+pub fn is_yes_in(answer: &str, lang: Lang) -> bool {
+    let answer = answer.trim().to_lowercase();
+    match lang {
+        Lang::En => answer == "y" || answer == "yes",
+        Lang::Fr => answer == "o" || answer == "oui",
+    }
+}
+
+/// English message catalog.
+fn english(msg: &Msg) -> String {
+    match msg {
+        Msg::PlayersInitialized => "Players have been initialized:".to_string(),
+        Msg::PlayerSummary { name, stats } => format!("{}: {}", name, english_stats(stats)),
+        Msg::Seed(seed) => format!("Seed: {}", seed),
+        Msg::Resuming { round, abandoned } => {
+            format!("Resuming the match at round {} ({} objective(s) abandoned).", round, abandoned)
+        }
+        Msg::NewRound => "----- New Round -----".to_string(),
+        Msg::RoundTied => "The round is tied; no change in vitality.".to_string(),
+        Msg::RoundWon { winner } => format!("{} wins the round!", winner),
+        Msg::VitalityLost { name, amount } => format!("{} loses {} vitality points.", name, amount),
+        Msg::EndOfRound => "\n--- End of Round ---".to_string(),
+        Msg::TurnStarted { name, stats } => format!("\nIt's {}'s turn ({})", name, english_stats(stats)),
+        Msg::ModifierExpired { name, stat, source } => {
+            format!("The {} bonus of {} from {} wears off.", english_stat_key(stat), name, source)
+        }
+        Msg::Goals(goals) => format!("Goals: {:?}", goals),
+        Msg::ObjectiveHeader { name, index, goal } => {
+            format!("\n--- {} - Objective {}: Target {} ---", name, index, goal)
+        }
+        Msg::ObjectiveScore { score, offset, tier, elapsed_ms } => format!(
+            "Score for this objective: {} (offset {:+}, tier {}, {} ms)",
+            score, offset, tier, elapsed_ms
+        ),
+        Msg::PerfectStreak { streak, bonus } => format!("Perfect streak x{}: +{}% score!", streak, bonus),
+        Msg::CriticalHit { name } => format!("Critical hit by {}!", name),
+        Msg::CriticalDamage { name, crits, damage } => {
+            format!("{}'s {} critical hit(s) raise the damage to {}.", name, crits, damage)
+        }
+        Msg::AverageScore { name, score } => format!("{}'s average score: {}", name, score),
+        Msg::NewRoundPrompt => "Start a new round? [Y/N]: ".to_string(),
+        Msg::NoVitalityLeft => "A player has no vitality left. Game over! Sorry, but that's it!".to_string(),
+        Msg::ThanksForPlaying => "Thanks for playing!".to_string(),
+
+        Msg::PressEnterToStart => "Press ENTER to start this objective...".to_string(),
+        Msg::PressEnterToStop => "\nPress ENTER to stop the counter...".to_string(),
+        Msg::Counter { counter, miss } => format!("\rCounter: {} | Misses: {}", counter, miss),
+        Msg::StopIgnored { elapsed_ms } => format!("\nStop ignored: too fast to be human ({} ms).", elapsed_ms),
+        Msg::ObjectiveStopped { counter, miss } => {
+            format!("\nObjective stopped at: {} (Misses: {})", counter, miss)
+        }
+
+        Msg::RoundModifiers(modifiers) => {
+            let names: Vec<&str> = modifiers.iter().map(|m| english_modifier(*m)).collect();
+            format!("Round modifiers: {}", names.join(", "))
+        }
+        Msg::GoalsHidden => "Goals: hidden until each objective starts".to_string(),
+        Msg::DamageReflected { name, amount } => format!("{} takes {} reflected damage.", name, amount),
+        Msg::BossPhase { name, phase, phases } => format!("{} enters phase {} of {}!", name, phase, phases),
+        Msg::BossReflects { name, percent } => format!("{} now reflects {}% of the damage it takes.", name, percent),
+        Msg::MissCost { name, amount } => format!("{} loses {} vitality for missing the goal.", name, amount),
+        Msg::MissForgiven { name } => format!("Lucky! {}'s first miss is forgiven.", name),
+
+        Msg::ChoosePenalty { name } => format!("Choose a penalty to apply to {}:", name),
+        Msg::PenaltyOption { choice, penalty } => {
+            format!("{}: -{} to {}", choice, PENALTY_AMOUNT, english_stat(penalty.stat()))
+        }
+        Msg::PenaltyPrompt => "Your choice (1 or 2): ".to_string(),
+        Msg::PenaltyApplied { name, penalty } => {
+            format!("{} loses {} {}.", name, PENALTY_AMOUNT, english_stat(penalty.stat()))
+        }
+        Msg::InvalidPenaltyChoice => "Invalid choice. No penalty applied.".to_string(),
+
+        Msg::CardDrawn { name, card } => format!("{} draws {}.", name, card.name),
+        Msg::StaminaLeft { name, stamina } => format!("{} has {} stamina.", name, stamina),
+        Msg::CardOption { choice, card, cost: Some(cost) } => {
+            format!("{}: {} ({}) - {} stamina", choice, card.name, english_effect(card.effect), cost)
+        }
+        Msg::CardOption { choice, card, cost: None } => format!("{}: {} ({})", choice, card.name, english_effect(card.effect)),
+        Msg::SlowOption { choice, percent, cost } => {
+            format!("{}: slow the counter down by {}% - {} stamina", choice, percent, cost)
+        }
+        Msg::ActionPrompt => "Choose an action before this objective (number, or ENTER to skip): ".to_string(),
+        Msg::InvalidActionChoice => "Invalid choice. No action taken.".to_string(),
+        Msg::CounterSlowed { name, percent } => format!("{} slows the counter down by {}%.", name, percent),
+        Msg::RetryPrompt { cost, stamina } => {
+            format!("Retry this objective for {} stamina ({} left)? [y/N]: ", cost, stamina)
+        }
+        Msg::ObjectiveRetried { name, stamina } => {
+            format!("{} retries the objective ({} stamina left).", name, stamina)
+        }
+        Msg::CardPlayed { name, card } => format!("{} plays {}: {}.", name, card.name, english_effect(card.effect)),
+        Msg::GoalRerolled { goal } => format!("The goal is drawn again: {}", goal),
+        Msg::ShieldAbsorbs { name, amount } => format!("{}'s shield absorbs {} damage.", name, amount),
+
+        Msg::StakePrompt { name, vitality, max } => format!(
+            "{}, you have {} vitality. How much do you stake on this round (0 to {}, ENTER for 0)? ",
+            name, vitality, max
+        ),
+        Msg::StakePlaced { name, stake } => format!("{} stakes {} vitality.", name, stake),
+        Msg::StakeWon { name, amount } => format!("{} wins the bet and gets {} vitality back.", name, amount),
+        Msg::StakeLost { name, stake } => format!("{} loses the {} vitality at stake.", name, stake),
+        Msg::StakesRefunded => "The stakes are refunded.".to_string(),
+
+        Msg::BlockPrompt { name, cost, stamina } => {
+            format!("{}, spend {} stamina (out of {}) to try to block? [y/N]: ", name, cost, stamina)
+        }
+        Msg::BlockAttempt { name, goal } => format!("{} tries to block: stop the counter at {}!", name, goal),
+        Msg::Blocked { name, blocked, damage } => format!("{} blocks {} of the {} damage.", name, blocked, damage),
+        Msg::BlockFailed { name } => format!("{} fails to block.", name),
+
+        Msg::ReportTitle { name } => format!("--- Accuracy report for {} ---", name),
+        Msg::ReportNoObjective => "No objective played.".to_string(),
+        Msg::ReportObjectives(n) => format!("Objectives: {}", n),
+        Msg::ReportPerfectHits(n) => format!("Perfect hits: {}", n),
+        Msg::ReportCrits(n) => format!("Critical hits: {}", n),
+        Msg::ReportBestStreak(n) => format!("Best perfect streak: {}", n),
+        Msg::ReportMeanAbsError(e) => format!("Mean absolute error: {:.2}", e),
+        Msg::ReportBias { bias, early, late } => format!("Bias: {:+.2} ({} early, {} late)", bias, early, late),
+        Msg::ReportMeanTime(ms) => format!("Mean time to stop: {:.0} ms", ms),
+        Msg::ReportSpread(ms) => format!("Timing spread: {:.0} ms", ms),
+        Msg::ReportRejected(n) => format!("Rejected stops (too fast): {}", n),
+        Msg::ReportSuspicious => "WARNING: timing is too consistent to be human (possible bot/macro).".to_string(),
+
+        Msg::InterruptedSaved { path } => {
+            format!("Match interrupted and saved to {} (the current round will be replayed from its start).", path)
+        }
+        Msg::ResumeHint { path } => format!("Resume it with: duel_game --resume {}", path),
+        Msg::InterruptedNotSaved { error } => format!("Match interrupted, but it could not be saved: {}", error),
+        Msg::Interrupted => "Match interrupted.".to_string(),
+
+        Msg::GameEnded { error } => format!("Game ended: {}", error),
+        Msg::ErrInputClosed => "input closed, the match has been ended".to_string(),
+        Msg::ErrIo { error } => format!("I/O error: {}", error),
+        Msg::ErrCounterThread => "the counter thread stopped unexpectedly".to_string(),
+        Msg::ErrInvalidSave { error } => format!("invalid saved match: {}", error),
+        Msg::ErrBot { error } => format!("could not start the bot: {}", error),
+        Msg::ErrBotTimeout => "the bot did not answer in time".to_string(),
+        Msg::ErrBotClosed => "the bot has exited".to_string(),
+        Msg::ErrBotMalformed { reply } => format!("unexpected answer from the bot: {}", reply),
+        Msg::ErrEmptyName => "the player's name cannot be empty".to_string(),
+        Msg::ErrStatOutOfRange { stat, value, min, max } => format!(
+            "{} must be between {} and {} (got {})",
+            english_stat(*stat), min, max, value
+        ),
+        Msg::ErrOverBudget { total, budget } => {
+            format!("the stats add up to {} points, more than the budget of {}", total, budget)
+        }
+        Msg::ErrNotEnoughPoints { points, available } => {
+            format!("cannot spend {} stat points, only {} available", points, available)
+        }
+        Msg::ErrInvalidProfiles { error } => format!("invalid profiles file: {}", error),
+        Msg::ErrInvalidDeck { error } => format!("invalid deck file: {}", error),
+        Msg::ErrStakeTooHigh { stake, max } => format!("cannot stake {} vitality: at most {} may be staked", stake, max),
+        Msg::ErrUnknownStage { stage, stages } => {
+            format!("there is no stage {} (the campaign has {} stages)", stage, stages)
+        }
+        Msg::ErrStageLocked { stage, unlock } => {
+            format!("stage {} is locked: {} to unlock it", stage, english_unlock(*unlock))
+        }
+        Msg::ErrInvalidProgress { error } => format!("invalid campaign progress: {}", error),
+
+        Msg::SimSummary { matches, seed, threads } => {
+            format!("Simulated {} matches (seed {}, {} threads)", matches, seed, threads)
+        }
+        Msg::SimWins { name, wins, percent } => format!("{} wins: {} ({:.2}%)", name, wins, percent),
+        Msg::SimDraws { draws, percent } => format!("Draws: {} ({:.2}%)", draws, percent),
+        Msg::SimAverageRounds(rounds) => format!("Average rounds per match: {:.2}", rounds),
+
+        Msg::ServerListening { addr } => format!("HTTP API listening on http://{}", addr),
+
+        Msg::SpectatorListening { addr } => format!("Spectators can follow the match on tcp://{}", addr),
+
+        Msg::BotFailed { name, error } => format!("Bot {} failed ({}): default move played.", name, error),
+        Msg::BotWithDeck { name } => format!("{} has a deck, but bots cannot play ability cards", name),
+
+        Msg::ArenaStarted { entrants, series, seed } => format!(
+            "Arena: {} bots, {} seed(s) per pair, each played with both first movers (seeds from {}).",
+            entrants, series, seed
+        ),
+        Msg::ArenaMatchWon { first, second, seed, winner, rounds } => {
+            format!("{} vs {} (seed {}): {} wins in {} rounds", first, second, seed, winner, rounds)
+        }
+        Msg::ArenaMatchDrawn { first, second, seed, rounds } => {
+            format!("{} vs {} (seed {}): draw after {} rounds", first, second, seed, rounds)
+        }
+        Msg::ArenaMatchForfeited { first, second, seed, winner, error } => {
+            format!("{} vs {} (seed {}): {} wins by forfeit ({})", first, second, seed, winner, error)
+        }
+        Msg::ArenaMatchFailed { first, second, seed, error } => {
+            format!("{} vs {} (seed {}): draw, the match failed ({})", first, second, seed, error)
+        }
+        Msg::ArenaTableHeader => format!(
+            "{:>4} {:<20} {:>6} {:>6} {:>6} {:>6} {:>8}  {}",
+            "#", "Bot", "Games", "Wins", "Draws", "Losses", "Win rate", "95% CI"
+        ),
+        Msg::ArenaRow { rank, name, games, wins, draws, losses, rate, low, high } => format!(
+            "{:>4} {:<20} {:>6} {:>6} {:>6} {:>6} {:>7.1}%  [{:.1}%, {:.1}%]",
+            rank, name, games, wins, draws, losses, rate * 100.0, low * 100.0, high * 100.0
+        ),
+
+        Msg::OptimizeStarted { builds, budget, matches, seed } => format!(
+            "Testing {} builds of {} points, {} simulated matches each (seed {}).",
+            builds, budget, matches, seed
+        ),
+        Msg::OptimizeReference { vitality, speed, strength } => format!(
+            "Reference opponent: Vitality {}, Speed {}, Strength {}",
+            vitality, speed, strength
+        ),
+        Msg::OptimizeTableHeader => format!(
+            "{:>4} {:>8} {:>8} {:>8} {:>8}  {}",
+            "#", "Vitality", "Speed", "Strength", "Win rate", "95% CI"
+        ),
+        Msg::OptimizeRow { rank, vitality, speed, strength, rate, low, high } => format!(
+            "{:>4} {:>8} {:>8} {:>8} {:>7.1}%  [{:.1}%, {:.1}%]",
+            rank, vitality, speed, strength, rate * 100.0, low * 100.0, high * 100.0
+        ),
+        Msg::OptimizeShares { top, vitality, speed, strength } => format!(
+            "The {} best builds put on average {:.1}% of the budget in vitality, {:.1}% in speed and {:.1}% in strength.",
+            top, vitality, speed, strength
+        ),
+        Msg::OptimizeInvalidBudget { budget, step } => format!(
+            "the budget ({}) cannot be split in multiples of the step ({}) within the limits of a player's stats",
+            budget, step
+        ),
+        Msg::HeadlessUnsupported { rules } => format!("simulations and analyses do not support these rules: {}", rules),
+
+        Msg::AnalyzeModel { speed, strength, sigma_ms, wrap_ms } => format!(
+            "Speed {}, Strength {}, timing error σ = {} ms (the counter wraps every {} ms).",
+            speed, strength, sigma_ms, wrap_ms
+        ),
+        Msg::AnalyzeUniformGoals => "Goals: uniform from 0 to 100".to_string(),
+        Msg::AnalyzeGoal(goal) => format!("Goal: {}", goal),
+        Msg::AnalyzeExpected { mean, std_dev } => {
+            format!("Expected score per objective: {:.2} (standard deviation {:.2})", mean, std_dev)
+        }
+        Msg::AnalyzeMiss(p) => format!("Probability of a miss: {:.2}%", p * 100.0),
+        Msg::AnalyzeTier { tier, probability } => format!("Tier {}: {:.2}%", tier, probability * 100.0),
+        Msg::AnalyzeScoresTitle => "Score distribution:".to_string(),
+        Msg::AnalyzeScore { score, probability } => format!("{:>6}: {:>6.2}%", score, probability * 100.0),
+
+        Msg::WizardTitle { index, budget } => {
+            format!("\n--- Creating player {} ({} points to share between the stats) ---", index, budget)
+        }
+        Msg::WizardName { index } => format!("Name of player {}: ", index),
+        Msg::WizardStat { stat, min, max, left, default } => format!(
+            "{} ({}-{}, {} points left) [{}]: ",
+            capitalize(english_stat(*stat)), min, max, left, default
+        ),
+        Msg::WizardInvalidNumber => "Please enter a whole number.".to_string(),
+
+        Msg::ProfileLevel { name, level, xp } => format!("{} plays at level {} ({} XP).", name, level, xp),
+        Msg::ProfileSummary { name, level, xp, next_xp } => match next_xp {
+            Some(next) => format!("{}: level {}, {} XP (next level at {} XP)", name, level, xp, next),
+            None => format!("{}: level {} (maximum), {} XP", name, level, xp),
+        },
+        Msg::ProfileStats { vitality, speed, strength, points } => format!(
+            "Vitality {}, Speed {}, Strength {}; {} stat points to spend",
+            vitality, speed, strength, points
+        ),
+        Msg::ProfileRecord { matches, wins } => format!("Matches played: {}, won: {}", matches, wins),
+        Msg::ProfileAllocated { name, stat, points } => {
+            format!("{} spends {} points on {}.", name, points, english_stat(*stat))
+        }
+        Msg::XpGained { name, xp, level } => format!("{} earns {} XP (level {}).", name, xp, level),
+        Msg::LevelUp { name, level, points } => format!(
+            "{} reaches level {} and has {} stat points to spend (duel_game profile \"{}\" --allocate STAT).",
+            name, level, points, name
+        ),
+
+        Msg::CampaignStageRow { stage, name, status } => {
+            let status = match status {
+                StageStatus::Locked(unlock) => format!("locked: {}", english_unlock(*unlock)),
+                StageStatus::Open => "open".to_string(),
+                StageStatus::Beaten { flawless: false } => "beaten".to_string(),
+                StageStatus::Beaten { flawless: true } => "beaten without losing a round".to_string(),
+            };
+            format!("{}. {:<20} {}", stage, name, status)
+        }
+        Msg::CampaignStageTitle { stage, name } => format!("===== Stage {}: {} =====", stage, name),
+        Msg::CampaignIntro { stage } => english_story(*stage, false).to_string(),
+        Msg::CampaignRules { goals, perfect } => {
+            format!("Rules: {} objectives per round, a perfect stop is worth {} points.", goals, perfect)
+        }
+        Msg::CampaignWon { name, rounds } => format!("You defeated {} in {} rounds!", name, rounds),
+        Msg::CampaignFlawless => "Flawless victory: you did not lose a single round.".to_string(),
+        Msg::CampaignVictory { stage } => english_story(*stage, true).to_string(),
+        Msg::CampaignLost { name } => format!("{} defeated you.", name),
+        Msg::CampaignLockedHint { stage, unlock } => {
+            format!("Stage {} is still locked: {} to unlock it.", stage, english_unlock(*unlock))
+        }
+        Msg::CampaignNextPrompt => "Face the next opponent? [y/N]: ".to_string(),
+        Msg::CampaignRetryPrompt => "Try again? [y/N]: ".to_string(),
+        Msg::CampaignComplete => "You have beaten every opponent: the campaign is complete!".to_string(),
+    }
+}
+
+/// French message catalog.
+fn french(msg: &Msg) -> String {
+    match msg {
+        Msg::PlayersInitialized => "Les joueurs ont été créés :".to_string(),
+        Msg::PlayerSummary { name, stats } => format!("{} : {}", name, french_stats(stats)),
+        Msg::Seed(seed) => format!("Graine : {}", seed),
+        Msg::Resuming { round, abandoned } => {
+            format!("Reprise du match à la manche {} ({} objectif(s) abandonné(s)).", round, abandoned)
+        }
+        Msg::NewRound => "----- Nouvelle manche -----".to_string(),
+        Msg::RoundTied => "Égalité ; la vitalité ne change pas.".to_string(),
+        Msg::RoundWon { winner } => format!("{} remporte la manche !", winner),
+        Msg::VitalityLost { name, amount } => format!("{} perd {} points de vitalité.", name, amount),
+        Msg::EndOfRound => "\n--- Fin de la manche ---".to_string(),
+        Msg::TurnStarted { name, stats } => format!("\nAu tour de {} ({})", name, french_stats(stats)),
+        Msg::ModifierExpired { name, stat, source } => {
+            format!("Le bonus de {} de {} ({}) prend fin.", french_stat_key(stat), name, source)
+        }
+        Msg::Goals(goals) => format!("Objectifs : {:?}", goals),
+        Msg::ObjectiveHeader { name, index, goal } => {
+            format!("\n--- {} - Objectif {} : Cible {} ---", name, index, goal)
+        }
+        Msg::ObjectiveScore { score, offset, tier, elapsed_ms } => format!(
+            "Score pour cet objectif : {} (écart {:+}, palier {}, {} ms)",
+            score, offset, tier, elapsed_ms
+        ),
+        Msg::PerfectStreak { streak, bonus } => format!("Série parfaite x{} : +{} % de score !", streak, bonus),
+        Msg::CriticalHit { name } => format!("Coup critique de {} !", name),
+        Msg::CriticalDamage { name, crits, damage } => {
+            format!("Les {} coup(s) critique(s) de {} portent les dégâts à {}.", crits, name, damage)
+        }
+        Msg::AverageScore { name, score } => format!("Score moyen de {} : {}", name, score),
+        Msg::NewRoundPrompt => "Commencer une nouvelle manche ? [O/N] : ".to_string(),
+        Msg::NoVitalityLeft => "Un joueur n'a plus de vitalité. Partie terminée ! Désolé, c'est fini !".to_string(),
+        Msg::ThanksForPlaying => "Merci d'avoir joué !".to_string(),
+
+        Msg::PressEnterToStart => "Appuyez sur ENTRÉE pour lancer cet objectif...".to_string(),
+        Msg::PressEnterToStop => "\nAppuyez sur ENTRÉE pour arrêter le compteur...".to_string(),
+        Msg::Counter { counter, miss } => format!("\rCompteur : {} | Ratés : {}", counter, miss),
+        Msg::StopIgnored { elapsed_ms } => {
+            format!("\nArrêt ignoré : trop rapide pour un humain ({} ms).", elapsed_ms)
+        }
+        Msg::ObjectiveStopped { counter, miss } => {
+            format!("\nObjectif arrêté à : {} (Ratés : {})", counter, miss)
+        }
+
+        Msg::RoundModifiers(modifiers) => {
+            let names: Vec<&str> = modifiers.iter().map(|m| french_modifier(*m)).collect();
+            format!("Modificateurs de la manche : {}", names.join(", "))
+        }
+        Msg::GoalsHidden => "Objectifs : cachés jusqu'au début de chaque objectif".to_string(),
+        Msg::DamageReflected { name, amount } => format!("{} subit {} points de dégâts renvoyés.", name, amount),
+        Msg::BossPhase { name, phase, phases } => format!("{} entre dans la phase {} sur {} !", name, phase, phases),
+        Msg::BossReflects { name, percent } => {
+            format!("{} renvoie désormais {} % des dégâts qu'il subit.", name, percent)
+        }
+        Msg::MissCost { name, amount } => format!("{} perd {} points de vitalité pour avoir manqué l'objectif.", name, amount),
+        Msg::MissForgiven { name } => format!("Coup de chance ! Le premier raté de {} est pardonné.", name),
+
+        Msg::ChoosePenalty { name } => format!("Choisissez une pénalité à infliger à {} :", name),
+        Msg::PenaltyOption { choice, penalty } => {
+            format!("{} : -{} en {}", choice, PENALTY_AMOUNT, french_stat(penalty.stat()))
+        }
+        Msg::PenaltyPrompt => "Votre choix (1 ou 2) : ".to_string(),
+        Msg::PenaltyApplied { name, penalty } => {
+            format!("{} perd {} en {}.", name, PENALTY_AMOUNT, french_stat(penalty.stat()))
+        }
+        Msg::InvalidPenaltyChoice => "Choix invalide. Aucune pénalité appliquée.".to_string(),
+
+        Msg::CardDrawn { name, card } => format!("{} pioche {}.", name, card.name),
+        Msg::StaminaLeft { name, stamina } => format!("{} a {} d'endurance.", name, stamina),
+        Msg::CardOption { choice, card, cost: Some(cost) } => {
+            format!("{} : {} ({}) - {} d'endurance", choice, card.name, french_effect(card.effect), cost)
+        }
+        Msg::CardOption { choice, card, cost: None } => format!("{} : {} ({})", choice, card.name, french_effect(card.effect)),
+        Msg::SlowOption { choice, percent, cost } => {
+            format!("{} : ralentir le compteur de {} % - {} d'endurance", choice, percent, cost)
+        }
+        Msg::ActionPrompt => "Choisir une action avant cet objectif (numéro, ou ENTRÉE pour passer) : ".to_string(),
+        Msg::InvalidActionChoice => "Choix invalide. Aucune action effectuée.".to_string(),
+        Msg::CounterSlowed { name, percent } => format!("{} ralentit le compteur de {} %.", name, percent),
+        Msg::RetryPrompt { cost, stamina } => {
+            format!("Rejouer cet objectif pour {} d'endurance (il en reste {}) ? [o/N] : ", cost, stamina)
+        }
+        Msg::ObjectiveRetried { name, stamina } => {
+            format!("{} rejoue l'objectif (il lui reste {} d'endurance).", name, stamina)
+        }
+        Msg::CardPlayed { name, card } => format!("{} joue {} : {}.", name, card.name, french_effect(card.effect)),
+        Msg::GoalRerolled { goal } => format!("L'objectif est tiré à nouveau : {}", goal),
+        Msg::ShieldAbsorbs { name, amount } => format!("Le bouclier de {} absorbe {} points de dégâts.", name, amount),
+
+        Msg::StakePrompt { name, vitality, max } => format!(
+            "{}, vous avez {} points de vitalité. Combien misez-vous sur cette manche (0 à {}, ENTRÉE pour 0) ? ",
+            name, vitality, max
+        ),
+        Msg::StakePlaced { name, stake } => format!("{} mise {} points de vitalité.", name, stake),
+        Msg::StakeWon { name, amount } => format!("{} gagne son pari et récupère {} points de vitalité.", name, amount),
+        Msg::StakeLost { name, stake } => format!("{} perd les {} points de vitalité misés.", name, stake),
+        Msg::StakesRefunded => "Les mises sont remboursées.".to_string(),
+
+        Msg::BlockPrompt { name, cost, stamina } => {
+            format!("{}, dépenser {} d'endurance (sur {}) pour tenter de parer ? [o/N] : ", name, cost, stamina)
+        }
+        Msg::BlockAttempt { name, goal } => format!("{} tente de parer : arrêtez le compteur à {} !", name, goal),
+        Msg::Blocked { name, blocked, damage } => format!("{} pare {} des {} points de dégâts.", name, blocked, damage),
+        Msg::BlockFailed { name } => format!("{} ne parvient pas à parer.", name),
+
+        Msg::ReportTitle { name } => format!("--- Bilan de précision de {} ---", name),
+        Msg::ReportNoObjective => "Aucun objectif joué.".to_string(),
+        Msg::ReportObjectives(n) => format!("Objectifs : {}", n),
+        Msg::ReportPerfectHits(n) => format!("Arrêts parfaits : {}", n),
+        Msg::ReportCrits(n) => format!("Coups critiques : {}", n),
+        Msg::ReportBestStreak(n) => format!("Meilleure série parfaite : {}", n),
+        Msg::ReportMeanAbsError(e) => format!("Erreur absolue moyenne : {:.2}", e),
+        Msg::ReportBias { bias, early, late } => {
+            format!("Biais : {:+.2} ({} en avance, {} en retard)", bias, early, late)
+        }
+        Msg::ReportMeanTime(ms) => format!("Temps moyen d'arrêt : {:.0} ms", ms),
+        Msg::ReportSpread(ms) => format!("Dispersion du timing : {:.0} ms", ms),
+        Msg::ReportRejected(n) => format!("Arrêts refusés (trop rapides) : {}", n),
+        Msg::ReportSuspicious => {
+            "ATTENTION : timing trop régulier pour un humain (bot ou macro possible).".to_string()
+        }
+
+        Msg::InterruptedSaved { path } => {
+            format!("Match interrompu et sauvegardé dans {} (la manche en cours sera rejouée depuis le début).", path)
+        }
+        Msg::ResumeHint { path } => format!("Pour le reprendre : duel_game --resume {}", path),
+        Msg::InterruptedNotSaved { error } => {
+            format!("Match interrompu, mais la sauvegarde a échoué : {}", error)
+        }
+        Msg::Interrupted => "Match interrompu.".to_string(),
+
+        Msg::GameEnded { error } => format!("Partie terminée : {}", error),
+        Msg::ErrInputClosed => "entrée fermée, le match est terminé".to_string(),
+        Msg::ErrIo { error } => format!("erreur d'entrée/sortie : {}", error),
+        Msg::ErrCounterThread => "le fil du compteur s'est arrêté de façon inattendue".to_string(),
+        Msg::ErrInvalidSave { error } => format!("sauvegarde de match invalide : {}", error),
+        Msg::ErrBot { error } => format!("impossible de lancer le bot : {}", error),
+        Msg::ErrBotTimeout => "le bot n'a pas répondu à temps".to_string(),
+        Msg::ErrBotClosed => "le bot s'est arrêté".to_string(),
+        Msg::ErrBotMalformed { reply } => format!("réponse inattendue du bot : {}", reply),
+        Msg::ErrEmptyName => "le nom du joueur ne peut pas être vide".to_string(),
+        Msg::ErrStatOutOfRange { stat, value, min, max } => format!(
+            "la {} doit être comprise entre {} et {} (valeur : {})",
+            french_stat(*stat), min, max, value
+        ),
+        Msg::ErrOverBudget { total, budget } => {
+            format!("les statistiques totalisent {} points, plus que le budget de {}", total, budget)
+        }
+        Msg::ErrNotEnoughPoints { points, available } => {
+            format!("impossible de dépenser {} points de statistiques, seulement {} disponibles", points, available)
+        }
+        Msg::ErrInvalidProfiles { error } => format!("fichier de profils invalide : {}", error),
+        Msg::ErrInvalidDeck { error } => format!("fichier de deck invalide : {}", error),
+        Msg::ErrStakeTooHigh { stake, max } => {
+            format!("impossible de miser {} points de vitalité : la mise est limitée à {}", stake, max)
+        }
+        Msg::ErrUnknownStage { stage, stages } => {
+            format!("l'étape {} n'existe pas (la campagne compte {} étapes)", stage, stages)
+        }
+        Msg::ErrStageLocked { stage, unlock } => {
+            format!("l'étape {} est verrouillée : {} pour la débloquer", stage, french_unlock(*unlock))
+        }
+        Msg::ErrInvalidProgress { error } => format!("progression de campagne invalide : {}", error),
+
+        Msg::SimSummary { matches, seed, threads } => {
+            format!("{} matchs simulés (graine {}, {} fils)", matches, seed, threads)
+        }
+        Msg::SimWins { name, wins, percent } => format!("Victoires de {} : {} ({:.2} %)", name, wins, percent),
+        Msg::SimDraws { draws, percent } => format!("Matchs nuls : {} ({:.2} %)", draws, percent),
+        Msg::SimAverageRounds(rounds) => format!("Nombre moyen de manches par match : {:.2}", rounds),
+
+        Msg::ServerListening { addr } => format!("API HTTP à l'écoute sur http://{}", addr),
+
+        Msg::SpectatorListening { addr } => format!("Les spectateurs peuvent suivre le match sur tcp://{}", addr),
+
+        Msg::BotFailed { name, error } => format!("Le bot {} a échoué ({}) : coup par défaut joué.", name, error),
+        Msg::BotWithDeck { name } => format!("{} a un deck, mais les bots ne peuvent pas jouer de cartes", name),
+
+        Msg::ArenaStarted { entrants, series, seed } => format!(
+            "Arène : {} bots, {} graine(s) par paire, chacune jouée avec les deux ordres de passage (graines à partir de {}).",
+            entrants, series, seed
+        ),
+        Msg::ArenaMatchWon { first, second, seed, winner, rounds } => {
+            format!("{} contre {} (graine {}) : {} gagne en {} manches", first, second, seed, winner, rounds)
+        }
+        Msg::ArenaMatchDrawn { first, second, seed, rounds } => {
+            format!("{} contre {} (graine {}) : match nul après {} manches", first, second, seed, rounds)
+        }
+        Msg::ArenaMatchForfeited { first, second, seed, winner, error } => {
+            format!("{} contre {} (graine {}) : {} gagne par forfait ({})", first, second, seed, winner, error)
+        }
+        Msg::ArenaMatchFailed { first, second, seed, error } => {
+            format!("{} contre {} (graine {}) : match nul, le match a échoué ({})", first, second, seed, error)
+        }
+        Msg::ArenaTableHeader => format!(
+            "{:>4} {:<20} {:>6} {:>6} {:>6} {:>6} {:>8}  {}",
+            "#", "Bot", "Matchs", "Vict.", "Nuls", "Déf.", "Taux", "IC 95 %"
+        ),
+        Msg::ArenaRow { rank, name, games, wins, draws, losses, rate, low, high } => format!(
+            "{:>4} {:<20} {:>6} {:>6} {:>6} {:>6} {:>7.1}%  [{:.1}%, {:.1}%]",
+            rank, name, games, wins, draws, losses, rate * 100.0, low * 100.0, high * 100.0
+        ),
+
+        Msg::OptimizeStarted { builds, budget, matches, seed } => format!(
+            "Test de {} répartitions de {} points, {} matchs simulés chacune (graine {}).",
+            builds, budget, matches, seed
+        ),
+        Msg::OptimizeReference { vitality, speed, strength } => format!(
+            "Adversaire de référence : Vitalité {}, Vitesse {}, Force {}",
+            vitality, speed, strength
+        ),
+        Msg::OptimizeTableHeader => format!(
+            "{:>4} {:>8} {:>8} {:>8} {:>8}  {}",
+            "#", "Vitalité", "Vitesse", "Force", "Taux", "IC 95 %"
+        ),
+        Msg::OptimizeRow { rank, vitality, speed, strength, rate, low, high } => format!(
+            "{:>4} {:>8} {:>8} {:>8} {:>7.1}%  [{:.1}%, {:.1}%]",
+            rank, vitality, speed, strength, rate * 100.0, low * 100.0, high * 100.0
+        ),
+        Msg::OptimizeShares { top, vitality, speed, strength } => format!(
+            "Les {} meilleures répartitions placent en moyenne {:.1} % du budget en vitalité, {:.1} % en vitesse et {:.1} % en force.",
+            top, vitality, speed, strength
+        ),
+        Msg::OptimizeInvalidBudget { budget, step } => format!(
+            "le budget ({}) ne peut pas être réparti en multiples du pas ({}) dans les limites des statistiques d'un joueur",
+            budget, step
+        ),
+        Msg::HeadlessUnsupported { rules } => format!("les simulations et les analyses ne prennent pas en charge ces règles : {}", rules),
+
+        Msg::AnalyzeModel { speed, strength, sigma_ms, wrap_ms } => format!(
+            "Vitesse {}, Force {}, erreur de timing σ = {} ms (le compteur repart à zéro toutes les {} ms).",
+            speed, strength, sigma_ms, wrap_ms
+        ),
+        Msg::AnalyzeUniformGoals => "Objectifs : uniformes de 0 à 100".to_string(),
+        Msg::AnalyzeGoal(goal) => format!("Objectif : {}", goal),
+        Msg::AnalyzeExpected { mean, std_dev } => {
+            format!("Score attendu par objectif : {:.2} (écart type {:.2})", mean, std_dev)
+        }
+        Msg::AnalyzeMiss(p) => format!("Probabilité d'un raté : {:.2} %", p * 100.0),
+        Msg::AnalyzeTier { tier, probability } => format!("Niveau {} : {:.2} %", tier, probability * 100.0),
+        Msg::AnalyzeScoresTitle => "Distribution des scores :".to_string(),
+        Msg::AnalyzeScore { score, probability } => format!("{:>6} : {:>6.2} %", score, probability * 100.0),
+
+        Msg::WizardTitle { index, budget } => {
+            format!("\n--- Création du joueur {} ({} points à répartir entre les statistiques) ---", index, budget)
+        }
+        Msg::WizardName { index } => format!("Nom du joueur {} : ", index),
+        Msg::WizardStat { stat, min, max, left, default } => format!(
+            "{} ({}-{}, {} points restants) [{}] : ",
+            capitalize(french_stat(*stat)), min, max, left, default
+        ),
+        Msg::WizardInvalidNumber => "Veuillez saisir un nombre entier.".to_string(),
+
+        Msg::ProfileLevel { name, level, xp } => format!("{} joue au niveau {} ({} XP).", name, level, xp),
+        Msg::ProfileSummary { name, level, xp, next_xp } => match next_xp {
+            Some(next) => format!("{} : niveau {}, {} XP (niveau suivant à {} XP)", name, level, xp, next),
+            None => format!("{} : niveau {} (maximum), {} XP", name, level, xp),
+        },
+        Msg::ProfileStats { vitality, speed, strength, points } => format!(
+            "Vitalité {}, Vitesse {}, Force {} ; {} points de statistiques à dépenser",
+            vitality, speed, strength, points
+        ),
+        Msg::ProfileRecord { matches, wins } => format!("Matchs joués : {}, gagnés : {}", matches, wins),
+        Msg::ProfileAllocated { name, stat, points } => {
+            format!("{} dépense {} points en {}.", name, points, french_stat(*stat))
+        }
+        Msg::XpGained { name, xp, level } => format!("{} gagne {} XP (niveau {}).", name, xp, level),
+        Msg::LevelUp { name, level, points } => format!(
+            "{} atteint le niveau {} et a {} points de statistiques à dépenser (duel_game profile \"{}\" --allocate STAT).",
+            name, level, points, name
+        ),
+
+        Msg::CampaignStageRow { stage, name, status } => {
+            let status = match status {
+                StageStatus::Locked(unlock) => format!("verrouillée : {}", french_unlock(*unlock)),
+                StageStatus::Open => "ouverte".to_string(),
+                StageStatus::Beaten { flawless: false } => "remportée".to_string(),
+                StageStatus::Beaten { flawless: true } => "remportée sans perdre une manche".to_string(),
+            };
+            format!("{}. {:<20} {}", stage, name, status)
+        }
+        Msg::CampaignStageTitle { stage, name } => format!("===== Étape {} : {} =====", stage, name),
+        Msg::CampaignIntro { stage } => french_story(*stage, false).to_string(),
+        Msg::CampaignRules { goals, perfect } => {
+            format!("Règles : {} objectifs par manche, un arrêt parfait vaut {} points.", goals, perfect)
+        }
+        Msg::CampaignWon { name, rounds } => format!("Vous avez battu {} en {} manches !", name, rounds),
+        Msg::CampaignFlawless => "Victoire parfaite : vous n'avez perdu aucune manche.".to_string(),
+        Msg::CampaignVictory { stage } => french_story(*stage, true).to_string(),
+        Msg::CampaignLost { name } => format!("{} vous a battu.", name),
+        Msg::CampaignLockedHint { stage, unlock } => {
+            format!("L'étape {} est encore verrouillée : {} pour la débloquer.", stage, french_unlock(*unlock))
+        }
+        Msg::CampaignNextPrompt => "Affronter l'adversaire suivant ? [o/N] : ".to_string(),
+        Msg::CampaignRetryPrompt => "Réessayer ? [o/N] : ".to_string(),
+        Msg::CampaignComplete => "Vous avez battu tous les adversaires : la campagne est terminée !".to_string(),
+    }
+}
+
+/// Returns `word` with its first letter in upper case.
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// English description of a round modifier.
+fn english_modifier(modifier: Modifier) -> &'static str {
+    match modifier {
+        Modifier::DoubleSpeed => "the counter runs twice as fast",
+        Modifier::HiddenGoals => "goals are hidden",
+        Modifier::DoubleDamage => "double damage",
+        Modifier::ExactHitsOnly => "only exact hits score",
+        Modifier::CounterBackwards => "the counter runs backwards",
+        Modifier::MissesCostVitality => "misses cost vitality",
+    }
+}
+
+/// French description of a round modifier.
+fn french_modifier(modifier: Modifier) -> &'static str {
+    match modifier {
+        Modifier::DoubleSpeed => "le compteur va deux fois plus vite",
+        Modifier::HiddenGoals => "les objectifs sont cachés",
+        Modifier::DoubleDamage => "dégâts doublés",
+        Modifier::ExactHitsOnly => "seuls les coups parfaits comptent",
+        Modifier::CounterBackwards => "le compteur tourne à l'envers",
+        Modifier::MissesCostVitality => "les ratés coûtent de la vitalité",
+    }
+}
+
+/// English description of a stage's unlock condition.
+fn english_unlock(unlock: Unlock) -> String {
+    match unlock {
+        Unlock::Start => "open from the start".to_string(),
+        Unlock::Beat(stage) => format!("beat stage {}", stage + 1),
+        Unlock::Flawless(stage) => format!("beat stage {} without losing a round", stage + 1),
+    }
+}
+
+/// French description of a stage's unlock condition.
+fn french_unlock(unlock: Unlock) -> String {
+    match unlock {
+        Unlock::Start => "ouverte dès le début".to_string(),
+        Unlock::Beat(stage) => format!("remportez l'étape {}", stage + 1),
+        Unlock::Flawless(stage) => format!("remportez l'étape {} sans perdre une manche", stage + 1),
+    }
+}
+
+/// English story of the campaign, told before (`victory` false) and after beating
+/// the opponent of each stage.
+fn english_story(stage: usize, victory: bool) -> &'static str {
+    match (stage, victory) {
+        (0, false) => "The village fair opens its counter-stopping contest. Timid Tom, the baker's son, \
+            shuffles onto the stage. He always stops a little late, they say.",
+        (0, true) => "The crowd cheers. A traveller in a hurry watches you from the back and smiles.",
+        (1, false) => "The traveller is Quick Quentin, who never waits for anything, not even the goal. \
+            He challenges you on the road to the city.",
+        (1, true) => "Quentin bows and points you to the city's duelling hall, where Stella trains.",
+        (2, false) => "Steady Stella runs the duelling hall. Her stops are calm and careful, and her hall \
+            rewards exact stops more than near misses.",
+        (2, true) => "Stella nods. \"Only those who never falter may meet the Metronome.\"",
+        (3, false) => "In the clock tower, the Metronome ticks. It has not missed a beat in years, and \
+            near misses are worth almost nothing here.",
+        (3, true) => "The Metronome stops ticking. Above, a door opens onto the top of the tower.",
+        (4, false) => "Grandmaster Chronos waits at the top of the tower. Seven objectives per round: \
+            time itself is the weapon.",
+        (4, true) => "Chronos lays down his hourglass. The tower's bells ring for the new grandmaster.",
+        _ => "",
+    }
+}
+
+/// French story of the campaign.
+fn french_story(stage: usize, victory: bool) -> &'static str {
+    match (stage, victory) {
+        (0, false) => "La fête du village ouvre son concours d'arrêt de compteur. Tom le Timide, le fils \
+            du boulanger, monte sur l'estrade. On dit qu'il s'arrête toujours un peu tard.",
+        (0, true) => "La foule applaudit. Au fond, un voyageur pressé vous observe en souriant.",
+        (1, false) => "Le voyageur, c'est Quentin le Rapide, qui n'attend jamais rien, pas même l'objectif. \
+            Il vous défie sur la route de la ville.",
+        (1, true) => "Quentin s'incline et vous indique la salle de duel de la ville, où s'entraîne Stella.",
+        (2, false) => "Stella la Posée tient la salle de duel. Ses arrêts sont calmes et précis, et sa salle \
+            récompense les arrêts exacts bien plus que les approximations.",
+        (2, true) => "Stella hoche la tête. « Seuls ceux qui ne faiblissent jamais peuvent affronter le Métronome. »",
+        (3, false) => "Dans la tour de l'horloge, le Métronome bat la mesure. Il n'a pas manqué un temps depuis \
+            des années, et les approximations ne valent presque rien ici.",
+        (3, true) => "Le Métronome se tait. Au-dessus, une porte s'ouvre sur le sommet de la tour.",
+        (4, false) => "Le Grand Maître Chronos attend au sommet de la tour. Sept objectifs par manche : \
+            le temps lui-même est l'arme.",
+        (4, true) => "Chronos pose son sablier. Les cloches de la tour sonnent pour le nouveau grand maître.",
+        _ => "",
+    }
+}
+
+/// English description of an effect, from its user's point of view.
+fn english_effect(effect: Effect) -> String {
+    match effect {
+        Effect::LowerStat { stat, amount } => format!("removes {} {} from the opponent", amount, english_stat(stat)),
+        Effect::StealStat { stat, amount } => format!("steals {} {} from the opponent", amount, english_stat(stat)),
+        Effect::Shield => "absorbs the next damage".to_string(),
+        Effect::SlowCounter { percent } => format!("slows the counter of the next objective down by {}%", percent),
+        Effect::RerollGoal => "draws the goal of the next objective again".to_string(),
+    }
+}
+
+/// French description of an effect, from its user's point of view.
+fn french_effect(effect: Effect) -> String {
+    match effect {
+        Effect::LowerStat { stat, amount } => format!("retire {} en {} à l'adversaire", amount, french_stat(stat)),
+        Effect::StealStat { stat, amount } => format!("vole {} en {} à l'adversaire", amount, french_stat(stat)),
+        Effect::Shield => "absorbe les prochains dégâts".to_string(),
+        Effect::SlowCounter { percent } => format!("ralentit de {} % le compteur du prochain objectif", percent),
+        Effect::RerollGoal => "tire à nouveau l'objectif suivant".to_string(),
+    }
+}
+
+/// English name of a stat.
+fn english_stat(stat: Stat) -> &'static str {
+    match stat {
+        Stat::Vitality => "vitality",
+        Stat::Speed => "speed",
+        Stat::Strength => "strength",
+    }
+}
+
+/// French name of a stat.
+fn french_stat(stat: Stat) -> &'static str {
+    match stat {
+        Stat::Vitality => "vitalité",
+        Stat::Speed => "vitesse",
+        Stat::Strength => "force",
+    }
+}
+
+/// English name of a core or extra stat. Extra stats come from rulesets and keep their name.
+fn english_stat_key(key: &StatKey) -> String {
+    match key {
+        StatKey::Core(stat) => english_stat(*stat).to_string(),
+        StatKey::Extra(name) => name.replace('_', " "),
+    }
+}
+
+/// French name of a core or extra stat. Extra stats come from rulesets and keep their name.
+fn french_stat_key(key: &StatKey) -> String {
+    match key {
+        StatKey::Core(stat) => french_stat(*stat).to_string(),
+        StatKey::Extra(name) => name.replace('_', " "),
+    }
+}
+
+/// Lists every stat of a player with its value, in English.
+fn english_stats(stats: &StatMap) -> String {
+    let entries: Vec<String> = stats
+        .iter()
+        .map(|(key, value)| format!("{}: {}", capitalize(&english_stat_key(key)), value))
+        .collect();
+    entries.join(", ")
+}
+
+/// Lists every stat of a player with its value, in French.
+fn french_stats(stats: &StatMap) -> String {
+    let entries: Vec<String> = stats
+        .iter()
+        .map(|(key, value)| format!("{} : {}", capitalize(&french_stat_key(key)), value))
+        .collect();
+    entries.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lang() {
+        assert_eq!("fr".parse::<Lang>(), Ok(Lang::Fr));
+        assert_eq!("EN".parse::<Lang>(), Ok(Lang::En));
+        assert!("de".parse::<Lang>().is_err());
+        assert_eq!(Lang::Fr.to_string(), "fr");
+    }
+
+    #[test]
+    fn test_catalogs() {
+        assert_eq!(Msg::NewRound.text(Lang::En), "----- New Round -----");
+        assert_eq!(Msg::NewRound.text(Lang::Fr), "----- Nouvelle manche -----");
+        let applied = Msg::PenaltyApplied { name: "Michel", penalty: Penalty::Strength };
+        assert_eq!(applied.text(Lang::En), "Michel loses 5 strength.");
+        assert_eq!(applied.text(Lang::Fr), "Michel perd 5 en force.");
+        assert!(Msg::NewRoundPrompt.text(Lang::Fr).contains("[O/N]"));
+        let player = crate::classes::Player::new("Alice".to_string(), 50, 40, 30).with_stat("focus_points".parse::<StatKey>().unwrap(), 2);
+        let summary = Msg::PlayerSummary { name: &player.name, stats: &player.stats };
+        assert_eq!(summary.text(Lang::En), "Alice: Vitality: 50, Speed: 40, Strength: 30, Focus points: 2");
+        assert_eq!(summary.text(Lang::Fr), "Alice : Vitalité : 50, Vitesse : 40, Force : 30, Focus points : 2");
+        let retry = Msg::RetryPrompt { cost: 1, stamina: 2 };
+        assert_eq!(retry.text(Lang::En), "Retry this objective for 1 stamina (2 left)? [y/N]: ");
+    }
+
+    #[test]
+    fn test_is_yes_per_language() {
+        assert!(is_yes_in("o\n", Lang::Fr));
+        assert!(is_yes_in("Oui", Lang::Fr));
+        assert!(!is_yes_in("y", Lang::Fr));
+        assert!(!is_yes_in("n", Lang::Fr));
+        assert!(is_yes_in("Y\n", Lang::En));
+        assert!(!is_yes_in("o", Lang::En));
+    }
+}
@@ -0,0 +1,59 @@
+use std::io::{self, BufRead, Write};
+
+use crate::error::GameError;
+
+/// Discards any input typed before a prompt is displayed.
+/// Stdin is line-buffered, so a player could otherwise press ENTER ahead of time
+/// and have the start and stop of an objective fire instantly.
+/// This is a no-op when stdin is not a terminal.
+#[cfg(unix)]
+pub fn flush_pending_input() {
+    // SAFETY: `tcflush` only reads the file descriptor and fails harmlessly
+    // with ENOTTY when stdin is not a terminal.
+    unsafe {
+        libc::tcflush(libc::STDIN_FILENO, libc::TCIFLUSH);
+    }
+}
+
+/// Discards any input typed before a prompt is displayed.
+/// Not supported on this platform: pending input is kept.
+#[cfg(not(unix))]
+pub fn flush_pending_input() {}
+
+/// Reads one line from a reader.
+/// Returns `GameError::InputClosed` when the reader has reached end of file.
+pub fn read_line_from<R: BufRead>(reader: &mut R) -> Result<String, GameError> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(GameError::InputClosed);
+    }
+    Ok(line)
+}
+
+/// Reads one line typed by the player, discarding anything typed ahead of time.
+pub fn read_line() -> Result<String, GameError> {
+    flush_pending_input();
+    read_line_from(&mut io::stdin().lock())
+}
+
+/// Displays a prompt on the current line and reads the player's answer.
+pub fn prompt(text: &str) -> Result<String, GameError> {
+    print!("{}", text);
+    io::stdout().flush()?;
+    read_line()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_read_line_from() {
+        let mut cursor = Cursor::new("y\nn\n");
+        assert_eq!(read_line_from(&mut cursor).unwrap(), "y\n");
+        assert_eq!(read_line_from(&mut cursor).unwrap(), "n\n");
+        // Once the input is exhausted, the match must end cleanly.
+        assert!(matches!(read_line_from(&mut cursor), Err(GameError::InputClosed)));
+    }
+}
@@ -0,0 +1,58 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::error::GameError;
+
+/// Reads a value from a JSON file. Contents that do not hold a `T` are
+/// reported with the error built by `invalid`.
+pub fn load<T: DeserializeOwned>(path: &Path, invalid: fn(String) -> GameError) -> Result<T, GameError> {
+    let json = fs::read_to_string(path)?;
+    serde_json::from_str(&json).map_err(|e| invalid(e.to_string()))
+}
+
+/// Reads a value from a JSON file like `load`, a missing file holding the default value.
+pub fn load_or_default<T: DeserializeOwned + Default>(
+    path: &Path,
+    invalid: fn(String) -> GameError,
+) -> Result<T, GameError> {
+    match load(path, invalid) {
+        Err(GameError::Io(e)) if e.kind() == io::ErrorKind::NotFound => Ok(T::default()),
+        result => result,
+    }
+}
+
+/// Writes a value to a JSON file, replacing its contents.
+pub fn save<T: Serialize>(value: &T, path: &Path, invalid: fn(String) -> GameError) -> Result<(), GameError> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| invalid(e.to_string()))?;
+    fs::write(path, json)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::process;
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join(format!("duel_game_json_test_{}.json", process::id()));
+        let missing = load::<BTreeMap<String, u32>>(&path, GameError::InvalidSave);
+        let default = load_or_default::<BTreeMap<String, u32>>(&path, GameError::InvalidSave);
+        let value = BTreeMap::from([("Alice".to_string(), 250)]);
+        save(&value, &path, GameError::InvalidSave).unwrap();
+        let loaded = load::<BTreeMap<String, u32>>(&path, GameError::InvalidSave);
+        fs::write(&path, "not json").unwrap();
+        let invalid = load_or_default::<BTreeMap<String, u32>>(&path, GameError::InvalidSave);
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(missing, Err(GameError::Io(_))));
+        assert_eq!(default.unwrap(), BTreeMap::new());
+        assert_eq!(loaded.unwrap(), value);
+        assert!(matches!(invalid, Err(GameError::InvalidSave(_))));
+    }
+}
@@ -0,0 +1,54 @@
+//! Duel game engine.
+//!
+//! Two players take turns stopping a running counter as close as possible to
+//! random objectives; the better average score damages the opponent's vitality.
+//! The library exposes the players, the rules, the match engine (interactive
+//! and headless, or driven one action at a time) and the events emitted during
+//! a match, so that other front ends can embed the game. The engine's
+//! internals (helpers, the turn loop, statistics) stay private: what front
+//! ends need of them is re-exported here. The `duel_game` binary is a thin
+//! terminal front end.
+
+pub mod analysis;
+pub mod arena;
+pub mod autosave;
+pub mod boss;
+pub mod bot;
+pub mod campaign;
+pub mod cards;
+pub mod classes;
+pub mod crits;
+pub mod defense;
+pub mod duel;
+pub mod events;
+pub mod i18n;
+pub mod luck;
+pub mod modifiers;
+pub mod optimize;
+pub mod profile;
+pub mod ruleset;
+pub mod server;
+pub mod simulation;
+pub mod spectator;
+pub mod stamina;
+pub mod stat_map;
+pub mod wager;
+pub mod wizard;
+
+mod error;
+mod game_runner;
+mod helper;
+mod input;
+mod json_file;
+mod stats;
+mod turns_helper;
+
+pub use classes::{Controller, Player, PlayerBuilder, PlayerRes};
+pub use duel::Duel;
+pub use error::GameError;
+pub use events::{EventBus, EventSink, GameEvent, MatchEvent, MatchEvents};
+pub use game_runner::{resume_game, run_game};
+pub use helper::{compute_score, score_tier, Effect, Penalty, RoundOutcome};
+pub use ruleset::Ruleset;
+pub use stats::{ObjectiveStat, PlayerStats};
+pub use turns_helper::{play_round, play_round_with, RoundReport};
@@ -0,0 +1,133 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::classes::{Player, PlayerRes};
+use crate::stat_map::StatKey;
+
+/// Name of the luck stat. Players have no luck unless they are given it as an
+/// extra stat, e.g. by the ruleset.
+pub const LUCK: &str = "luck";
+
+/// Value goals are pulled toward by luck: the middle of the counter, where neither
+/// an early nor a late stop makes it wrap.
+pub const FRIENDLY_GOAL: u8 = 50;
+
+/// How a player's luck bends the randomness of a match. Luck only draws from the
+/// match's generator when it can change something, so a match between players
+/// without luck replays the same for a seed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LuckRules {
+    /// Chance to forgive the first miss of an objective, per point of luck, in percent.
+    pub forgive_percent_per_point: u32,
+    /// Largest chance to forgive a miss, in percent.
+    pub max_forgive_percent: u32,
+    /// Share of their distance to `FRIENDLY_GOAL` goals are pulled by, per point of luck, in percent.
+    pub goal_pull_percent_per_point: u32,
+    /// Largest share goals are pulled by, in percent.
+    pub max_goal_pull_percent: u32,
+    /// Chance of critical hits added per point of luck, in percent.
+    pub crit_percent_per_point: u32,
+}
+
+impl Default for LuckRules {
+    /// Default luck rules: each point of luck gives a 2% chance to forgive a miss (up to 50%),
+    /// pulls goals 1% closer to the middle (up to 25%) and adds 1% to the chance of critical hits.
+    fn default() -> Self {
+        LuckRules {
+            forgive_percent_per_point: 2,
+            max_forgive_percent: 50,
+            goal_pull_percent_per_point: 1,
+            max_goal_pull_percent: 25,
+            crit_percent_per_point: 1,
+        }
+    }
+}
+
+impl LuckRules {
+    /// Chance to forgive the first miss of an objective with `luck`, in percent.
+    pub fn forgive_chance(&self, luck: u32) -> u32 {
+        luck.saturating_mul(self.forgive_percent_per_point).min(self.max_forgive_percent)
+    }
+
+    /// Forgives the first miss of a stop with a chance growing with `luck`.
+    /// Draws from `rng` only for a stop with misses and a chance to forgive them.
+    /// Returns whether a miss was forgiven.
+    pub fn forgive_miss<R: Rng + ?Sized>(&self, luck: u32, res: &mut PlayerRes, rng: &mut R) -> bool {
+        let chance = self.forgive_chance(luck);
+        if res.miss == 0 || chance == 0 || rng.gen_range(0..100) >= chance {
+            return false;
+        }
+        res.miss -= 1;
+        true
+    }
+
+    /// Share of their distance to `FRIENDLY_GOAL` goals are pulled by with `luck`, in percent.
+    pub fn goal_pull(&self, luck: u32) -> u32 {
+        luck.saturating_mul(self.goal_pull_percent_per_point).min(self.max_goal_pull_percent)
+    }
+
+    /// Pulls generated goals toward `FRIENDLY_GOAL` by a share growing with `luck`.
+    /// Nothing is drawn: the goals stay those of the seed, only closer to the middle.
+    pub fn pull_goals(&self, luck: u32, goals: &mut [u8]) {
+        let pull = self.goal_pull(luck).min(100) as i32;
+        for goal in goals {
+            let distance = FRIENDLY_GOAL as i32 - *goal as i32;
+            *goal = (*goal as i32 + distance * pull / 100) as u8;
+        }
+    }
+
+    /// Chance of critical hits added by `luck`, in percent.
+    pub fn crit_bonus(&self, luck: u32) -> u32 {
+        luck.saturating_mul(self.crit_percent_per_point)
+    }
+}
+
+/// Returns the luck of a player, 0 if they have none.
+pub fn luck(player: &Player) -> u32 {
+    player.stat(StatKey::Extra(LUCK.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_forgive_miss() {
+        let rules = LuckRules::default();
+        assert_eq!((rules.forgive_chance(10), rules.forgive_chance(40)), (20, 50));
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let always = LuckRules { max_forgive_percent: 100, forgive_percent_per_point: 100, ..rules };
+        let mut res = PlayerRes { miss: 2, ..PlayerRes::default() };
+        assert!(always.forgive_miss(1, &mut res, &mut rng));
+        assert_eq!(res.miss, 1);
+        // Without luck, or without a miss, nothing is drawn.
+        let before = rng.clone();
+        assert!(!rules.forgive_miss(0, &mut res, &mut rng));
+        assert!(!always.forgive_miss(1, &mut PlayerRes::default(), &mut rng));
+        assert_eq!(rng, before);
+        assert_eq!(res.miss, 1);
+    }
+
+    #[test]
+    fn test_pull_goals() {
+        let rules = LuckRules::default();
+        let mut goals = [0, 100, 50, 46];
+        rules.pull_goals(0, &mut goals);
+        assert_eq!(goals, [0, 100, 50, 46]);
+        rules.pull_goals(10, &mut goals);
+        assert_eq!(goals, [5, 95, 50, 46]);
+        // The pull is capped.
+        let mut goals = [0];
+        rules.pull_goals(1000, &mut goals);
+        assert_eq!(goals, [12]);
+    }
+
+    #[test]
+    fn test_luck_of_player() {
+        let player = Player::default();
+        assert_eq!(luck(&player), 0);
+        assert_eq!(luck(&player.with_stat(StatKey::Extra(LUCK.to_string()), 7)), 7);
+    }
+}
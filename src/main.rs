@@ -1,74 +1,674 @@
-use clap::Parser;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::thread;
+use std::time::Duration;
 
-mod classes;
-mod helper;
-mod turns_helper;
-mod game_runner;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand};
 
-use classes::Player;
-use game_runner::run_game;
+use duel_game::simulation::{run_batch, unsupported_rules, BatchSummary, SimConfig};
+use duel_game::analysis::{objective_distribution, score_distribution, uniform_goals, ScoreDistribution};
+use duel_game::arena::{entrants, run_arena, ArenaConfig, Standing};
+use duel_game::bot::{BotProcess, DEFAULT_BOT_TIMEOUT_MS};
+use duel_game::campaign::{print_ladder, run_campaign, DEFAULT_CAMPAIGN_PATH};
+use duel_game::cards::Deck;
+use duel_game::crits::CritRules;
+use duel_game::defense::Defense;
+use duel_game::autosave::{install_interrupt_handler, MatchState, DEFAULT_AUTOSAVE_PATH};
+use duel_game::optimize::{average_shares, grid, optimize, BuildResult, OptimizeConfig};
+use duel_game::i18n::{set_lang, set_quiet, tr, Lang, Msg};
+use duel_game::server::{ApiServer, DEFAULT_SERVER_ADDR};
+use duel_game::spectator::start_spectator_feed;
+use duel_game::events::EventBus;
+use duel_game::stat_map::{parse_extra_stat, StatKey};
+use duel_game::classes::Stat;
+use duel_game::luck::LUCK;
+use duel_game::modifiers::WeightedModifier;
+use duel_game::profile::{ProfileStore, Progression, XpAward, DEFAULT_PROFILES_PATH};
+use duel_game::stamina::StaminaRules;
+use duel_game::wager::Wagering;
+use duel_game::wizard::{ask_name, create_player};
+use duel_game::{resume_game, run_game, GameError, Player, PlayerBuilder, Ruleset};
+
+/// Names of the players when they are not given outside of a duel.
+const DEFAULT_NAMES: [&str; 2] = ["Michel", "Jacquie"];
 
 /// Duel Game in Rust
 #[derive(Parser, Debug)]
 #[command(name = "Duel Game", about = "A duel game implemented in Rust", version = "0.1")]
 struct Args {
-    /// First player's name.
-    #[arg(long, default_value = "Michel")]
-    name1: String,
-    /// Second player's name.
-    #[arg(long, default_value = "Jacquie")]
-    name2: String,
-    /// Player vitality.
-    #[arg(long, default_value_t = 50)]
-    vitality: u32,
-    /// Player speed (increment delay in ms).
-    #[arg(long, default_value_t = 50)]
-    speed: u32,
-    /// Player strength.
-    #[arg(long, default_value_t = 50)]
-    strength: u32,
+    /// First player's name (asked for if omitted in a duel, "Michel" otherwise).
+    #[arg(long, global = true)]
+    name1: Option<String>,
+    /// Second player's name (asked for if omitted in a duel, "Jacquie" otherwise).
+    #[arg(long, global = true)]
+    name2: Option<String>,
+    /// Player vitality (asked for if omitted in a duel, 50 otherwise).
+    #[arg(long, global = true)]
+    vitality: Option<u32>,
+    /// Player speed, the counter's increment delay in ms (asked for if omitted in a duel, 50 otherwise).
+    #[arg(long, global = true)]
+    speed: Option<u32>,
+    /// Player strength (asked for if omitted in a duel, 50 otherwise).
+    #[arg(long, global = true)]
+    strength: Option<u32>,
     /// Number of objectives per round.
-    #[arg(long, default_value_t = 5)]
+    #[arg(long, global = true, default_value_t = 5)]
     goals: u8,
+    /// Seed of the random number generator (random if omitted).
+    #[arg(long, global = true)]
+    seed: Option<u64>,
+    /// Language of the game's text (en or fr).
+    #[arg(long, global = true, default_value_t = Lang::En)]
+    lang: Lang,
+    /// Let a bot program play for the first player (executable and arguments).
+    #[arg(long, global = true, value_name = "COMMAND")]
+    bot1: Option<String>,
+    /// Let a bot program play for the second player (executable and arguments).
+    #[arg(long, global = true, value_name = "COMMAND")]
+    bot2: Option<String>,
+    /// Time a bot has to answer each request, in ms.
+    #[arg(long, global = true, default_value_t = DEFAULT_BOT_TIMEOUT_MS)]
+    bot_timeout_ms: u64,
+    /// Play a betting phase before each round, where the winner gets back this share
+    /// of their stake, in percent (200 if no value is given).
+    #[arg(long, global = true, value_name = "PERCENT", num_args = 0..=1, default_missing_value = "200")]
+    wager: Option<u32>,
+    /// Let the loser of each round try to block part of the damage with a counter stop,
+    /// at the cost of stamina.
+    #[arg(long, global = true)]
+    defense: bool,
+    /// Let players regenerate stamina every round and spend it on retrying objectives,
+    /// playing ability cards and slowing the counter down.
+    #[arg(long, global = true)]
+    stamina: bool,
+    /// Let perfect stops be critical hits with this chance, in percent (10 if no value
+    /// is given), and perfect stops in a row build a score streak.
+    #[arg(long, global = true, value_name = "PERCENT", num_args = 0..=1, default_missing_value = "10")]
+    crits: Option<u32>,
+    /// Give the first player the ability cards of this deck file (see decks/standard.json),
+    /// unless a bot plays for them.
+    #[arg(long, global = true, value_name = "FILE")]
+    deck1: Option<PathBuf>,
+    /// Give the second player the ability cards of this deck file, unless a bot plays for them.
+    #[arg(long, global = true, value_name = "FILE")]
+    deck2: Option<PathBuf>,
+    /// Broadcast the match's events to spectators connecting to this address (e.g. 0.0.0.0:7878).
+    #[arg(long, global = true, value_name = "ADDR")]
+    spectate: Option<String>,
+    /// Build the duel's players from their profiles in this file, and record
+    /// the experience they earn (instead of taking their stats from the options).
+    #[arg(long, global = true, value_name = "FILE", conflicts_with_all = ["vitality", "speed", "strength"])]
+    profiles: Option<PathBuf>,
+    /// Add a modifier to the pool each round's modifier is drawn from, as NAME or NAME:WEIGHT
+    /// (double_speed, hidden_goals, double_damage, exact_hits_only, counter_backwards,
+    /// misses_cost_vitality, or none for a round without modifier). Repeat for each entry.
+    #[arg(long = "modifier", global = true, value_name = "NAME[:WEIGHT]")]
+    modifiers: Vec<WeightedModifier>,
+    /// Give every player an extra stat, as NAME=VALUE (e.g. armor=5, or luck=10 to be
+    /// forgiven misses, get friendlier goals and more critical hits). Repeat for each stat.
+    #[arg(long = "stat", global = true, value_name = "NAME=VALUE", value_parser = parse_extra_stat)]
+    extra_stats: Vec<(StatKey, u32)>,
+    /// Resume a match saved after an interruption.
+    #[arg(long, value_name = "FILE")]
+    resume: Option<PathBuf>,
+    /// File the match is saved to when interrupted with Ctrl-C.
+    #[arg(long, value_name = "FILE", default_value = DEFAULT_AUTOSAVE_PATH)]
+    autosave: PathBuf,
+    /// Command to run (an interactive duel if omitted).
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Commands available besides the interactive duel.
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Simulate many headless matches between the two players.
+    Simulate {
+        /// Number of matches to simulate.
+        #[arg(long, default_value_t = 10_000)]
+        matches: u64,
+        /// Number of worker threads (all cores if omitted).
+        #[arg(long)]
+        threads: Option<usize>,
+        /// Standard deviation of the simulated timing error, in ms.
+        #[arg(long, default_value_t = 80.0, value_parser = parse_sigma)]
+        sigma_ms: f64,
+        /// Number of rounds after which a match is a draw.
+        #[arg(long, default_value_t = 1000)]
+        max_rounds: u32,
+    },
+    /// Compute the exact score distribution of an objective for the first player's stats.
+    Analyze {
+        /// Standard deviation of the human timing error, in ms.
+        #[arg(long, default_value_t = 80.0, value_parser = parse_sigma)]
+        sigma_ms: f64,
+        /// Analyze a single goal instead of goals drawn uniformly from 0 to 100.
+        #[arg(long, value_parser = clap::value_parser!(u8).range(0..=100))]
+        goal: Option<u8>,
+    },
+    /// Search the best split of a stat budget against the players' stats.
+    Optimize {
+        /// Total points split between vitality, speed and strength (the reference's total if omitted).
+        #[arg(long)]
+        budget: Option<u32>,
+        /// Granularity of the search grid.
+        #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u32).range(1..))]
+        step: u32,
+        /// Number of matches simulated for each build.
+        #[arg(long, default_value_t = 500)]
+        matches: u64,
+        /// Number of worker threads (all cores if omitted).
+        #[arg(long)]
+        threads: Option<usize>,
+        /// Standard deviation of the simulated timing error, in ms.
+        #[arg(long, default_value_t = 80.0, value_parser = parse_sigma)]
+        sigma_ms: f64,
+        /// Number of rounds after which a match is a draw.
+        #[arg(long, default_value_t = 1000)]
+        max_rounds: u32,
+        /// Number of builds to report.
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+    },
+    /// Play a round-robin tournament between bot programs.
+    Arena {
+        /// Bot commands (executable and arguments, quoted if needed).
+        #[arg(required = true, num_args = 2..)]
+        bots: Vec<String>,
+        /// Number of seeds played by each pair of bots.
+        #[arg(long, default_value_t = 10)]
+        series: u32,
+        /// Number of rounds after which a match is a draw.
+        #[arg(long, default_value_t = 1000)]
+        max_rounds: u32,
+    },
+    /// Face a ladder of computer opponents, one after the other, as the first player.
+    Campaign {
+        /// Stage to play (the first one not beaten yet if omitted).
+        #[arg(long)]
+        stage: Option<usize>,
+        /// List the stages and what it takes to unlock them, without playing.
+        #[arg(long)]
+        list: bool,
+        /// File the campaign progress is saved to.
+        #[arg(long, value_name = "FILE", default_value = DEFAULT_CAMPAIGN_PATH)]
+        save: PathBuf,
+    },
+    /// Show a player's profile, and spend the stat points earned by leveling up.
+    Profile {
+        /// Name of the player.
+        name: String,
+        /// Stat to spend points on (vitality, speed or strength).
+        #[arg(long, value_name = "STAT")]
+        allocate: Option<Stat>,
+        /// Number of points to spend.
+        #[arg(long, default_value_t = 1, requires = "allocate")]
+        points: u32,
+    },
+    /// Serve a local HTTP API to create, play and inspect matches.
+    Serve {
+        /// Address to listen on.
+        #[arg(long, default_value = DEFAULT_SERVER_ADDR)]
+        addr: String,
+    },
 }
 
-fn main() {
+fn main() -> ExitCode {
     // Initialize the logger.
     env_logger::init();
 
     // Parse command-line arguments.
     let args = Args::parse();
+    // Bots may exit at any time: writing to them must fail with an error
+    // instead of killing the game, so SIGPIPE stays ignored when they play.
+    if args.bot1.is_none() && args.bot2.is_none() && !matches!(args.command, Some(Command::Arena { .. })) {
+        restore_sigpipe();
+    }
+    set_lang(args.lang);
+
+    let rules = Ruleset {
+        modifier_pool: args.modifiers.clone(),
+        extra_stats: args.extra_stats.iter().cloned().collect(),
+        defense: args.defense.then(Defense::default),
+        stamina: args.stamina.then(StaminaRules::default),
+        crits: args.crits.map(|chance_percent| CritRules { chance_percent, ..CritRules::default() }),
+        wagering: args.wager.map(|multiplier_percent| Wagering { multiplier_percent, ..Wagering::default() }),
+        ..Ruleset::with_goal_count(args.goals)
+    };
+
+    // Simulated matches and score distributions only model the rules the headless engine plays;
+    // the analysis does not model luck either.
+    if let Some(command @ (Command::Simulate { .. } | Command::Analyze { .. } | Command::Optimize { .. })) = &args.command {
+        let mut unsupported = unsupported_rules(&rules);
+        if matches!(command, Command::Analyze { .. }) && rules.extra_stats.contains_key(&StatKey::Extra(LUCK.to_string())) {
+            unsupported.push("luck");
+        }
+        if !unsupported.is_empty() {
+            let error = tr(Msg::HeadlessUnsupported { rules: &unsupported.join(", ") });
+            Args::command().error(ErrorKind::ArgumentConflict, error).exit();
+        }
+    }
 
-    // Create two players using the provided arguments.
-    let p1 = Player::new(args.name1, args.vitality, args.speed, args.strength);
-    let p2 = Player::new(args.name2, args.vitality, args.speed, args.strength);
-    let goal_count = args.goals;
+    // Create two players using the provided arguments. In a new duel, the players
+    // choose what the arguments leave out, or play with their profiles; other
+    // commands use the default values.
+    let [b1, b2] = [player_builder(args.name1.clone(), &args), player_builder(args.name2.clone(), &args)];
+    let players = match (&args.command, &args.resume, &args.profiles) {
+        (None, None, Some(path)) => profile_players([args.name1.clone(), args.name2.clone()], path, &rules.progression),
+        (None, None, None) => create_player(1, b1).and_then(|p1| Ok([p1, create_player(2, b2)?])),
+        _ => default_player(b1, DEFAULT_NAMES[0]).and_then(|p1| Ok([p1, default_player(b2, DEFAULT_NAMES[1])?])),
+    };
+    let [p1, p2] = match players {
+        Ok(players) => players,
+        Err(e) => return exit_with(e),
+    };
+    let seed = args.seed.unwrap_or_else(rand::random);
 
-    // Start the game with the two players and the specified number of objectives per round.
-    run_game(p1, p2, goal_count);
+    // Let spectators follow the matches played by this process.
+    let events = EventBus::default();
+    if let Some(addr) = &args.spectate {
+        match start_spectator_feed(addr, &events) {
+            Ok(addr) => println!("{}", tr(Msg::SpectatorListening { addr: &addr.to_string() })),
+            Err(e) => return exit_with(e),
+        }
+    }
+
+    match args.command {
+        None => {
+            // Save the match if the players press Ctrl-C.
+            if let Err(e) = install_interrupt_handler(args.autosave, &events) {
+                log::warn!("Could not install the Ctrl-C handler: {}", e);
+            }
+            // Start the game with the two players and the specified number of objectives per round,
+            // or resume a saved match. Bots are attached to the players either way, while
+            // a resumed match keeps the decks in the state they were saved in.
+            let bots = [args.bot1, args.bot2];
+            let timeout = Duration::from_millis(args.bot_timeout_ms);
+            let events = events.for_match(1);
+            let result = match args.resume {
+                Some(path) => MatchState::load(&path).and_then(|mut state| {
+                    state.players = attach_bots(state.players.clone(), &bots, timeout)?;
+                    resume_game(state, &events)
+                }),
+                None => attach_decks([p1, p2], &[args.deck1, args.deck2])
+                    .and_then(|players| attach_bots(players, &bots, timeout))
+                    .and_then(|[p1, p2]| run_game(p1, p2, &rules, seed, &events)),
+            };
+            // Players with a profile earn experience from the finished match.
+            let result = match (result, &args.profiles) {
+                (Ok(state), Some(path)) => award_xp(path, &state),
+                (result, _) => result.map(|_| ()),
+            };
+            if let Err(e) = result {
+                return exit_with(e);
+            }
+        }
+        Some(Command::Simulate { matches, threads, sigma_ms, max_rounds }) => {
+            let threads = threads.unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+            let cfg = SimConfig { rules, sigma_ms, max_rounds };
+            let summary = run_batch(&p1, &p2, &cfg, matches, seed, threads);
+            print_summary(&p1, &p2, &summary, seed, threads);
+        }
+        Some(Command::Analyze { sigma_ms, goal }) => {
+            let distribution = match goal {
+                Some(goal) => objective_distribution(goal, p1.speed(), p1.strength(), sigma_ms, &rules),
+                None => score_distribution(&uniform_goals(), p1.speed(), p1.strength(), sigma_ms, &rules),
+            };
+            let wrap_ms = 101 * p1.speed().max(1) as u64;
+            println!("{}", tr(Msg::AnalyzeModel { speed: p1.speed(), strength: p1.strength(), sigma_ms, wrap_ms }));
+            println!("{}", tr(goal.map_or(Msg::AnalyzeUniformGoals, Msg::AnalyzeGoal)));
+            print_distribution(&distribution);
+        }
+        Some(Command::Optimize { budget, step, matches, threads, sigma_ms, max_rounds, top }) => {
+            // The players' stats are those of the reference opponent.
+            let threads = threads.unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+            let budget = budget.unwrap_or(p2.vitality() + p2.speed() + p2.strength());
+            let builds = grid(budget, step).len();
+            if builds == 0 {
+                let error = tr(Msg::OptimizeInvalidBudget { budget, step });
+                Args::command().error(ErrorKind::ValueValidation, error).exit();
+            }
+            let cfg = OptimizeConfig {
+                sim: SimConfig { rules, sigma_ms, max_rounds },
+                budget,
+                step,
+                matches,
+                seed,
+                threads,
+            };
+            println!("{}", tr(Msg::OptimizeStarted { builds, budget, matches, seed }));
+            println!("{}", tr(Msg::OptimizeReference { vitality: p2.vitality(), speed: p2.speed(), strength: p2.strength() }));
+            let results = optimize(&p2, &cfg);
+            print_builds(&results[..top.min(results.len())]);
+        }
+        Some(Command::Arena { bots, series, max_rounds }) => {
+            let entrants = entrants(&bots);
+            let cfg = ArenaConfig {
+                rules,
+                template: p1,
+                series,
+                base_seed: seed,
+                max_rounds,
+                timeout: Duration::from_millis(args.bot_timeout_ms),
+            };
+            println!("{}", tr(Msg::ArenaStarted { entrants: entrants.len(), series, seed }));
+            // Only the results of the matches are printed, not their rounds.
+            set_quiet(true);
+            let standings = run_arena(&entrants, &cfg, &events, |m| {
+                let winner = |w: usize| if w == 0 { m.first } else { m.second };
+                let msg = match (m.outcome.winner, &m.error) {
+                    (Some(w), Some(error)) => {
+                        Msg::ArenaMatchForfeited { first: m.first, second: m.second, seed: m.seed, winner: winner(w), error }
+                    }
+                    (None, Some(error)) => Msg::ArenaMatchFailed { first: m.first, second: m.second, seed: m.seed, error },
+                    (Some(w), None) => Msg::ArenaMatchWon {
+                        first: m.first,
+                        second: m.second,
+                        seed: m.seed,
+                        winner: winner(w),
+                        rounds: m.outcome.rounds,
+                    },
+                    (None, None) => Msg::ArenaMatchDrawn { first: m.first, second: m.second, seed: m.seed, rounds: m.outcome.rounds },
+                };
+                println!("{}", tr(msg));
+            });
+            print_standings(&standings);
+        }
+        Some(Command::Campaign { stage, list, save }) => {
+            // Stages are numbered from 1 on the command line. A bot may play the campaign.
+            let result = if list {
+                print_ladder(&save)
+            } else {
+                let timeout = Duration::from_millis(args.bot_timeout_ms);
+                attach_decks([p1, p2], &[args.deck1, None])
+                    .and_then(|players| attach_bots(players, &[args.bot1, None], timeout))
+                    .and_then(|[p1, _]| run_campaign(&p1, stage.map(|s| s.saturating_sub(1)), &save, seed, &events))
+            };
+            if let Err(e) = result {
+                return exit_with(e);
+            }
+        }
+        Some(Command::Profile { name, allocate, points }) => {
+            let path = args.profiles.unwrap_or_else(|| PathBuf::from(DEFAULT_PROFILES_PATH));
+            if let Err(e) = show_profile(&path, &name, allocate.map(|stat| (stat, points)), &rules.progression) {
+                return exit_with(e);
+            }
+        }
+        Some(Command::Serve { addr }) => {
+            let server = match ApiServer::bind(&addr, events) {
+                Ok(server) => server,
+                Err(e) => return exit_with(e),
+            };
+            let addr = server.local_addr().map_or(addr, |a| a.to_string());
+            println!("{}", tr(Msg::ServerListening { addr: &addr }));
+            server.run();
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+/// Starts the builder of a player from the command-line options.
+fn player_builder(name: Option<String>, args: &Args) -> PlayerBuilder {
+    let mut builder = PlayerBuilder::new();
+    if let Some(name) = name {
+        builder = builder.name(name);
+    }
+    for (stat, value) in Stat::ALL.into_iter().zip([args.vitality, args.speed, args.strength]) {
+        if let Some(value) = value {
+            builder = builder.stat(stat, value);
+        }
+    }
+    builder
+}
+
+/// Parses a standard deviation of the timing error, which cannot be negative.
+fn parse_sigma(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(sigma) if sigma >= 0.0 && sigma.is_finite() => Ok(sigma),
+        Ok(_) => Err(format!("'{}' ms is not allowed, the deviation cannot be negative", s)),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Builds a player, with `name` and the default stats for what the builder is missing.
+fn default_player(builder: PlayerBuilder, name: &str) -> Result<Player, GameError> {
+    let builder = match builder.get_name() {
+        Some(_) => builder,
+        None => builder.name(name),
+    };
+    Ok(builder.build()?)
+}
+
+/// Builds the players of a duel from their profiles, asking for the names that are not given.
+fn profile_players(names: [Option<String>; 2], path: &Path, progression: &Progression) -> Result<[Player; 2], GameError> {
+    let store = ProfileStore::load(path)?;
+    let load = |index: usize, name: Option<String>| -> Result<Player, GameError> {
+        let name = match name {
+            Some(name) => name,
+            None => ask_name(index)?,
+        };
+        let profile = store.profiles.get(&name).cloned().unwrap_or_default();
+        let player = profile.builder(&name, progression).build()?;
+        println!("{}", tr(Msg::ProfileLevel { name: &player.name, level: profile.level(progression), xp: profile.xp }));
+        Ok(player)
+    };
+    let [n1, n2] = names;
+    Ok([load(1, n1)?, load(2, n2)?])
+}
+
+/// Records a finished match in the players' profiles and reports the experience they earned.
+fn award_xp(path: &Path, state: &MatchState) -> Result<(), GameError> {
+    let progression = &state.rules.progression;
+    let mut store = ProfileStore::load(path)?;
+    println!();
+    for (i, player) in state.players.iter().enumerate() {
+        let award = XpAward::from_match(state, i);
+        let profile = store.profile(&player.name);
+        let levels = profile.record_match(&award, progression);
+        let level = profile.level(progression);
+        println!("{}", tr(Msg::XpGained { name: &player.name, xp: award.xp(progression), level }));
+        if levels > 0 {
+            let points = profile.unspent_points(progression);
+            println!("{}", tr(Msg::LevelUp { name: &player.name, level, points }));
+        }
+    }
+    store.save(path)
+}
+
+/// Prints a player's profile, after spending points on a stat if asked to.
+fn show_profile(path: &Path, name: &str, allocate: Option<(Stat, u32)>, progression: &Progression) -> Result<(), GameError> {
+    let mut store = ProfileStore::load(path)?;
+    let profile = store.profile(name);
+    if let Some((stat, points)) = allocate {
+        profile.allocate(stat, points, progression)?;
+        println!("{}", tr(Msg::ProfileAllocated { name, stat, points }));
+    }
+    let level = profile.level(progression);
+    let next_xp = (level < progression.max_level).then(|| progression.xp_for_level(level + 1));
+    println!("{}", tr(Msg::ProfileSummary { name, level, xp: profile.xp, next_xp }));
+    println!(
+        "{}",
+        tr(Msg::ProfileStats {
+            vitality: profile.stat(Stat::Vitality, progression),
+            speed: profile.stat(Stat::Speed, progression),
+            strength: profile.stat(Stat::Strength, progression),
+            points: profile.unspent_points(progression),
+        })
+    );
+    println!("{}", tr(Msg::ProfileRecord { matches: profile.matches, wins: profile.wins }));
+    if allocate.is_some() {
+        store.save(path)?;
+    }
+    Ok(())
+}
+
+/// Starts the bots playing for the players, for those given one.
+/// The bot protocol has no ability cards, so a player with a deck cannot be a bot.
+fn attach_bots(players: [Player; 2], commands: &[Option<String>; 2], timeout: Duration) -> Result<[Player; 2], GameError> {
+    let [p1, p2] = players;
+    let attach = |player: Player, command: &Option<String>| match command {
+        Some(_) if !player.deck.is_empty() => Err(GameError::InvalidDeck(tr(Msg::BotWithDeck { name: &player.name }))),
+        Some(command) => BotProcess::spawn(command, timeout).map(|bot| player.with_bot(bot)),
+        None => Ok(player),
+    };
+    Ok([attach(p1, &commands[0])?, attach(p2, &commands[1])?])
+}
+
+/// Gives the players the decks read from the given files, for those given one.
+fn attach_decks(players: [Player; 2], paths: &[Option<PathBuf>; 2]) -> Result<[Player; 2], GameError> {
+    let [p1, p2] = players;
+    let attach = |player: Player, path: &Option<PathBuf>| match path {
+        Some(path) => Deck::load(path).map(|deck| player.with_deck(deck)),
+        None => Ok(player),
+    };
+    Ok([attach(p1, &paths[0])?, attach(p2, &paths[1])?])
+}
+
+/// Reports the error that ended the game and converts it into an exit code.
+/// A clean end, such as closed input, is reported on stdout.
+fn exit_with(e: GameError) -> ExitCode {
+    let code = e.exit_code();
+    if code == 0 {
+        println!("{}", tr(Msg::GameEnded { error: &e.to_string() }));
+    } else {
+        eprintln!("{}", tr(Msg::GameEnded { error: &e.to_string() }));
+    }
+    ExitCode::from(code)
+}
+
+/// Restores the default SIGPIPE behavior, so that writing to a closed pipe
+/// (e.g. `duel_game | head`) ends the process quietly instead of panicking.
+#[cfg(unix)]
+fn restore_sigpipe() {
+    // SAFETY: resetting a signal to its default disposition at startup,
+    // before any other thread is spawned (only the argument parser has run).
+    unsafe {
+        libc::signal(libc::SIGPIPE, libc::SIG_DFL);
+    }
+}
+
+/// Restores the default SIGPIPE behavior (nothing to do on this platform).
+#[cfg(not(unix))]
+fn restore_sigpipe() {}
+
+/// Prints the results of a batch of simulated matches.
+fn print_summary(p1: &Player, p2: &Player, summary: &BatchSummary, seed: u64, threads: usize) {
+    let percent = |n: u64| if summary.matches == 0 { 0.0 } else { 100.0 * n as f64 / summary.matches as f64 };
+    println!("{}", tr(Msg::SimSummary { matches: summary.matches, seed, threads }));
+    for (player, wins) in [p1, p2].iter().zip(summary.wins) {
+        println!("{}", tr(Msg::SimWins { name: &player.name, wins, percent: percent(wins) }));
+    }
+    println!("{}", tr(Msg::SimDraws { draws: summary.draws, percent: percent(summary.draws) }));
+    if summary.matches > 0 {
+        println!("{}", tr(Msg::SimAverageRounds(summary.total_rounds as f64 / summary.matches as f64)));
+    }
+}
+
+/// Prints the score distribution of an objective.
+fn print_distribution(distribution: &ScoreDistribution) {
+    println!(
+        "{}",
+        tr(Msg::AnalyzeExpected { mean: distribution.mean(), std_dev: distribution.std_dev() })
+    );
+    println!("{}", tr(Msg::AnalyzeMiss(distribution.miss)));
+    for (i, probability) in distribution.tiers.iter().enumerate() {
+        println!("{}", tr(Msg::AnalyzeTier { tier: i + 1, probability: *probability }));
+    }
+    println!("{}", tr(Msg::AnalyzeScoresTitle));
+    for (score, probability) in distribution.scores.iter().rev() {
+        // Leave out the outcomes too rare to matter.
+        if *probability >= 0.0005 {
+            println!("{}", tr(Msg::AnalyzeScore { score: *score, probability: *probability }));
+        }
+    }
+}
+
+/// Prints the best builds found by the optimizer.
+fn print_builds(best: &[BuildResult]) {
+    println!();
+    println!("{}", tr(Msg::OptimizeTableHeader));
+    for (i, result) in best.iter().enumerate() {
+        let (low, high) = result.interval;
+        println!(
+            "{}",
+            tr(Msg::OptimizeRow {
+                rank: i + 1,
+                vitality: result.build.vitality,
+                speed: result.build.speed,
+                strength: result.build.strength,
+                rate: result.win_rate,
+                low,
+                high,
+            })
+        );
+    }
+    let [vitality, speed, strength] = average_shares(best);
+    println!("{}", tr(Msg::OptimizeShares { top: best.len(), vitality, speed, strength }));
+}
+
+/// Prints the results table of an arena.
+fn print_standings(standings: &[Standing]) {
+    println!();
+    println!("{}", tr(Msg::ArenaTableHeader));
+    for (i, standing) in standings.iter().enumerate() {
+        let (low, high) = standing.confidence_interval();
+        println!(
+            "{}",
+            tr(Msg::ArenaRow {
+                rank: i + 1,
+                name: &standing.name,
+                games: standing.games(),
+                wins: standing.wins,
+                draws: standing.draws,
+                losses: standing.losses,
+                rate: standing.win_rate(),
+                low,
+                high,
+            })
+        );
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use clap::Parser;
+    use duel_game::modifiers::Modifier;
 
     #[test]
     fn test_args_defaults() {
         // When no command-line arguments are provided, default values should be used.
-        let args = Args::parse_from(&["test"]);
-        assert_eq!(args.name1, "Michel");
-        assert_eq!(args.name2, "Jacquie");
-        assert_eq!(args.vitality, 50);
-        assert_eq!(args.speed, 50);
-        assert_eq!(args.strength, 50);
+        let args = Args::parse_from(["test"]);
+        // Names and stats left out are asked for by the creation wizard.
+        assert_eq!(args.name1, None);
+        assert_eq!(args.name2, None);
+        assert_eq!(args.vitality, None);
+        assert_eq!(args.speed, None);
+        assert_eq!(args.strength, None);
         assert_eq!(args.goals, 5);
+        assert_eq!(args.seed, None);
+        assert_eq!(args.lang, Lang::En);
+        assert_eq!(args.bot1, None);
+        assert_eq!(args.bot2, None);
+        assert_eq!(args.bot_timeout_ms, DEFAULT_BOT_TIMEOUT_MS);
+        assert_eq!(args.deck1, None);
+        assert_eq!(args.deck2, None);
+        assert_eq!(args.spectate, None);
+        assert_eq!(args.profiles, None);
+        assert!(args.modifiers.is_empty());
+        assert_eq!(args.wager, None);
+        assert!(!args.defense);
+        assert!(!args.stamina);
+        assert_eq!(args.resume, None);
+        assert_eq!(args.autosave, PathBuf::from(DEFAULT_AUTOSAVE_PATH));
+        assert!(args.command.is_none());
     }
 
     #[test]
     fn test_args_custom_values() {
         // Provide custom command-line arguments and verify they are parsed correctly.
-        let args = Args::parse_from(&[
+        let args = Args::parse_from([
             "test",
             "--name1", "Alice",
             "--name2", "Bob",
@@ -76,12 +676,182 @@ mod tests {
             "--speed", "40",
             "--strength", "70",
             "--goals", "7",
+            "--bot2", "python3 bot.py --easy",
+            "--deck1", "decks/standard.json",
         ]);
-        assert_eq!(args.name1, "Alice");
-        assert_eq!(args.name2, "Bob");
-        assert_eq!(args.vitality, 80);
-        assert_eq!(args.speed, 40);
-        assert_eq!(args.strength, 70);
+        assert_eq!(args.name1.as_deref(), Some("Alice"));
+        assert_eq!(args.name2.as_deref(), Some("Bob"));
+        assert_eq!(args.vitality, Some(80));
+        assert_eq!(args.speed, Some(40));
+        assert_eq!(args.strength, Some(70));
         assert_eq!(args.goals, 7);
+        assert_eq!(args.bot2.as_deref(), Some("python3 bot.py --easy"));
+        assert_eq!(args.deck1, Some(PathBuf::from("decks/standard.json")));
+    }
+
+    #[test]
+    fn test_players_from_args() {
+        // Outside of a duel, missing values take the defaults, and the limits apply.
+        let args = Args::parse_from(["test", "simulate", "--name2", "Bob", "--speed", "30"]);
+        let p1 = default_player(player_builder(args.name1.clone(), &args), DEFAULT_NAMES[0]).unwrap();
+        let p2 = default_player(player_builder(args.name2.clone(), &args), DEFAULT_NAMES[1]).unwrap();
+        assert_eq!(p1, Player::new("Michel".to_string(), 50, 30, 50));
+        assert_eq!(p2.name, "Bob");
+        let args = Args::parse_from(["test", "simulate", "--speed", "0"]);
+        let result = default_player(player_builder(None, &args), "Michel");
+        assert!(matches!(result, Err(GameError::InvalidPlayer(_))));
+    }
+
+    #[test]
+    fn test_bot_with_deck_is_refused() {
+        // Bots cannot play cards: a player with a deck is not given a bot.
+        let deck: Deck = serde_json::from_str(r#"{"cards": [{"name": "Shield", "effect": "shield"}]}"#).unwrap();
+        let players = [Player::default().with_deck(deck), Player::default()];
+        let result = attach_bots(players, &[Some("true".to_string()), None], Duration::from_secs(1));
+        assert!(matches!(result, Err(GameError::InvalidDeck(_))));
+    }
+
+    #[test]
+    fn test_args_campaign() {
+        let args = Args::parse_from(["test", "campaign", "--stage", "2", "--name1", "Alice"]);
+        assert!(matches!(
+            args.command,
+            Some(Command::Campaign { stage: Some(2), list: false, ref save }) if save == &PathBuf::from(DEFAULT_CAMPAIGN_PATH)
+        ));
+        let args = Args::parse_from(["test", "campaign", "--list", "--save", "progress.json"]);
+        assert!(matches!(args.command, Some(Command::Campaign { stage: None, list: true, .. })));
+    }
+
+    #[test]
+    fn test_args_profiles() {
+        let args = Args::parse_from(["test", "--profiles", "profiles.json", "--name1", "Alice"]);
+        assert_eq!(args.profiles, Some(PathBuf::from("profiles.json")));
+        // Stats come from the profiles, not from the options.
+        assert!(Args::try_parse_from(["test", "--profiles", "profiles.json", "--speed", "30"]).is_err());
+        let args = Args::parse_from(["test", "profile", "Alice", "--allocate", "speed", "--points", "5"]);
+        assert!(matches!(
+            args.command,
+            Some(Command::Profile { ref name, allocate: Some(Stat::Speed), points: 5 }) if name == "Alice"
+        ));
+        assert!(Args::try_parse_from(["test", "profile", "Alice", "--allocate", "luck"]).is_err());
+    }
+
+    #[test]
+    fn test_args_modifiers() {
+        let args = Args::parse_from(["test", "--modifier", "double_damage:3", "--modifier", "none:2"]);
+        assert_eq!(
+            args.modifiers,
+            vec![
+                WeightedModifier { modifier: Some(Modifier::DoubleDamage), weight: 3 },
+                WeightedModifier { modifier: None, weight: 2 },
+            ]
+        );
+        assert!(Args::try_parse_from(["test", "--modifier", "triple_damage"]).is_err());
+    }
+
+    #[test]
+    fn test_args_wager_and_defense() {
+        let args = Args::parse_from(["test", "--wager"]);
+        assert_eq!(args.wager, Some(200));
+        let args = Args::parse_from(["test", "--wager", "150", "simulate", "--defense"]);
+        assert_eq!(args.wager, Some(150));
+        assert!(args.defense);
+        assert!(matches!(args.command, Some(Command::Simulate { .. })));
+    }
+
+    #[test]
+    fn test_args_stamina() {
+        let args = Args::parse_from(["test", "--stamina", "arena", "./a", "./b"]);
+        assert!(args.stamina);
+        assert!(matches!(args.command, Some(Command::Arena { .. })));
+    }
+
+    #[test]
+    fn test_args_crits() {
+        assert_eq!(Args::parse_from(["test"]).crits, None);
+        assert_eq!(Args::parse_from(["test", "--crits"]).crits, Some(10));
+        assert_eq!(Args::parse_from(["test", "--crits", "25"]).crits, Some(25));
+    }
+
+    #[test]
+    fn test_args_extra_stats() {
+        let args = Args::parse_from(["test", "--stat", "armor=5", "--stat", "focus=2"]);
+        let names: Vec<String> = args.extra_stats.iter().map(|(key, value)| format!("{}={}", key, value)).collect();
+        assert_eq!(names, ["armor=5", "focus=2"]);
+        assert!(Args::try_parse_from(["test", "--stat", "speed=5"]).is_err());
+    }
+
+    #[test]
+    fn test_args_simulate() {
+        // Player options can be given after the subcommand.
+        let args = Args::parse_from([
+            "test",
+            "simulate",
+            "--matches", "100",
+            "--threads", "4",
+            "--strength", "60",
+            "--seed", "9",
+        ]);
+        assert_eq!(args.strength, Some(60));
+        assert_eq!(args.seed, Some(9));
+        match args.command {
+            Some(Command::Simulate { matches, threads, .. }) => {
+                assert_eq!(matches, 100);
+                assert_eq!(threads, Some(4));
+            }
+            _ => panic!("expected the simulate command"),
+        }
+    }
+
+    #[test]
+    fn test_args_analyze() {
+        let args = Args::parse_from(["test", "analyze", "--speed", "40", "--sigma-ms", "60"]);
+        assert_eq!(args.speed, Some(40));
+        assert!(matches!(args.command, Some(Command::Analyze { sigma_ms, goal: None }) if sigma_ms == 60.0));
+        assert!(Args::try_parse_from(["test", "analyze", "--goal", "101"]).is_err());
+        assert!(Args::try_parse_from(["test", "analyze", "--sigma-ms", "-1"]).is_err());
+        assert!(Args::try_parse_from(["test", "simulate", "--sigma-ms", "NaN"]).is_err());
+        assert!(Args::try_parse_from(["test", "analyze", "--sigma-ms", "0"]).is_ok());
+    }
+
+    #[test]
+    fn test_args_optimize() {
+        let args = Args::parse_from(["test", "optimize", "--budget", "120", "--step", "10"]);
+        match args.command {
+            Some(Command::Optimize { budget, step, matches, top, .. }) => {
+                assert_eq!(budget, Some(120));
+                assert_eq!(step, 10);
+                assert_eq!(matches, 500);
+                assert_eq!(top, 10);
+            }
+            _ => panic!("expected the optimize command"),
+        }
+        assert!(Args::try_parse_from(["test", "optimize", "--step", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_args_arena() {
+        let args = Args::parse_from(["test", "arena", "python3 a.py", "./b", "--series", "3"]);
+        match args.command {
+            Some(Command::Arena { bots, series, max_rounds }) => {
+                assert_eq!(bots, ["python3 a.py", "./b"]);
+                assert_eq!(series, 3);
+                assert_eq!(max_rounds, 1000);
+            }
+            _ => panic!("expected the arena command"),
+        }
+        // At least two bots are needed.
+        assert!(Args::try_parse_from(["test", "arena", "./b"]).is_err());
+    }
+
+    #[test]
+    fn test_args_serve() {
+        let args = Args::parse_from(["test", "serve"]);
+        match args.command {
+            Some(Command::Serve { addr }) => assert_eq!(addr, DEFAULT_SERVER_ADDR),
+            _ => panic!("expected the serve command"),
+        }
+        let args = Args::parse_from(["test", "serve", "--addr", "0.0.0.0:9000"]);
+        assert!(matches!(args.command, Some(Command::Serve { addr }) if addr == "0.0.0.0:9000"));
     }
 }
@@ -0,0 +1,274 @@
+use std::str::FromStr;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::classes::{Player, PlayerRes};
+use crate::helper::score_tier;
+use crate::ruleset::Ruleset;
+
+/// Vitality lost for each miss under `Modifier::MissesCostVitality`.
+pub const MISS_VITALITY_COST: u32 = 5;
+
+/// A special rule that changes how a round is played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Modifier {
+    /// The counter runs twice as fast.
+    DoubleSpeed,
+    /// The goals of a turn are not announced: each one is only revealed when its objective starts.
+    HiddenGoals,
+    /// The loser of the round takes twice the damage.
+    DoubleDamage,
+    /// Only exact hits score: every other stop scores 0.
+    ExactHitsOnly,
+    /// The counter runs from 100 down to 0.
+    CounterBackwards,
+    /// Each miss costs the player vitality at once.
+    MissesCostVitality,
+}
+
+impl Modifier {
+    /// Every modifier, in declaration order.
+    pub const ALL: [Modifier; 6] = [
+        Modifier::DoubleSpeed,
+        Modifier::HiddenGoals,
+        Modifier::DoubleDamage,
+        Modifier::ExactHitsOnly,
+        Modifier::CounterBackwards,
+        Modifier::MissesCostVitality,
+    ];
+
+    /// Name of the modifier in rules files and on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Modifier::DoubleSpeed => "double_speed",
+            Modifier::HiddenGoals => "hidden_goals",
+            Modifier::DoubleDamage => "double_damage",
+            Modifier::ExactHitsOnly => "exact_hits_only",
+            Modifier::CounterBackwards => "counter_backwards",
+            Modifier::MissesCostVitality => "misses_cost_vitality",
+        }
+    }
+}
+
+impl FromStr for Modifier {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.to_ascii_lowercase().replace('-', "_");
+        Modifier::ALL.into_iter().find(|m| m.name() == name).ok_or_else(|| {
+            let names: Vec<&str> = Modifier::ALL.iter().map(|m| m.name()).collect();
+            format!("unknown modifier '{}' (expected one of {})", s, names.join(", "))
+        })
+    }
+}
+
+/// An entry of the pool round modifiers are drawn from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WeightedModifier {
+    /// The modifier drawn, or `None` for a round played without one.
+    pub modifier: Option<Modifier>,
+    /// Relative chance of the entry being drawn.
+    pub weight: u32,
+}
+
+impl FromStr for WeightedModifier {
+    type Err = String;
+
+    /// Parses `NAME` or `NAME:WEIGHT`, where `NAME` is a modifier or `none`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, weight) = match s.split_once(':') {
+            Some((name, weight)) => {
+                let weight = weight.parse().map_err(|_| format!("invalid weight '{}'", weight))?;
+                (name, weight)
+            }
+            None => (s, 1),
+        };
+        let modifier = if name.eq_ignore_ascii_case("none") { None } else { Some(name.parse()?) };
+        Ok(WeightedModifier { modifier, weight })
+    }
+}
+
+/// Draws the modifier of a round from a weighted pool.
+/// A pool without any modifier to draw leaves `rng` untouched, so that
+/// matches without random modifiers replay the same goals for a seed.
+pub fn draw_modifier<R: Rng + ?Sized>(pool: &[WeightedModifier], rng: &mut R) -> Option<Modifier> {
+    if !pool.iter().any(|entry| entry.modifier.is_some() && entry.weight > 0) {
+        return None;
+    }
+    let total: u64 = pool.iter().map(|entry| entry.weight as u64).sum();
+    let mut pick = rng.gen_range(0..total);
+    for entry in pool {
+        if pick < entry.weight as u64 {
+            return entry.modifier;
+        }
+        pick -= entry.weight as u64;
+    }
+    None
+}
+
+/// Rules of a single round, on top of the match's `Ruleset`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoundRules {
+    /// Modifiers in effect for both players.
+    pub modifiers: Vec<Modifier>,
+    /// Share of the damage taken by each player that is sent back to the round's winner, in percent.
+    pub reflect_percent: [u32; 2],
+}
+
+impl RoundRules {
+    /// Whether a modifier is in effect.
+    pub fn has(&self, modifier: Modifier) -> bool {
+        self.modifiers.contains(&modifier)
+    }
+
+    /// Delay between two increments of the counter of a player with the given speed.
+    pub fn counter_speed(&self, speed: u32) -> u32 {
+        if self.has(Modifier::DoubleSpeed) {
+            (speed / 2).max(1)
+        } else {
+            speed
+        }
+    }
+
+    /// Adds a modifier, unless it is already in effect.
+    pub fn add(&mut self, modifier: Modifier) {
+        if !self.has(modifier) {
+            self.modifiers.push(modifier);
+        }
+    }
+
+    /// Value shown by a counter that has been incremented `ticks` times since its last wrap.
+    /// It is also the number of increments after which the counter shows `ticks`.
+    pub fn shown_counter(&self, ticks: u8) -> u8 {
+        if self.has(Modifier::CounterBackwards) {
+            100 - ticks.min(100)
+        } else {
+            ticks
+        }
+    }
+
+    /// Computes the score of an objective under the rules of the match and of the round.
+    pub fn score(&self, rules: &Ruleset, goal: u8, res: &PlayerRes, strength: u32) -> u32 {
+        if self.has(Modifier::ExactHitsOnly) && score_tier(goal, res.counter) != 1 {
+            0
+        } else {
+            rules.score(goal, res, strength)
+        }
+    }
+
+    /// Damage taken by the loser of a round won by `damage` points.
+    pub fn damage(&self, damage: u32) -> u32 {
+        if self.has(Modifier::DoubleDamage) {
+            damage * 2
+        } else {
+            damage
+        }
+    }
+
+    /// Vitality a player loses at once for missing the goal `miss` times.
+    pub fn miss_cost(&self, miss: u32) -> u32 {
+        if self.has(Modifier::MissesCostVitality) {
+            miss * MISS_VITALITY_COST
+        } else {
+            0
+        }
+    }
+
+    /// Damage sent back to the winner of a round when the player at `loser` takes `damage`.
+    pub fn reflected_damage(&self, loser: usize, damage: u32) -> u32 {
+        damage * self.reflect_percent[loser] / 100
+    }
+}
+
+/// Changes the rules of each round of a match before it is played.
+pub trait RoundHook {
+    /// Called at the start of every round with both players, in turn order.
+    fn start_round(&mut self, players: [&Player; 2], round: &mut RoundRules);
+}
+
+/// Leaves every round unchanged.
+impl RoundHook for () {
+    fn start_round(&mut self, _players: [&Player; 2], _round: &mut RoundRules) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_round_rules() {
+        let mut round = RoundRules::default();
+        assert_eq!(round.counter_speed(50), 50);
+        assert_eq!(round.reflected_damage(0, 40), 0);
+        round.modifiers.push(Modifier::DoubleSpeed);
+        round.reflect_percent[1] = 50;
+        assert!(round.has(Modifier::DoubleSpeed));
+        assert!(!round.has(Modifier::HiddenGoals));
+        assert_eq!(round.counter_speed(50), 25);
+        assert_eq!(round.counter_speed(1), 1);
+        assert_eq!(round.reflected_damage(1, 41), 20);
+    }
+
+    #[test]
+    fn test_modifier_effects() {
+        let rules = Ruleset::default();
+        let close = PlayerRes { counter: 48, miss: 2, ..PlayerRes::default() };
+        let mut round = RoundRules::default();
+        assert_eq!(round.shown_counter(30), 30);
+        assert_eq!(round.damage(15), 15);
+        assert_eq!(round.miss_cost(2), 0);
+        assert_eq!(round.score(&rules, 50, &close, 10), rules.score(50, &close, 10));
+
+        for modifier in [Modifier::CounterBackwards, Modifier::DoubleDamage, Modifier::MissesCostVitality, Modifier::ExactHitsOnly] {
+            round.add(modifier);
+            round.add(modifier);
+        }
+        assert_eq!(round.modifiers.len(), 4);
+        assert_eq!(round.shown_counter(30), 70);
+        assert_eq!(round.shown_counter(100), 0);
+        assert_eq!(round.damage(15), 30);
+        assert_eq!(round.miss_cost(2), 2 * MISS_VITALITY_COST);
+        assert_eq!(round.score(&rules, 50, &close, 10), 0);
+        let exact = PlayerRes { counter: 50, ..PlayerRes::default() };
+        assert_eq!(round.score(&rules, 50, &exact, 10), 110);
+    }
+
+    #[test]
+    fn test_parse_modifiers() {
+        for modifier in Modifier::ALL {
+            assert_eq!(modifier.name().parse::<Modifier>(), Ok(modifier));
+        }
+        assert_eq!("Double-Damage".parse::<Modifier>(), Ok(Modifier::DoubleDamage));
+        assert!("triple_damage".parse::<Modifier>().is_err());
+
+        let entry: WeightedModifier = "hidden_goals:3".parse().unwrap();
+        assert_eq!(entry, WeightedModifier { modifier: Some(Modifier::HiddenGoals), weight: 3 });
+        let entry: WeightedModifier = "none".parse().unwrap();
+        assert_eq!(entry, WeightedModifier { modifier: None, weight: 1 });
+        assert!("none:many".parse::<WeightedModifier>().is_err());
+    }
+
+    #[test]
+    fn test_draw_modifier() {
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        // A pool without modifiers does not consume any randomness.
+        let calm = [WeightedModifier { modifier: None, weight: 5 }];
+        assert_eq!(draw_modifier(&[], &mut rng), None);
+        assert_eq!(draw_modifier(&calm, &mut rng), None);
+        assert_eq!(rng.get_word_pos(), 0);
+
+        let pool = [
+            WeightedModifier { modifier: None, weight: 1 },
+            WeightedModifier { modifier: Some(Modifier::DoubleDamage), weight: 3 },
+            WeightedModifier { modifier: Some(Modifier::HiddenGoals), weight: 0 },
+        ];
+        let draws: Vec<Option<Modifier>> = (0..400).map(|_| draw_modifier(&pool, &mut rng)).collect();
+        assert!(!draws.contains(&Some(Modifier::HiddenGoals)));
+        let doubled = draws.iter().filter(|m| **m == Some(Modifier::DoubleDamage)).count();
+        assert!((250..350).contains(&doubled));
+    }
+}
@@ -0,0 +1,188 @@
+use crate::arena::wilson_interval;
+use crate::classes::{Player, PlayerBuilder, PlayerError, PlayerLimits, Stat};
+use crate::simulation::{run_batch, SimConfig};
+
+/// A split of the stat budget between the three attributes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Build {
+    pub vitality: u32,
+    pub speed: u32,
+    pub strength: u32,
+}
+
+impl Build {
+    /// Creates a player with this build, if it respects the default `PlayerLimits`.
+    pub fn player(&self, name: &str) -> Result<Player, PlayerError> {
+        PlayerBuilder::new()
+            .name(name)
+            .vitality(self.vitality)
+            .speed(self.speed)
+            .strength(self.strength)
+            .build()
+    }
+}
+
+/// Settings of a build search.
+#[derive(Debug, Clone)]
+pub struct OptimizeConfig {
+    /// Settings of the simulated matches.
+    pub sim: SimConfig,
+    /// Total number of points split between vitality, speed and strength.
+    pub budget: u32,
+    /// Granularity of the grid: every stat is a multiple of `step`, at least `step`
+    /// (see `grid`).
+    pub step: u32,
+    /// Number of matches simulated against the reference for each build.
+    pub matches: u64,
+    /// Master seed of the simulations, shared by every build so that they face the same luck.
+    pub seed: u64,
+    /// Number of worker threads.
+    pub threads: usize,
+}
+
+/// Result of a build against the reference opponent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuildResult {
+    pub build: Build,
+    /// Share of the points won, a draw counting as half a win.
+    pub win_rate: f64,
+    /// 95% confidence interval of the win rate.
+    pub interval: (f64, f64),
+}
+
+/// Every build of the grid: stats are positive multiples of `step` adding up to `budget`,
+/// within the default `PlayerLimits` (so speed is at least `MIN_SPEED`). A stat of 0 is
+/// left out, since a player without vitality or speed cannot play.
+pub fn grid(budget: u32, step: u32) -> Vec<Build> {
+    let step = step.max(1);
+    let limits = PlayerLimits::default();
+    if budget > limits.budget {
+        return Vec::new();
+    }
+    // Smallest multiple of the step a stat can take.
+    let start = |stat| limits.range(stat).min.max(step).div_ceil(step) * step;
+    let values = |stat| (start(stat)..=limits.range(stat).max).step_by(step as usize);
+    let mut builds = Vec::new();
+    for vitality in values(Stat::Vitality) {
+        for speed in values(Stat::Speed) {
+            let Some(strength) = budget.checked_sub(vitality + speed) else {
+                break;
+            };
+            if strength >= start(Stat::Strength) && strength <= limits.strength.max && strength.is_multiple_of(step) {
+                builds.push(Build { vitality, speed, strength });
+            }
+        }
+    }
+    builds
+}
+
+/// Simulates every build of the grid against `reference` and returns the
+/// results, best first.
+pub fn optimize(reference: &Player, cfg: &OptimizeConfig) -> Vec<BuildResult> {
+    let mut results: Vec<BuildResult> = grid(cfg.budget, cfg.step)
+        .into_iter()
+        .map(|build| {
+            let player = build.player("Build").expect("grid builds respect the default limits");
+            let summary = run_batch(&player, reference, &cfg.sim, cfg.matches, cfg.seed, cfg.threads);
+            let win_rate = if summary.matches == 0 {
+                0.0
+            } else {
+                (summary.wins[0] as f64 + 0.5 * summary.draws as f64) / summary.matches as f64
+            };
+            BuildResult {
+                build,
+                win_rate,
+                interval: wilson_interval(win_rate, summary.matches),
+            }
+        })
+        .collect();
+    results.sort_by(|a, b| b.win_rate.total_cmp(&a.win_rate));
+    results
+}
+
+/// Average share of the budget each stat gets in `results`, in percent, in the
+/// order vitality, speed, strength. Applied to the best builds, it shows which
+/// stat the scoring rules favor.
+pub fn average_shares(results: &[BuildResult]) -> [f64; 3] {
+    let mut shares = [0.0; 3];
+    for result in results {
+        let Build { vitality, speed, strength } = result.build;
+        let total = (vitality + speed + strength).max(1) as f64;
+        for (share, stat) in shares.iter_mut().zip([vitality, speed, strength]) {
+            *share += 100.0 * stat as f64 / total;
+        }
+    }
+    if !results.is_empty() {
+        shares.iter_mut().for_each(|share| *share /= results.len() as f64);
+    }
+    shares
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classes::MIN_SPEED;
+
+    #[test]
+    fn test_grid() {
+        let builds = grid(30, 10);
+        assert_eq!(builds, vec![Build { vitality: 10, speed: 10, strength: 10 }]);
+        let builds = grid(150, 5);
+        assert!(builds.iter().all(|b| b.vitality + b.speed + b.strength == 150));
+        assert!(builds.iter().all(|b| b.vitality >= 5 && b.speed >= MIN_SPEED && b.strength >= 5));
+        // Splits of 30 steps into at most 20 steps each, with at least 2 for speed.
+        assert_eq!(builds.len(), 286);
+        // A budget below three steps, or not a multiple of the step, cannot be split.
+        assert!(grid(25, 10).is_empty());
+        assert!(grid(35, 10).is_empty());
+        // Nor can a budget above what the limits allow.
+        assert!(grid(210, 10).is_empty());
+    }
+
+    #[test]
+    fn test_grid_respects_limits() {
+        let limits = PlayerLimits::default();
+        for (budget, step) in [(200, 5), (150, 3), (119, 7), (30, 1)] {
+            let builds = grid(budget, step);
+            assert!(!builds.is_empty());
+            for build in builds {
+                assert!(build.player("Build").is_ok(), "{:?} is outside the limits", build);
+                assert!(build.speed >= MIN_SPEED && build.vitality <= limits.vitality.max);
+            }
+        }
+        // Speed starts at the first multiple of the step above the minimum.
+        assert!(grid(150, 3).iter().all(|b| b.speed >= 12));
+    }
+
+    #[test]
+    fn test_optimize_ranks_builds() {
+        let reference = Player::new("Reference".to_string(), 20, 20, 20);
+        let cfg = OptimizeConfig {
+            sim: SimConfig { max_rounds: 200, ..SimConfig::default() },
+            budget: 60,
+            step: 10,
+            matches: 100,
+            seed: 3,
+            threads: 2,
+        };
+        let results = optimize(&reference, &cfg);
+        assert_eq!(results.len(), grid(60, 10).len());
+        assert!(results.windows(2).all(|w| w[0].win_rate >= w[1].win_rate));
+        // The best build does at least as well as the reference's own split.
+        assert!(results[0].win_rate >= 0.5);
+        let (low, high) = results[0].interval;
+        assert!(low <= results[0].win_rate && results[0].win_rate <= high);
+    }
+
+    #[test]
+    fn test_average_shares() {
+        let result = |vitality, speed, strength| BuildResult {
+            build: Build { vitality, speed, strength },
+            win_rate: 0.5,
+            interval: (0.0, 1.0),
+        };
+        let shares = average_shares(&[result(50, 25, 25), result(25, 25, 50)]);
+        assert_eq!(shares, [37.5, 25.0, 37.5]);
+        assert_eq!(average_shares(&[]), [0.0; 3]);
+    }
+}
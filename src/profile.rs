@@ -0,0 +1,314 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::autosave::MatchState;
+use crate::classes::{PlayerBuilder, PlayerError, PlayerLimits, Stat};
+use crate::error::GameError;
+use crate::json_file;
+
+/// Default file the players' profiles are kept in.
+pub const DEFAULT_PROFILES_PATH: &str = "duel_game_profiles.json";
+
+/// Points of each stat.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatPoints {
+    pub vitality: u32,
+    pub speed: u32,
+    pub strength: u32,
+}
+
+impl StatPoints {
+    /// Returns the points of a stat.
+    pub fn get(&self, stat: Stat) -> u32 {
+        match stat {
+            Stat::Vitality => self.vitality,
+            Stat::Speed => self.speed,
+            Stat::Strength => self.strength,
+        }
+    }
+
+    /// Adds points to a stat.
+    pub fn add(&mut self, stat: Stat, points: u32) {
+        let slot = match stat {
+            Stat::Vitality => &mut self.vitality,
+            Stat::Speed => &mut self.speed,
+            Stat::Strength => &mut self.strength,
+        };
+        *slot = slot.saturating_add(points);
+    }
+
+    /// Sum of the points of every stat.
+    pub fn total(&self) -> u32 {
+        Stat::ALL.iter().map(|stat| self.get(*stat)).sum()
+    }
+}
+
+/// How players progress from match to match: the experience they earn,
+/// the levels it buys and how far their stats can grow.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Progression {
+    /// Stats of a level 1 player, before any allocation.
+    pub base: StatPoints,
+    /// Stat points granted by every level gained.
+    pub points_per_level: u32,
+    /// Highest level a player can reach.
+    pub max_level: u32,
+    /// Experience needed to go from level 1 to 2; each following level costs this much more.
+    pub xp_per_level: u64,
+    /// Experience for winning a match.
+    pub xp_per_win: u64,
+    /// Experience for each objective stopped exactly on the goal.
+    pub xp_per_perfect_hit: u64,
+    /// Experience for each vitality point taken from the opponent.
+    pub xp_per_damage: u64,
+    /// Caps of the stats, whatever the player's level.
+    pub limits: PlayerLimits,
+}
+
+impl Progression {
+    /// Total experience needed to reach `level`.
+    pub fn xp_for_level(&self, level: u32) -> u64 {
+        let level = level.max(1) as u64;
+        self.xp_per_level * (level - 1) * level / 2
+    }
+
+    /// Level reached with `xp` experience.
+    pub fn level(&self, xp: u64) -> u32 {
+        let mut level = 1;
+        while level < self.max_level && xp >= self.xp_for_level(level + 1) {
+            level += 1;
+        }
+        level
+    }
+}
+
+impl Default for Progression {
+    /// Default progression:
+    /// base: vitality 40, speed 30, strength 30; 5 points per level up to level 20;
+    /// levels cost 100 XP more each; 100 XP per win, 10 per perfect hit, 1 per damage point;
+    /// stats capped by the default `PlayerLimits`.
+    fn default() -> Self {
+        Progression {
+            base: StatPoints { vitality: 40, speed: 30, strength: 30 },
+            points_per_level: 5,
+            max_level: 20,
+            xp_per_level: 100,
+            xp_per_win: 100,
+            xp_per_perfect_hit: 10,
+            xp_per_damage: 1,
+            limits: PlayerLimits::default(),
+        }
+    }
+}
+
+/// Experience earned by a player in a match.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct XpAward {
+    /// Whether the player won the match.
+    pub won: bool,
+    /// Objectives stopped exactly on the goal before the counter wrapped.
+    pub perfect_hits: u32,
+    /// Vitality points taken from the opponent.
+    pub damage: u32,
+}
+
+impl XpAward {
+    /// Experience earned from a finished match by the player at `index` (0 or 1).
+    pub fn from_match(state: &MatchState, index: usize) -> Self {
+        let opponent = &state.players[1 - index];
+        XpAward {
+            won: opponent.vitality() == 0 && state.players[index].vitality() > 0,
+            perfect_hits: state.stats[index]
+                .objectives
+                .iter()
+                .filter(|s| s.is_perfect())
+                .count() as u32,
+            damage: state.damage_dealt[index],
+        }
+    }
+
+    /// Total experience under the given progression.
+    pub fn xp(&self, progression: &Progression) -> u64 {
+        let win = if self.won { progression.xp_per_win } else { 0 };
+        win + self.perfect_hits as u64 * progression.xp_per_perfect_hit + self.damage as u64 * progression.xp_per_damage
+    }
+}
+
+/// Progress of a player, kept between matches.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    /// Experience earned so far.
+    pub xp: u64,
+    /// Stat points spent on top of the base stats.
+    pub allocated: StatPoints,
+    /// Number of matches played.
+    pub matches: u32,
+    /// Number of matches won.
+    pub wins: u32,
+}
+
+impl Profile {
+    /// Current level of the player.
+    pub fn level(&self, progression: &Progression) -> u32 {
+        progression.level(self.xp)
+    }
+
+    /// Stat points earned by leveling up and not spent yet.
+    pub fn unspent_points(&self, progression: &Progression) -> u32 {
+        let earned = (self.level(progression) - 1) * progression.points_per_level;
+        earned.saturating_sub(self.allocated.total())
+    }
+
+    /// Current value of a stat: its base value plus the points spent on it.
+    pub fn stat(&self, stat: Stat, progression: &Progression) -> u32 {
+        progression.base.get(stat) + self.allocated.get(stat)
+    }
+
+    /// Spends earned points on a stat, within the caps of the progression.
+    pub fn allocate(&mut self, stat: Stat, points: u32, progression: &Progression) -> Result<(), PlayerError> {
+        let available = self.unspent_points(progression);
+        if points > available {
+            return Err(PlayerError::NotEnoughPoints { points, available });
+        }
+        let range = progression.limits.range(stat);
+        let value = self.stat(stat, progression) + points;
+        if !range.contains(value) {
+            return Err(PlayerError::OutOfRange { stat, value, range });
+        }
+        let total = progression.base.total() + self.allocated.total() + points;
+        if total > progression.limits.budget {
+            return Err(PlayerError::OverBudget { total, budget: progression.limits.budget });
+        }
+        self.allocated.add(stat, points);
+        Ok(())
+    }
+
+    /// Starts the builder of the player described by this profile.
+    pub fn builder(&self, name: &str, progression: &Progression) -> PlayerBuilder {
+        Stat::ALL
+            .into_iter()
+            .fold(PlayerBuilder::new().name(name).limits(progression.limits), |builder, stat| {
+                builder.stat(stat, self.stat(stat, progression))
+            })
+    }
+
+    /// Records a match and its experience. Returns the number of levels gained.
+    pub fn record_match(&mut self, award: &XpAward, progression: &Progression) -> u32 {
+        let level = self.level(progression);
+        self.matches += 1;
+        if award.won {
+            self.wins += 1;
+        }
+        self.xp += award.xp(progression);
+        self.level(progression) - level
+    }
+}
+
+/// Every player's profile, by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileStore {
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl ProfileStore {
+    /// Reads the profiles from a JSON file. A missing file holds no profile.
+    pub fn load(path: &Path) -> Result<Self, GameError> {
+        json_file::load_or_default(path, GameError::InvalidProfiles)
+    }
+
+    /// Writes the profiles to a JSON file.
+    pub fn save(&self, path: &Path) -> Result<(), GameError> {
+        json_file::save(self, path, GameError::InvalidProfiles)
+    }
+
+    /// Returns the profile of a player, creating it at level 1 if needed.
+    pub fn profile(&mut self, name: &str) -> &mut Profile {
+        self.profiles.entry(name.to_string()).or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classes::{Player, PlayerRes};
+    use crate::ruleset::Ruleset;
+    use crate::stats::ObjectiveStat;
+
+    #[test]
+    fn test_levels() {
+        let progression = Progression::default();
+        assert_eq!(progression.xp_for_level(1), 0);
+        assert_eq!(progression.xp_for_level(2), 100);
+        assert_eq!(progression.xp_for_level(3), 300);
+        assert_eq!(progression.level(0), 1);
+        assert_eq!(progression.level(299), 2);
+        assert_eq!(progression.level(300), 3);
+        assert_eq!(progression.level(u64::MAX / 4), progression.max_level);
+    }
+
+    #[test]
+    fn test_award_from_match() {
+        let p1 = Player::new("Alice".to_string(), 20, 50, 50);
+        let p2 = Player::new("Bob".to_string(), 0, 50, 50);
+        let mut state = MatchState::new(p1, p2, Ruleset::default(), 1);
+        state.damage_dealt = [45, 30];
+        let hit = |goal, counter| ObjectiveStat::new(goal, &PlayerRes { counter, ..PlayerRes::default() }, 50, 0);
+        state.stats[0].record(hit(40, 40));
+        state.stats[0].record(hit(40, 41));
+        state.stats[1].record(hit(70, 70));
+        // Stopping on the goal after the counter wrapped is not a perfect hit.
+        let wrapped = PlayerRes { counter: 20, miss: 1, ..PlayerRes::default() };
+        state.stats[1].record(ObjectiveStat::new(20, &wrapped, 50, 0));
+
+        let alice = XpAward::from_match(&state, 0);
+        assert_eq!(alice, XpAward { won: true, perfect_hits: 1, damage: 45 });
+        assert_eq!(alice.xp(&Progression::default()), 100 + 10 + 45);
+        let bob = XpAward::from_match(&state, 1);
+        assert_eq!(bob, XpAward { won: false, perfect_hits: 1, damage: 30 });
+    }
+
+    #[test]
+    fn test_record_match_and_allocate() {
+        let progression = Progression::default();
+        let mut profile = Profile::default();
+        assert_eq!(profile.unspent_points(&progression), 0);
+        assert!(matches!(
+            profile.allocate(Stat::Speed, 1, &progression),
+            Err(PlayerError::NotEnoughPoints { points: 1, available: 0 })
+        ));
+
+        // 310 XP: level 3, 10 points to spend.
+        let gained = profile.record_match(&XpAward { won: true, perfect_hits: 1, damage: 200 }, &progression);
+        assert_eq!(gained, 2);
+        assert_eq!((profile.matches, profile.wins), (1, 1));
+        assert_eq!(profile.unspent_points(&progression), 10);
+        profile.allocate(Stat::Speed, 6, &progression).unwrap();
+        assert_eq!(profile.unspent_points(&progression), 4);
+
+        let player = profile.builder("Alice", &progression).build().unwrap();
+        assert_eq!(player, Player::new("Alice".to_string(), 40, 36, 30));
+    }
+
+    #[test]
+    fn test_allocation_caps() {
+        let progression = Progression {
+            limits: PlayerLimits { budget: 105, ..PlayerLimits::default() },
+            ..Progression::default()
+        };
+        let mut profile = Profile { xp: 10_000, ..Profile::default() };
+        assert!(matches!(
+            profile.allocate(Stat::Speed, 6, &progression),
+            Err(PlayerError::OverBudget { total: 106, budget: 105 })
+        ));
+        let progression = Progression::default();
+        profile.allocate(Stat::Vitality, 40, &progression).unwrap();
+        assert!(matches!(
+            profile.allocate(Stat::Vitality, 25, &progression),
+            Err(PlayerError::OutOfRange { stat: Stat::Vitality, value: 105, .. })
+        ));
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::classes::{Player, PlayerRes};
+use crate::crits::CritRules;
+use crate::helper::{compute_score_with, TIER_BASE_SCORES};
+use crate::luck::LuckRules;
+use crate::defense::Defense;
+use crate::modifiers::WeightedModifier;
+use crate::profile::Progression;
+use crate::stamina::StaminaRules;
+use crate::stat_map::{StatKey, StatModifier};
+use crate::wager::Wagering;
+
+/// Rules of a duel, shared by interactive and headless matches.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ruleset {
+    /// Number of objectives per round.
+    pub goal_count: u8,
+    /// Base score of each accuracy tier, from tier 1 (perfect stop) to tier 5.
+    pub tier_scores: [u32; 5],
+    /// How players level up between matches, and the caps of their stats.
+    #[serde(default)]
+    pub progression: Progression,
+    /// Pool the modifier of each round is drawn from (no modifiers if empty).
+    #[serde(default)]
+    pub modifier_pool: Vec<WeightedModifier>,
+    /// Betting phase played before each round (no betting if `None`).
+    #[serde(default)]
+    pub wagering: Option<Wagering>,
+    /// Block the loser of a round may attempt (no blocking if `None`).
+    #[serde(default)]
+    pub defense: Option<Defense>,
+    /// How players earn stamina and spend it on retries, ability cards and a slower
+    /// counter (none of these if `None`: ability cards are then free).
+    #[serde(default)]
+    pub stamina: Option<StaminaRules>,
+    /// Critical hits and perfect streaks (neither if `None`).
+    #[serde(default)]
+    pub crits: Option<CritRules>,
+    /// How the players' luck bends the randomness of the match.
+    #[serde(default)]
+    pub luck: LuckRules,
+    /// Stats every player starts a match with on top of the core ones, like `armor`.
+    #[serde(default)]
+    pub extra_stats: BTreeMap<StatKey, u32>,
+    /// Stat modifiers every player starts a match with.
+    #[serde(default)]
+    pub stat_modifiers: Vec<StatModifier>,
+}
+
+impl Ruleset {
+    /// Creates the default rules with the given number of objectives per round.
+    pub fn with_goal_count(goal_count: u8) -> Self {
+        Ruleset {
+            goal_count,
+            ..Ruleset::default()
+        }
+    }
+
+    /// Prepares a player for a match under these rules: gives them the extra stats
+    /// they do not have yet, and puts the rules' stat modifiers in effect.
+    pub fn equip(&self, player: &mut Player) {
+        for (key, value) in &self.extra_stats {
+            if !player.stats.contains(key.clone()) {
+                player.stats.set(key.clone(), *value);
+            }
+        }
+        for modifier in &self.stat_modifiers {
+            player.stats.add_modifier(modifier.clone());
+        }
+    }
+
+    /// Computes the score of an objective under these rules.
+    pub fn score(&self, goal: u8, res: &PlayerRes, strength: u32) -> u32 {
+        compute_score_with(goal, res, strength, &self.tier_scores)
+    }
+}
+
+impl Default for Ruleset {
+    /// Default values for Ruleset:
+    /// goal_count: 5, tier_scores: 100, 80, 60, 40, 20, default progression, no round modifiers, no betting, no blocking,
+    /// no stamina, no critical hits, default luck rules, no extra stats or stat modifiers.
+    fn default() -> Self {
+        Ruleset {
+            goal_count: 5,
+            tier_scores: TIER_BASE_SCORES,
+            progression: Progression::default(),
+            modifier_pool: Vec::new(),
+            wagering: None,
+            defense: None,
+            stamina: None,
+            crits: None,
+            luck: LuckRules::default(),
+            extra_stats: BTreeMap::new(),
+            stat_modifiers: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classes::Stat;
+    use crate::helper::compute_score;
+    use crate::stat_map::Bonus;
+
+    #[test]
+    fn test_default_ruleset_matches_compute_score() {
+        let rules = Ruleset::default();
+        let res = PlayerRes { counter: 47, miss: 1, ..PlayerRes::default() };
+        assert_eq!(rules.score(50, &res, 30), compute_score(50, &res, 30));
+    }
+
+    #[test]
+    fn test_equip() {
+        let armor: StatKey = "armor".parse().unwrap();
+        let rules = Ruleset {
+            extra_stats: BTreeMap::from([(armor.clone(), 5)]),
+            stat_modifiers: vec![StatModifier {
+                stat: Stat::Speed.into(),
+                bonus: Bonus::Percent(10),
+                source: "haste".to_string(),
+                rounds: Some(1),
+            }],
+            ..Ruleset::default()
+        };
+        let mut player = Player::default();
+        rules.equip(&mut player);
+        assert_eq!((player.stat(armor.clone()), player.speed()), (5, 55));
+        // A player who already has an extra stat keeps their value.
+        let mut player = Player::default().with_stat(armor.clone(), 8);
+        rules.equip(&mut player);
+        assert_eq!(player.stat(armor), 8);
+    }
+
+    #[test]
+    fn test_custom_tier_scores() {
+        // Only exact hits score anything under these rules.
+        let rules = Ruleset {
+            tier_scores: [100, 0, 0, 0, 0],
+            ..Ruleset::with_goal_count(3)
+        };
+        let res = PlayerRes { counter: 48, ..PlayerRes::default() };
+        assert_eq!(rules.goal_count, 3);
+        assert_eq!(rules.score(50, &res, 10), 10);
+    }
+}
@@ -0,0 +1,411 @@
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::classes::{Player, PlayerBuilder, PlayerError};
+use crate::duel::{Duel, Phase};
+use crate::error::GameError;
+use crate::events::EventBus;
+use crate::helper::Penalty;
+use crate::ruleset::Ruleset;
+
+/// Address the HTTP API listens on by default.
+pub const DEFAULT_SERVER_ADDR: &str = "127.0.0.1:8080";
+
+/// Number of finished matches kept for their final state to be read; older ones are removed.
+pub const MAX_FINISHED_MATCHES: usize = 100;
+
+/// Number of unfinished matches kept; creating one more removes the match left
+/// unused for the longest time.
+pub const MAX_LIVE_MATCHES: usize = 1000;
+
+/// Body of `POST /matches`. Every field is optional. The players are built with
+/// the same limits as on the command line, and a round has at least one objective.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct NewMatch {
+    pub name1: String,
+    pub name2: String,
+    pub vitality: u32,
+    pub speed: u32,
+    pub strength: u32,
+    pub goals: u8,
+    /// Seed of the match (random if omitted).
+    pub seed: Option<u64>,
+}
+
+impl Default for NewMatch {
+    /// Same defaults as the command line.
+    fn default() -> Self {
+        NewMatch {
+            name1: "Michel".to_string(),
+            name2: "Jacquie".to_string(),
+            vitality: 50,
+            speed: 50,
+            strength: 50,
+            goals: 5,
+            seed: None,
+        }
+    }
+}
+
+/// Body of `POST /matches/{id}/penalty`.
+#[derive(Debug, Clone, Deserialize)]
+struct PenaltyChoice {
+    penalty: Penalty,
+}
+
+/// Response of the API: an HTTP status and a JSON body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiResponse {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl ApiResponse {
+    fn ok(body: Value) -> Self {
+        ApiResponse { status: 200, body }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        ApiResponse {
+            status,
+            body: json!({ "error": message.into() }),
+        }
+    }
+}
+
+/// Matches run by the API, indexed by id. Only the last `MAX_FINISHED_MATCHES`
+/// finished matches and the `MAX_LIVE_MATCHES` most recently used unfinished ones are kept.
+#[derive(Debug, Default)]
+pub struct MatchRegistry {
+    matches: HashMap<u64, Duel>,
+    finished: VecDeque<u64>,
+    /// Last time each unfinished match was created or acted on.
+    last_used: HashMap<u64, Instant>,
+    next_id: u64,
+    events: EventBus,
+}
+
+impl MatchRegistry {
+    /// Creates a registry whose matches emit their events through `events`,
+    /// tagged with their id.
+    pub fn new(events: EventBus) -> Self {
+        MatchRegistry { events, ..MatchRegistry::default() }
+    }
+
+    /// Handles one API request received at time `now`.
+    ///
+    /// Routes:
+    /// - `POST /matches`: creates a match, returns `{id, state}`.
+    /// - `GET /matches/{id}`: returns the match's state.
+    /// - `POST /matches/{id}/start`: starts the next objective, returns `{goal, state}`.
+    /// - `POST /matches/{id}/stop`: stops the counter, returns `{result, state}`. Stops sooner
+    ///   than a human could react are refused with 409, and the counter keeps running.
+    /// - `POST /matches/{id}/penalty`: applies `{"penalty": "speed" | "strength"}`, returns the state.
+    pub fn handle(&mut self, method: &str, path: &str, body: &str, now: Instant) -> ApiResponse {
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        match (method, segments.as_slice()) {
+            ("POST", ["matches"]) => self.create(body, now),
+            (_, ["matches"]) => ApiResponse::error(405, "method not allowed"),
+            (method, ["matches", id, rest @ ..]) => {
+                let Some((id, duel)) = id.parse().ok().and_then(|id: u64| self.matches.get_mut(&id).map(|duel| (id, duel)))
+                else {
+                    return ApiResponse::error(404, format!("no match with id {}", id));
+                };
+                let was_finished = duel.phase() == Phase::Finished;
+                let response = match (method, rest) {
+                    ("GET", []) => ApiResponse::ok(json!(duel.state())),
+                    ("POST", ["start"]) => match duel.start_objective(now) {
+                        Ok(goal) => ApiResponse::ok(json!({ "goal": goal, "state": duel.state() })),
+                        Err(e) => ApiResponse::error(409, e.to_string()),
+                    },
+                    ("POST", ["stop"]) => match duel.stop_objective(now) {
+                        Ok(stat) => ApiResponse::ok(json!({ "result": stat, "state": duel.state() })),
+                        Err(e) => ApiResponse::error(409, e.to_string()),
+                    },
+                    ("POST", ["penalty"]) => {
+                        let choice: PenaltyChoice = match serde_json::from_str(body) {
+                            Ok(choice) => choice,
+                            Err(e) => return ApiResponse::error(400, format!("invalid body: {}", e)),
+                        };
+                        match duel.choose_penalty(choice.penalty) {
+                            Ok(()) => ApiResponse::ok(json!(duel.state())),
+                            Err(e) => ApiResponse::error(409, e.to_string()),
+                        }
+                    }
+                    (_, [] | ["start"] | ["stop"] | ["penalty"]) => ApiResponse::error(405, "method not allowed"),
+                    _ => ApiResponse::error(404, "not found"),
+                };
+                if duel.phase() != Phase::Finished {
+                    self.last_used.insert(id, now);
+                } else if !was_finished {
+                    self.retire(id);
+                }
+                response
+            }
+            _ => ApiResponse::error(404, "not found"),
+        }
+    }
+
+    /// Remembers that a match is over, and removes the oldest finished matches
+    /// beyond `MAX_FINISHED_MATCHES`.
+    fn retire(&mut self, id: u64) {
+        self.last_used.remove(&id);
+        self.finished.push_back(id);
+        while self.finished.len() > MAX_FINISHED_MATCHES {
+            if let Some(oldest) = self.finished.pop_front() {
+                self.matches.remove(&oldest);
+            }
+        }
+    }
+
+    /// Removes the unfinished match left unused for the longest time once
+    /// `MAX_LIVE_MATCHES` are kept, to make room for a new one.
+    fn make_room(&mut self) {
+        if self.last_used.len() < MAX_LIVE_MATCHES {
+            return;
+        }
+        if let Some(oldest) = self.last_used.iter().min_by_key(|(id, used)| (**used, **id)).map(|(id, _)| *id) {
+            self.last_used.remove(&oldest);
+            self.matches.remove(&oldest);
+        }
+    }
+
+    /// Creates a match from the body of `POST /matches`, received at time `now`.
+    fn create(&mut self, body: &str, now: Instant) -> ApiResponse {
+        let params: NewMatch = if body.trim().is_empty() {
+            NewMatch::default()
+        } else {
+            match serde_json::from_str(body) {
+                Ok(params) => params,
+                Err(e) => return ApiResponse::error(400, format!("invalid body: {}", e)),
+            }
+        };
+        let build = |name: String| -> Result<Player, PlayerError> {
+            PlayerBuilder::new()
+                .name(name)
+                .vitality(params.vitality)
+                .speed(params.speed)
+                .strength(params.strength)
+                .build()
+        };
+        let (p1, p2) = match (build(params.name1.clone()), build(params.name2.clone())) {
+            (Ok(p1), Ok(p2)) => (p1, p2),
+            (Err(e), _) | (_, Err(e)) => return ApiResponse::error(400, format!("invalid body: {}", e)),
+        };
+        let seed = params.seed.unwrap_or_else(rand::random);
+        let id = self.next_id + 1;
+        let duel = match Duel::new(p1, p2, Ruleset::with_goal_count(params.goals), seed, self.events.for_match(id)) {
+            Ok(duel) => duel,
+            Err(e) => return ApiResponse::error(400, format!("invalid body: {}", e)),
+        };
+        self.next_id = id;
+        let state = duel.state();
+        self.make_room();
+        self.matches.insert(id, duel);
+        self.last_used.insert(id, now);
+        ApiResponse {
+            status: 201,
+            body: json!({ "id": id, "seed": seed, "state": state }),
+        }
+    }
+}
+
+/// Local HTTP API to create, play and inspect matches.
+pub struct ApiServer {
+    http: Server,
+    registry: Mutex<MatchRegistry>,
+}
+
+impl ApiServer {
+    /// Binds the server to `addr` (e.g. "127.0.0.1:8080"). The events of its
+    /// matches are emitted through `events`.
+    pub fn bind(addr: &str, events: EventBus) -> Result<Self, GameError> {
+        let http = Server::http(addr).map_err(|e| GameError::Io(io::Error::other(e.to_string())))?;
+        Ok(ApiServer {
+            http,
+            registry: Mutex::new(MatchRegistry::new(events)),
+        })
+    }
+
+    /// Returns the address the server listens on.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.http.server_addr().to_ip()
+    }
+
+    /// Serves requests until the process ends. A client that disconnects
+    /// before reading its response does not stop the server.
+    pub fn run(&self) {
+        for request in self.http.incoming_requests() {
+            if let Err(e) = self.respond(request) {
+                log::warn!("Could not send a response: {}", e);
+            }
+        }
+    }
+
+    /// Handles one request. The time is taken as soon as the request is received,
+    /// so that the counter does not depend on how long the body takes to read.
+    fn respond(&self, mut request: Request) -> io::Result<()> {
+        let now = Instant::now();
+        let mut body = String::new();
+        let response = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => {
+                let method = method_name(request.method());
+                let path = request.url().split('?').next().unwrap_or("").to_string();
+                let mut registry = self.registry.lock().unwrap_or_else(|e| e.into_inner());
+                registry.handle(method, &path, &body, now)
+            }
+            Err(e) => ApiResponse::error(400, format!("invalid body: {}", e)),
+        };
+        log::info!("{} {} -> {}", request.method(), request.url(), response.status);
+
+        let header = Header::from_bytes("Content-Type", "application/json").expect("valid header");
+        let reply = Response::from_string(response.body.to_string())
+            .with_status_code(response.status)
+            .with_header(header);
+        request.respond(reply)
+    }
+}
+
+/// Returns the name of the HTTP methods used by the API.
+fn method_name(method: &Method) -> &'static str {
+    match method {
+        Method::Get => "GET",
+        Method::Post => "POST",
+        _ => "OTHER",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    fn create_match(registry: &mut MatchRegistry, body: &str) -> u64 {
+        let response = registry.handle("POST", "/matches", body, Instant::now());
+        assert_eq!(response.status, 201);
+        response.body["id"].as_u64().unwrap()
+    }
+
+    #[test]
+    fn test_create_with_defaults() {
+        let mut registry = MatchRegistry::default();
+        let response = registry.handle("POST", "/matches", "", Instant::now());
+        assert_eq!(response.status, 201);
+        assert_eq!(response.body["state"]["players"][0]["name"], "Michel");
+        assert_eq!(response.body["state"]["phase"], "awaiting_start");
+        assert_eq!(response.body["state"]["goals"].as_array().unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_play_an_objective() {
+        let mut registry = MatchRegistry::default();
+        let id = create_match(&mut registry, r#"{"name1": "Alice", "goals": 2, "seed": 4}"#);
+        let now = Instant::now();
+        let start = registry.handle("POST", &format!("/matches/{}/start", id), "", now);
+        assert_eq!(start.status, 200);
+        assert_eq!(start.body["state"]["phase"], "counter_running");
+
+        // A stop right after the start is refused.
+        let early = registry.handle("POST", &format!("/matches/{}/stop", id), "", now);
+        assert_eq!(early.status, 409);
+
+        // Stop exactly when the counter reaches the goal.
+        let goal = start.body["goal"].as_u64().unwrap();
+        let stop_time = now + Duration::from_millis(goal * 50);
+        let stop = registry.handle("POST", &format!("/matches/{}/stop", id), "", stop_time);
+        assert_eq!(stop.status, 200);
+        assert_eq!(stop.body["result"]["tier"], 1);
+        assert_eq!(stop.body["result"]["rejected"], 1);
+        assert_eq!(stop.body["state"]["objective"], 1);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut registry = MatchRegistry::default();
+        let now = Instant::now();
+        assert_eq!(registry.handle("GET", "/matches/1", "", now).status, 404);
+        assert_eq!(registry.handle("POST", "/matches", "{oops", now).status, 400);
+        let id = create_match(&mut registry, "");
+        assert_eq!(registry.handle("POST", &format!("/matches/{}/stop", id), "", now).status, 409);
+        let penalty = registry.handle("POST", &format!("/matches/{}/penalty", id), r#"{"penalty":"luck"}"#, now);
+        assert_eq!(penalty.status, 400);
+        assert_eq!(registry.handle("GET", &format!("/matches/{}/stop", id), "", now).status, 405);
+        assert_eq!(registry.handle("GET", "/nothing", "", now).status, 404);
+    }
+
+    #[test]
+    fn test_create_rejects_invalid_matches() {
+        let mut registry = MatchRegistry::default();
+        let now = Instant::now();
+        // Without objectives every round would be a tie, and the match would never end.
+        assert_eq!(registry.handle("POST", "/matches", r#"{"goals": 0}"#, now).status, 400);
+        assert_eq!(registry.handle("POST", "/matches", r#"{"vitality": 4000000000}"#, now).status, 400);
+        assert_eq!(registry.handle("POST", "/matches", r#"{"speed": 0}"#, now).status, 400);
+        assert_eq!(registry.handle("POST", "/matches", r#"{"name1": " "}"#, now).status, 400);
+    }
+
+    #[test]
+    fn test_finished_matches_are_removed() {
+        let mut registry = MatchRegistry::default();
+        let now = Instant::now();
+        let mut ids = Vec::new();
+        for _ in 0..=MAX_FINISHED_MATCHES {
+            // The first player stops on the goal and the second far from it: one round ends the match.
+            let id = create_match(&mut registry, r#"{"vitality": 1, "goals": 1, "seed": 1}"#);
+            for offset in [0, 50] {
+                let start = registry.handle("POST", &format!("/matches/{}/start", id), "", now);
+                let goal = start.body["goal"].as_u64().unwrap();
+                let stop_time = now + Duration::from_millis((goal + offset) * 50);
+                registry.handle("POST", &format!("/matches/{}/stop", id), "", stop_time);
+            }
+            let state = registry.handle("GET", &format!("/matches/{}", id), "", now);
+            assert_eq!(state.body["phase"], "finished");
+            ids.push(id);
+        }
+        assert_eq!(registry.handle("GET", &format!("/matches/{}", ids[0]), "", now).status, 404);
+        assert_eq!(registry.handle("GET", &format!("/matches/{}", ids[1]), "", now).status, 200);
+    }
+
+    #[test]
+    fn test_idle_matches_are_removed() {
+        let mut registry = MatchRegistry::default();
+        let now = Instant::now();
+        let ids: Vec<u64> = (0..MAX_LIVE_MATCHES).map(|_| create_match(&mut registry, "")).collect();
+        // Playing the first match keeps it: the second one has been unused the longest.
+        let later = now + Duration::from_secs(1);
+        assert_eq!(registry.handle("POST", &format!("/matches/{}/start", ids[0]), "", later).status, 200);
+        create_match(&mut registry, "");
+        assert_eq!(registry.matches.len(), MAX_LIVE_MATCHES);
+        assert_eq!(registry.handle("GET", &format!("/matches/{}", ids[0]), "", later).status, 200);
+        assert_eq!(registry.handle("GET", &format!("/matches/{}", ids[1]), "", later).status, 404);
+    }
+
+    #[test]
+    fn test_server_over_http() {
+        let server = Arc::new(ApiServer::bind("127.0.0.1:0", EventBus::default()).unwrap());
+        let addr = server.local_addr().unwrap();
+        let runner = Arc::clone(&server);
+        thread::spawn(move || runner.run());
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream
+            .write_all(b"POST /matches HTTP/1.1\r\nHost: localhost\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).unwrap();
+        assert!(reply.starts_with("HTTP/1.1 201"));
+        assert!(reply.contains("\"id\":1"));
+    }
+}
@@ -0,0 +1,250 @@
+use std::thread;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::classes::{Player, PlayerRes, Stat};
+use crate::luck::luck;
+use crate::helper::{average_score, counter_at, create_goals, round_outcome, sample_normal, split_players, Penalty, RoundOutcome};
+use crate::ruleset::Ruleset;
+
+/// Settings shared by every simulated match of a batch.
+#[derive(Debug, Clone)]
+pub struct SimConfig {
+    /// Rules of the simulated matches.
+    pub rules: Ruleset,
+    /// Standard deviation of the simulated players' timing error, in milliseconds.
+    pub sigma_ms: f64,
+    /// Number of rounds after which a match is declared a draw.
+    pub max_rounds: u32,
+}
+
+impl Default for SimConfig {
+    /// Default values for SimConfig:
+    /// rules: default rules, sigma_ms: 80, max_rounds: 1000.
+    fn default() -> Self {
+        SimConfig {
+            rules: Ruleset::default(),
+            sigma_ms: 80.0,
+            max_rounds: 1000,
+        }
+    }
+}
+
+/// Result of a single simulated match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchOutcome {
+    /// Index of the winning player (0 or 1), or `None` for a draw.
+    pub winner: Option<usize>,
+    /// Number of rounds played.
+    pub rounds: u32,
+}
+
+/// Aggregated results of a batch of simulated matches.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchSummary {
+    /// Number of matches played.
+    pub matches: u64,
+    /// Number of matches won by each player.
+    pub wins: [u64; 2],
+    /// Number of matches that reached the round limit.
+    pub draws: u64,
+    /// Total number of rounds played over all matches.
+    pub total_rounds: u64,
+}
+
+impl BatchSummary {
+    /// Adds the result of one match to the summary.
+    pub fn record(&mut self, outcome: &MatchOutcome) {
+        self.matches += 1;
+        self.total_rounds += outcome.rounds as u64;
+        match outcome.winner {
+            Some(i) => self.wins[i] += 1,
+            None => self.draws += 1,
+        }
+    }
+
+    /// Merges the results of another summary into this one.
+    pub fn merge(&mut self, other: &BatchSummary) {
+        self.matches += other.matches;
+        self.wins[0] += other.wins[0];
+        self.wins[1] += other.wins[1];
+        self.draws += other.draws;
+        self.total_rounds += other.total_rounds;
+    }
+}
+
+/// Creates the random number generator of one match of a batch.
+/// Each match gets its own ChaCha stream, so its result depends only on the
+/// master seed and its index, never on which thread plays it.
+pub fn match_rng(master_seed: u64, index: u64) -> ChaCha8Rng {
+    let mut rng = ChaCha8Rng::seed_from_u64(master_seed);
+    rng.set_stream(index);
+    rng
+}
+
+/// Simulates a human stopping the counter: the player aims for the exact moment
+/// the counter reaches the goal, with a normally distributed timing error.
+pub fn simulate_stop<R: Rng + ?Sized>(goal: u8, speed: u32, sigma_ms: f64, rng: &mut R) -> PlayerRes {
+    let ideal_ms = goal as f64 * speed.max(1) as f64;
+    let elapsed_ms = sample_normal(rng, ideal_ms, sigma_ms).max(0.0);
+    counter_at(elapsed_ms as u64, speed)
+}
+
+/// Rules the headless engine does not play, by name: the simulated players make
+/// no choices, so they neither wager, block nor spend stamina, and rounds have no
+/// modifiers nor critical hits. Commands simulating matches must refuse rules listing any.
+pub fn unsupported_rules(rules: &Ruleset) -> Vec<&'static str> {
+    [
+        (!rules.modifier_pool.is_empty(), "modifiers"),
+        (rules.wagering.is_some(), "wagering"),
+        (rules.defense.is_some(), "defense"),
+        (rules.stamina.is_some(), "stamina"),
+        (rules.crits.is_some(), "crits"),
+    ]
+    .into_iter()
+    .filter_map(|(used, name)| used.then_some(name))
+    .collect()
+}
+
+/// Plays one headless turn and returns the player's average score.
+/// Luck pulls the goals and forgives misses as in `play_turn`.
+fn simulate_turn<R: Rng + ?Sized>(player: &Player, cfg: &SimConfig, rng: &mut R) -> u32 {
+    let mut goals = create_goals(cfg.rules.goal_count, rng);
+    cfg.rules.luck.pull_goals(luck(player), &mut goals);
+    let total_score: u32 = goals
+        .iter()
+        .map(|target| {
+            let mut result = simulate_stop(*target, player.speed(), cfg.sigma_ms, rng);
+            cfg.rules.luck.forgive_miss(luck(player), &mut result, rng);
+            cfg.rules.score(*target, &result, player.strength())
+        })
+        .sum();
+    average_score(total_score, cfg.rules.goal_count)
+}
+
+/// Simulates a complete match between two players, following the same rules as
+/// `play_round`: the loser of a round loses the score difference in vitality and
+/// the winner picks a random penalty. The match ends when a player has no vitality left.
+/// The players are equipped for the rules, and the rules listed by
+/// `unsupported_rules` are not played.
+pub fn simulate_match<R: Rng + ?Sized>(p1: &Player, p2: &Player, cfg: &SimConfig, rng: &mut R) -> MatchOutcome {
+    let mut players = [p1.clone(), p2.clone()];
+    cfg.rules.equip(&mut players[0]);
+    cfg.rules.equip(&mut players[1]);
+    let mut rounds = 0;
+
+    while rounds < cfg.max_rounds {
+        rounds += 1;
+        let avg_score1 = simulate_turn(&players[0], cfg, rng);
+        let avg_score2 = simulate_turn(&players[1], cfg, rng);
+
+        let RoundOutcome::Won { winner, damage } = round_outcome([avg_score1, avg_score2]) else {
+            continue;
+        };
+        let (winner_player, victim) = split_players(&mut players, winner);
+        victim.lower_stat(Stat::Vitality, damage);
+        if victim.vitality() == 0 {
+            return MatchOutcome {
+                winner: Some(winner),
+                rounds,
+            };
+        }
+        let penalty = if rng.gen_bool(0.5) { Penalty::Speed } else { Penalty::Strength };
+        penalty.apply(winner_player, victim);
+    }
+
+    MatchOutcome { winner: None, rounds }
+}
+
+/// Simulates `matches` matches between two players, spread over `threads` threads.
+/// The summary only depends on `master_seed`, whatever the number of threads.
+pub fn run_batch(p1: &Player, p2: &Player, cfg: &SimConfig, matches: u64, master_seed: u64, threads: usize) -> BatchSummary {
+    let threads = threads.max(1) as u64;
+    let chunk = matches.div_ceil(threads);
+
+    thread::scope(|scope| {
+        // Each thread plays a contiguous range of match indices.
+        let handles: Vec<_> = (0..threads)
+            .map(|t| {
+                let start = (t * chunk).min(matches);
+                let end = (start + chunk).min(matches);
+                scope.spawn(move || {
+                    let mut summary = BatchSummary::default();
+                    for index in start..end {
+                        let mut rng = match_rng(master_seed, index);
+                        summary.record(&simulate_match(p1, p2, cfg, &mut rng));
+                    }
+                    summary
+                })
+            })
+            .collect();
+
+        let mut total = BatchSummary::default();
+        for handle in handles {
+            total.merge(&handle.join().expect("simulation thread panicked"));
+        }
+        total
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulate_stop_perfect() {
+        // Without timing error the simulated player always hits the goal.
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        let res = simulate_stop(42, 50, 0.0, &mut rng);
+        assert_eq!(res.counter, 42);
+        assert_eq!(res.miss, 0);
+    }
+
+    #[test]
+    fn test_unsupported_rules() {
+        assert!(unsupported_rules(&Ruleset::default()).is_empty());
+        let rules = Ruleset {
+            defense: Some(crate::defense::Defense::default()),
+            crits: Some(crate::crits::CritRules::default()),
+            ..Ruleset::default()
+        };
+        assert_eq!(unsupported_rules(&rules), ["defense", "crits"]);
+    }
+
+    #[test]
+    fn test_simulate_match_with_luck() {
+        // Lucky players are pulled toward friendlier goals: a lucky player wins more often.
+        let luck = crate::stat_map::StatKey::Extra(crate::luck::LUCK.to_string());
+        let lucky = Player::new("Alice".to_string(), 50, 50, 50);
+        let unlucky = Player::new("Bob".to_string(), 50, 50, 50);
+        let cfg = SimConfig { sigma_ms: 400.0, ..SimConfig::default() };
+        let fair = run_batch(&lucky, &unlucky, &cfg, 400, 3, 1);
+        let lucky = lucky.with_stat(luck, 25);
+        let favored = run_batch(&lucky, &unlucky, &cfg, 400, 3, 1);
+        assert!(favored.wins[0] > fair.wins[0]);
+    }
+
+    #[test]
+    fn test_simulate_match_ends() {
+        // A stronger player facing a weak one should end the match with a winner.
+        let p1 = Player::new("Alice".to_string(), 50, 50, 80);
+        let p2 = Player::new("Bob".to_string(), 50, 50, 0);
+        let outcome = simulate_match(&p1, &p2, &SimConfig::default(), &mut match_rng(7, 0));
+        assert!(outcome.winner.is_some());
+        assert!(outcome.rounds >= 1);
+    }
+
+    #[test]
+    fn test_run_batch_deterministic_across_threads() {
+        // The same master seed must give the same summary for any thread count.
+        let p1 = Player::new("Alice".to_string(), 50, 50, 55);
+        let p2 = Player::new("Bob".to_string(), 50, 45, 50);
+        let cfg = SimConfig::default();
+        let single = run_batch(&p1, &p2, &cfg, 200, 1234, 1);
+        let multi = run_batch(&p1, &p2, &cfg, 200, 1234, 7);
+        assert_eq!(single, multi);
+        assert_eq!(single.matches, 200);
+        assert_eq!(single.wins[0] + single.wins[1] + single.draws, 200);
+    }
+}
@@ -0,0 +1,184 @@
+use std::io::{self, BufWriter, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::error::GameError;
+use crate::events::{EventBus, EventSink, MatchEvent};
+
+/// Number of lines a spectator may fall behind before being disconnected.
+pub const SPECTATOR_BACKLOG: usize = 1024;
+
+/// A connected spectator: the channel to the thread writing to them, and their
+/// connection, to close it if they fall behind.
+struct Client {
+    lines: SyncSender<Arc<str>>,
+    stream: TcpStream,
+}
+
+/// Every connected spectator.
+type Clients = Arc<Mutex<Vec<Client>>>;
+
+/// Read-only feed of a match's events for spectators.
+///
+/// Spectators connect over TCP and receive every event as one JSON object per
+/// line, with the id of the match it happened in. The sink only pushes events into a channel: serialization and network
+/// writes happen on other threads, so that neither a spectator nor their number
+/// can slow down the player's counter. A spectator who does not read the lines
+/// and falls `SPECTATOR_BACKLOG` lines behind is disconnected.
+pub struct SpectatorFeed {
+    events: Sender<MatchEvent>,
+}
+
+impl EventSink for SpectatorFeed {
+    fn on_event(&self, event: &MatchEvent) {
+        // The broadcaster only stops with the process: nothing to do if it is gone.
+        let _ = self.events.send(event.clone());
+    }
+}
+
+/// Starts broadcasting the events of the matches reporting to `events` to the
+/// spectators connecting to `addr`. Returns the address the feed listens on.
+pub fn start_spectator_feed(addr: &str, events: &EventBus) -> Result<SocketAddr, GameError> {
+    let listener = TcpListener::bind(addr)?;
+    let local_addr = listener.local_addr()?;
+    events.subscribe(Arc::new(spawn_feed(listener)));
+    Ok(local_addr)
+}
+
+/// Spawns the threads accepting spectators and broadcasting events to them.
+fn spawn_feed(listener: TcpListener) -> SpectatorFeed {
+    let clients: Clients = Arc::new(Mutex::new(Vec::new()));
+    let (events, received) = mpsc::channel();
+
+    let acceptor_clients = Arc::clone(&clients);
+    thread::spawn(move || accept_spectators(listener, acceptor_clients));
+    thread::spawn(move || broadcast(received, clients));
+
+    SpectatorFeed { events }
+}
+
+/// Accepts spectators and gives each of them a writer thread.
+fn accept_spectators(listener: TcpListener, clients: Clients) {
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                log::warn!("Could not accept a spectator: {}", e);
+                continue;
+            }
+        };
+        log::info!("Spectator connected from {:?}", stream.peer_addr().ok());
+        let closer = match stream.try_clone() {
+            Ok(closer) => closer,
+            Err(e) => {
+                log::warn!("Could not accept a spectator: {}", e);
+                continue;
+            }
+        };
+        let (lines, received) = mpsc::sync_channel(SPECTATOR_BACKLOG);
+        clients.lock().unwrap_or_else(|e| e.into_inner()).push(Client { lines, stream: closer });
+        thread::spawn(move || {
+            if let Err(e) = write_lines(stream, received) {
+                log::info!("Spectator disconnected: {}", e);
+            }
+        });
+    }
+}
+
+/// Serializes every event once and sends it to every spectator still connected.
+fn broadcast(events: Receiver<MatchEvent>, clients: Clients) {
+    for event in events {
+        let line: Arc<str> = match serde_json::to_string(&event) {
+            Ok(json) => json.into(),
+            Err(e) => {
+                log::warn!("Could not serialize {:?}: {}", event, e);
+                continue;
+            }
+        };
+        send_line(&mut clients.lock().unwrap_or_else(|e| e.into_inner()), &line);
+    }
+}
+
+/// Sends a line to every spectator without waiting. A spectator whose writer thread
+/// has stopped is forgotten. One who is too far behind is disconnected, which also
+/// ends their writer thread.
+fn send_line(clients: &mut Vec<Client>, line: &Arc<str>) {
+    clients.retain(|client| match client.lines.try_send(Arc::clone(line)) {
+        Ok(()) => true,
+        Err(TrySendError::Full(_)) => {
+            log::info!("Spectator disconnected: more than {} events behind", SPECTATOR_BACKLOG);
+            let _ = client.stream.shutdown(Shutdown::Both);
+            false
+        }
+        Err(TrySendError::Disconnected(_)) => false,
+    });
+}
+
+/// Writes the lines sent to one spectator until they disconnect.
+fn write_lines(stream: TcpStream, lines: Receiver<Arc<str>>) -> io::Result<()> {
+    stream.set_nodelay(true)?;
+    let mut writer = BufWriter::new(stream);
+    for line in lines {
+        writeln!(writer, "{}", line)?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::GameEvent;
+    use std::io::{BufRead, BufReader};
+    use std::time::{Duration, Instant};
+
+    /// Connects a spectator to the feed.
+    fn connect(addr: SocketAddr) -> BufReader<TcpStream> {
+        BufReader::new(TcpStream::connect(addr).unwrap())
+    }
+
+    #[test]
+    fn test_broadcast_to_several_spectators() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let feed = spawn_feed(listener);
+        let mut spectators = [connect(addr), connect(addr)];
+
+        // Spectators may be accepted after the first events: send until both have one.
+        let deadline = Instant::now() + Duration::from_secs(5);
+        for spectator in spectators.iter_mut() {
+            let mut line = String::new();
+            while line.is_empty() {
+                assert!(Instant::now() < deadline, "no event received");
+                let event = GameEvent::VitalityChanged { player: "Bob".to_string(), vitality: 40 };
+                feed.on_event(&MatchEvent { match_id: 2, event });
+                spectator.get_mut().set_read_timeout(Some(Duration::from_millis(50))).unwrap();
+                let _ = spectator.read_line(&mut line);
+            }
+            assert_eq!(line.trim_end(), r#"{"match_id":2,"event":"vitality_changed","player":"Bob","vitality":40}"#);
+        }
+    }
+
+    #[test]
+    fn test_slow_spectator_is_disconnected() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut spectator = connect(listener.local_addr().unwrap());
+        let (stream, _) = listener.accept().unwrap();
+        // Nothing reads the spectator's lines: the channel fills up.
+        let (lines, _received) = mpsc::sync_channel(SPECTATOR_BACKLOG);
+        let mut clients = vec![Client { lines, stream }];
+        let line: Arc<str> = "{}".into();
+        for _ in 0..SPECTATOR_BACKLOG {
+            send_line(&mut clients, &line);
+        }
+        assert_eq!(clients.len(), 1);
+        send_line(&mut clients, &line);
+        assert!(clients.is_empty());
+        // The connection is closed.
+        let mut rest = String::new();
+        spectator.get_mut().set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        assert_eq!(spectator.read_line(&mut rest).unwrap(), 0);
+    }
+}
@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+
+use crate::classes::{Player, DEFAULT_STAMINA};
+
+/// How players earn and spend stamina.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StaminaRules {
+    /// Most stamina a player can regenerate to.
+    pub max: u32,
+    /// Stamina regenerated at the start of every round.
+    pub regen_per_round: u32,
+    /// Stamina spent to play an objective again.
+    pub retry_cost: u32,
+    /// Stamina spent to play an ability card.
+    pub card_cost: u32,
+    /// Stamina spent to slow the counter of an objective down.
+    pub slow_cost: u32,
+    /// How much slower the counter runs when a player pays for it, in percent.
+    pub slow_percent: u32,
+}
+
+impl Default for StaminaRules {
+    /// Default stamina rules: up to 3 stamina, 1 regenerated per round, and every action costs 1.
+    /// Slowing the counter makes it run 50% slower.
+    fn default() -> Self {
+        StaminaRules {
+            max: DEFAULT_STAMINA,
+            regen_per_round: 1,
+            retry_cost: 1,
+            card_cost: 1,
+            slow_cost: 1,
+            slow_percent: 50,
+        }
+    }
+}
+
+impl StaminaRules {
+    /// Regenerates a player's stamina at the start of a round, up to the maximum.
+    /// A player above the maximum keeps their stamina.
+    pub fn regenerate(&self, player: &mut Player) {
+        if player.stamina < self.max {
+            player.stamina = (player.stamina + self.regen_per_round).min(self.max);
+        }
+    }
+}
+
+/// Spends `cost` stamina of a player. Returns `false`, spending nothing, when
+/// the player does not have enough.
+pub fn spend(player: &mut Player, cost: u32) -> bool {
+    if player.stamina < cost {
+        return false;
+    }
+    player.stamina -= cost;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regenerate() {
+        let rules = StaminaRules { max: 4, regen_per_round: 3, ..StaminaRules::default() };
+        let mut player = Player::new("Alice".to_string(), 50, 50, 50);
+        player.stamina = 0;
+        rules.regenerate(&mut player);
+        assert_eq!(player.stamina, 3);
+        rules.regenerate(&mut player);
+        assert_eq!(player.stamina, 4);
+        player.stamina = 6;
+        rules.regenerate(&mut player);
+        assert_eq!(player.stamina, 6);
+    }
+
+    #[test]
+    fn test_spend() {
+        let mut player = Player::new("Alice".to_string(), 50, 50, 50);
+        player.stamina = 2;
+        assert!(spend(&mut player, 2));
+        assert!(!spend(&mut player, 1));
+        assert!(spend(&mut player, 0));
+        assert_eq!(player.stamina, 0);
+    }
+}
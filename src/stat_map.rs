@@ -0,0 +1,284 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use crate::classes::Stat;
+
+/// Key of a player's stat: one of the core stats the rules of the game rely on,
+/// or an extra stat added by a ruleset, like `armor`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum StatKey {
+    Core(Stat),
+    Extra(String),
+}
+
+impl From<Stat> for StatKey {
+    fn from(stat: Stat) -> Self {
+        StatKey::Core(stat)
+    }
+}
+
+impl fmt::Display for StatKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatKey::Core(stat) => write!(f, "{}", stat.name()),
+            StatKey::Extra(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Names extra stats cannot take: they are saved next to the other fields of the
+/// player (see `StatMap`), which already use these.
+pub const RESERVED_NAMES: [&str; 6] = ["name", "stamina", "controller", "status", "deck", "modifiers"];
+
+impl FromStr for StatKey {
+    type Err = String;
+
+    /// Parses the name of a core stat, or of an extra stat made of lowercase
+    /// letters, digits and underscores, other than the `RESERVED_NAMES`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(stat) = s.parse::<Stat>() {
+            return Ok(StatKey::Core(stat));
+        }
+        let name = s.to_ascii_lowercase();
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') {
+            return Err(format!("invalid stat name '{}' (expected letters, digits or '_')", s));
+        }
+        if RESERVED_NAMES.contains(&name.as_str()) {
+            return Err(format!("'{}' is a field of the player, not a stat name", name));
+        }
+        Ok(StatKey::Extra(name))
+    }
+}
+
+impl TryFrom<String> for StatKey {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<StatKey> for String {
+    fn from(key: StatKey) -> Self {
+        key.to_string()
+    }
+}
+
+/// Parses an extra stat and the value players start with, as `NAME=VALUE`.
+/// Core stats are refused: every player has them already.
+pub fn parse_extra_stat(s: &str) -> Result<(StatKey, u32), String> {
+    let (name, value) = s.split_once('=').ok_or_else(|| format!("expected NAME=VALUE, got '{}'", s))?;
+    let key = match name.trim().parse::<StatKey>()? {
+        StatKey::Core(stat) => return Err(format!("'{}' is a core stat, not an extra one", stat.name())),
+        key => key,
+    };
+    let value = value.trim().parse::<u32>().map_err(|e| format!("invalid value '{}': {}", value, e))?;
+    Ok((key, value))
+}
+
+/// How a modifier changes a stat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Bonus {
+    /// Adds this amount to the stat, or removes it if negative.
+    Flat(i32),
+    /// Changes the stat by this share of its value after flat bonuses, in percent.
+    Percent(i32),
+}
+
+/// A temporary or lasting change to one of a player's stats.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatModifier {
+    /// The stat changed. It cannot be vitality: damage lowers the base value of
+    /// vitality, which must then be the value the player is left with.
+    #[serde(deserialize_with = "modifiable_stat")]
+    pub stat: StatKey,
+    /// How the stat is changed.
+    #[serde(flatten)]
+    pub bonus: Bonus,
+    /// What granted the modifier, shown when it wears off.
+    pub source: String,
+    /// Rounds left before the modifier wears off, or `None` for the whole match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rounds: Option<u32>,
+}
+
+/// Reads the stat of a modifier, refusing vitality.
+fn modifiable_stat<'de, D: Deserializer<'de>>(deserializer: D) -> Result<StatKey, D::Error> {
+    let key = StatKey::deserialize(deserializer)?;
+    if key == StatKey::Core(Stat::Vitality) {
+        return Err(de::Error::custom("vitality cannot be modified, only lowered by damage"));
+    }
+    Ok(key)
+}
+
+/// The stats of a player: a base value for each stat, and the modifiers in effect.
+/// It is saved as one field per stat next to the other fields of the player,
+/// e.g. `"vitality": 50, "armor": 5`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatMap {
+    #[serde(flatten)]
+    base: BTreeMap<StatKey, u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    modifiers: Vec<StatModifier>,
+}
+
+impl StatMap {
+    /// Whether the player has a base value for the stat.
+    pub fn contains(&self, key: impl Into<StatKey>) -> bool {
+        self.base.contains_key(&key.into())
+    }
+
+    /// Returns the value of a stat with every modifier applied: flat bonuses first,
+    /// then the sum of percent bonuses. A stat the player does not have is 0.
+    pub fn get(&self, key: impl Into<StatKey>) -> u32 {
+        self.effective(&key.into())
+    }
+
+    /// Returns the value of a stat before modifiers.
+    pub fn base(&self, key: impl Into<StatKey>) -> u32 {
+        self.base.get(&key.into()).copied().unwrap_or(0)
+    }
+
+    /// Returns a mutable reference to the value of a stat before modifiers,
+    /// adding the stat at 0 if the player does not have it.
+    pub fn base_mut(&mut self, key: impl Into<StatKey>) -> &mut u32 {
+        self.base.entry(key.into()).or_insert(0)
+    }
+
+    /// Sets the value of a stat before modifiers.
+    pub fn set(&mut self, key: impl Into<StatKey>, value: u32) {
+        self.base.insert(key.into(), value);
+    }
+
+    /// Iterates over the stats with their modified values: core stats first, then
+    /// extra stats by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&StatKey, u32)> {
+        self.base.keys().map(|key| (key, self.effective(key)))
+    }
+
+    /// Returns the modifiers in effect.
+    pub fn modifiers(&self) -> &[StatModifier] {
+        &self.modifiers
+    }
+
+    /// Puts a modifier in effect.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the modifier changes vitality (see `StatModifier::stat`).
+    pub fn add_modifier(&mut self, modifier: StatModifier) {
+        assert!(modifier.stat != StatKey::Core(Stat::Vitality), "vitality cannot be modified");
+        self.modifiers.push(modifier);
+    }
+
+    /// Counts down the modifiers at the end of a round and removes those that wear off.
+    /// Returns the removed modifiers.
+    pub fn tick(&mut self) -> Vec<StatModifier> {
+        for rounds in self.modifiers.iter_mut().filter_map(|m| m.rounds.as_mut()) {
+            *rounds = rounds.saturating_sub(1);
+        }
+        let (expired, kept) = std::mem::take(&mut self.modifiers).into_iter().partition(|m| m.rounds == Some(0));
+        self.modifiers = kept;
+        expired
+    }
+
+    fn effective(&self, key: &StatKey) -> u32 {
+        let base = self.base.get(key).copied().unwrap_or(0) as i64;
+        let (flat, percent) = self
+            .modifiers
+            .iter()
+            .filter(|m| &m.stat == key)
+            .fold((0i64, 0i64), |(flat, percent), m| match m.bonus {
+                Bonus::Flat(amount) => (flat + amount as i64, percent),
+                Bonus::Percent(amount) => (flat, percent + amount as i64),
+            });
+        ((base + flat) * (100 + percent) / 100).clamp(0, u32::MAX as i64) as u32
+    }
+}
+
+impl FromIterator<(StatKey, u32)> for StatMap {
+    fn from_iter<I: IntoIterator<Item = (StatKey, u32)>>(iter: I) -> Self {
+        StatMap {
+            base: iter.into_iter().collect(),
+            modifiers: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn armor() -> StatKey {
+        StatKey::Extra("armor".to_string())
+    }
+
+    fn modifier(stat: impl Into<StatKey>, bonus: Bonus, rounds: Option<u32>) -> StatModifier {
+        StatModifier { stat: stat.into(), bonus, source: "test".to_string(), rounds }
+    }
+
+    #[test]
+    fn test_parse_stat_key() {
+        assert_eq!("Speed".parse::<StatKey>(), Ok(StatKey::Core(Stat::Speed)));
+        assert_eq!("Armor".parse::<StatKey>(), Ok(armor()));
+        assert!("".parse::<StatKey>().is_err());
+        assert!("magic power".parse::<StatKey>().is_err());
+        // Fields of the player are not stats.
+        assert!("Stamina".parse::<StatKey>().is_err());
+        assert!(serde_json::from_str::<StatKey>(r#""deck""#).is_err());
+        assert_eq!(StatKey::from(Stat::Strength).to_string(), "strength");
+    }
+
+    #[test]
+    fn test_parse_extra_stat() {
+        assert_eq!(parse_extra_stat("Armor=5"), Ok((armor(), 5)));
+        assert!(parse_extra_stat("armor").is_err());
+        assert!(parse_extra_stat("armor=-1").is_err());
+        assert!(parse_extra_stat("speed=5").is_err());
+        assert!(parse_extra_stat("name=5").is_err());
+    }
+
+    #[test]
+    fn test_modified_values() {
+        let mut stats: StatMap = [(Stat::Speed.into(), 50), (armor(), 4)].into_iter().collect();
+        stats.add_modifier(modifier(Stat::Speed, Bonus::Flat(10), None));
+        stats.add_modifier(modifier(Stat::Speed, Bonus::Percent(-50), Some(1)));
+        stats.add_modifier(modifier(armor(), Bonus::Flat(-10), None));
+        assert_eq!(stats.get(Stat::Speed), 30);
+        assert_eq!(stats.base(Stat::Speed), 50);
+        assert_eq!(stats.get(armor()), 0);
+        assert_eq!(stats.get(Stat::Vitality), 0);
+        let values: Vec<(String, u32)> = stats.iter().map(|(key, value)| (key.to_string(), value)).collect();
+        assert_eq!(values, [("speed".to_string(), 30), ("armor".to_string(), 0)]);
+    }
+
+    #[test]
+    fn test_tick() {
+        let mut stats: StatMap = [(Stat::Strength.into(), 20)].into_iter().collect();
+        stats.add_modifier(modifier(Stat::Strength, Bonus::Flat(5), Some(2)));
+        stats.add_modifier(modifier(Stat::Strength, Bonus::Flat(5), None));
+        assert!(stats.tick().is_empty());
+        assert_eq!(stats.get(Stat::Strength), 30);
+        assert_eq!(stats.tick().len(), 1);
+        assert_eq!(stats.get(Stat::Strength), 25);
+        assert_eq!(stats.modifiers().len(), 1);
+    }
+
+    #[test]
+    fn test_serialized_fields() {
+        let mut stats: StatMap = [(Stat::Vitality.into(), 50), (armor(), 5)].into_iter().collect();
+        assert_eq!(serde_json::to_string(&stats).unwrap(), r#"{"vitality":50,"armor":5}"#);
+        stats.add_modifier(modifier(armor(), Bonus::Percent(20), Some(3)));
+        let json = serde_json::to_string(&stats).unwrap();
+        assert!(json.contains(r#""modifiers":[{"stat":"armor","percent":20,"source":"test","rounds":3}]"#));
+        assert_eq!(serde_json::from_str::<StatMap>(&json).unwrap(), stats);
+        // Vitality is only lowered by damage.
+        let json = r#"{"vitality":50,"modifiers":[{"stat":"vitality","flat":10,"source":"test"}]}"#;
+        assert!(serde_json::from_str::<StatMap>(json).is_err());
+    }
+}
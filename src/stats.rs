@@ -0,0 +1,295 @@
+use serde::{Deserialize, Serialize};
+
+use crate::classes::PlayerRes;
+use crate::helper::{score_tier, signed_offset, timing_error_ms};
+use crate::i18n::{tr, Msg};
+
+/// Bounds of the offset histogram buckets (inclusive), from earliest to latest.
+const HISTOGRAM_BUCKETS: [(i8, i8); 7] = [
+    (-50, -11),
+    (-10, -6),
+    (-5, -1),
+    (0, 0),
+    (1, 5),
+    (6, 10),
+    (11, 50),
+];
+
+/// Maximum width of a histogram bar, in characters.
+const HISTOGRAM_WIDTH: usize = 20;
+
+/// Minimum number of objectives before a player's consistency can be judged.
+pub const BOT_MIN_OBJECTIVES: usize = 8;
+
+/// Standard deviation of the timing error (in ms) below which a player is
+/// considered too consistent to be human.
+pub const BOT_MAX_STD_DEV_MS: f64 = 15.0;
+
+/// Statistics recorded for a single objective.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObjectiveStat {
+    /// The target value of the objective.
+    pub goal: u8,
+    /// The counter value at the moment of stopping.
+    pub counter: u8,
+    /// The number of misses during the objective.
+    pub miss: u32,
+    /// Signed offset from the goal: negative when early, positive when late.
+    pub offset: i8,
+    /// Time taken to stop the counter, in milliseconds.
+    pub elapsed_ms: u64,
+    /// Signed distance from the ideal stopping moment, in milliseconds.
+    pub error_ms: i64,
+    /// Number of stops rejected for being too fast to be human.
+    pub rejected: u32,
+    /// Accuracy tier reached, from 1 (perfect) to 5.
+    pub tier: u8,
+    /// Score obtained for the objective.
+    pub score: u32,
+    /// Whether the stop was a critical hit.
+    #[serde(default)]
+    pub crit: bool,
+    /// Number of perfect stops in a row within the turn up to this one, 0 if it is not perfect.
+    #[serde(default)]
+    pub streak: u32,
+}
+
+impl ObjectiveStat {
+    /// Builds the statistics of an objective from its goal, result, the player's speed and the score.
+    pub fn new(goal: u8, res: &PlayerRes, speed: u32, score: u32) -> Self {
+        let offset = signed_offset(goal, res.counter);
+        ObjectiveStat {
+            goal,
+            counter: res.counter,
+            miss: res.miss,
+            offset,
+            elapsed_ms: res.elapsed_ms,
+            error_ms: timing_error_ms(offset, res.elapsed_ms, speed),
+            rejected: res.rejected,
+            tier: score_tier(goal, res.counter),
+            score,
+            crit: false,
+            streak: 0,
+        }
+    }
+
+    /// Whether the stop was perfect: on the goal, before the counter ever wrapped.
+    pub fn is_perfect(&self) -> bool {
+        self.tier == 1 && self.miss == 0
+    }
+}
+
+/// Statistics accumulated by a player over a match.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerStats {
+    /// Every objective played, in order.
+    pub objectives: Vec<ObjectiveStat>,
+}
+
+impl PlayerStats {
+    /// Records the statistics of one objective.
+    pub fn record(&mut self, stat: ObjectiveStat) {
+        self.objectives.push(stat);
+    }
+
+    /// Appends every objective of another set of statistics.
+    pub fn extend(&mut self, other: PlayerStats) {
+        self.objectives.extend(other.objectives);
+    }
+
+    /// Mean absolute offset from the goal, or 0 without objectives.
+    pub fn mean_abs_error(&self) -> f64 {
+        self.mean(|s| s.offset.unsigned_abs() as f64)
+    }
+
+    /// Mean signed offset: negative when the player tends to stop early.
+    pub fn bias(&self) -> f64 {
+        self.mean(|s| s.offset as f64)
+    }
+
+    /// Mean time taken to stop the counter, in milliseconds.
+    pub fn mean_elapsed_ms(&self) -> f64 {
+        self.mean(|s| s.elapsed_ms as f64)
+    }
+
+    /// Number of perfect stops (see `ObjectiveStat::is_perfect`).
+    pub fn perfect_hits(&self) -> usize {
+        self.objectives.iter().filter(|s| s.is_perfect()).count()
+    }
+
+    /// Number of critical hits.
+    pub fn crits(&self) -> usize {
+        self.objectives.iter().filter(|s| s.crit).count()
+    }
+
+    /// Longest run of perfect stops in a row within a turn.
+    pub fn best_streak(&self) -> u32 {
+        self.objectives.iter().map(|s| s.streak).max().unwrap_or(0)
+    }
+
+    /// Total number of stops rejected for being too fast.
+    pub fn rejected_stops(&self) -> u32 {
+        self.objectives.iter().map(|s| s.rejected).sum()
+    }
+
+    /// Standard deviation of the timing error, in milliseconds.
+    pub fn error_std_dev_ms(&self) -> f64 {
+        let mean = self.mean(|s| s.error_ms as f64);
+        self.mean(|s| (s.error_ms as f64 - mean).powi(2)).sqrt()
+    }
+
+    /// Whether the player's timing is too consistent to be human,
+    /// which hints at a bot or a keyboard macro.
+    pub fn is_suspicious(&self) -> bool {
+        self.objectives.len() >= BOT_MIN_OBJECTIVES && self.error_std_dev_ms() < BOT_MAX_STD_DEV_MS
+    }
+
+    /// Number of objectives stopped early and late.
+    pub fn early_late(&self) -> (usize, usize) {
+        let early = self.objectives.iter().filter(|s| s.offset < 0).count();
+        let late = self.objectives.iter().filter(|s| s.offset > 0).count();
+        (early, late)
+    }
+
+    /// Number of objectives falling in each offset bucket of `HISTOGRAM_BUCKETS`.
+    pub fn histogram(&self) -> [usize; HISTOGRAM_BUCKETS.len()] {
+        let mut counts = [0; HISTOGRAM_BUCKETS.len()];
+        for stat in &self.objectives {
+            if let Some(i) = HISTOGRAM_BUCKETS
+                .iter()
+                .position(|(low, high)| (*low..=*high).contains(&stat.offset))
+            {
+                counts[i] += 1;
+            }
+        }
+        counts
+    }
+
+    /// Builds the end-of-match accuracy report of a player.
+    pub fn report(&self, name: &str) -> String {
+        let mut lines = vec![tr(Msg::ReportTitle { name })];
+        if self.objectives.is_empty() {
+            lines.push(tr(Msg::ReportNoObjective));
+            return lines.join("\n") + "\n";
+        }
+        let (early, late) = self.early_late();
+        lines.push(tr(Msg::ReportObjectives(self.objectives.len())));
+        lines.push(tr(Msg::ReportPerfectHits(self.perfect_hits())));
+        if self.crits() > 0 {
+            lines.push(tr(Msg::ReportCrits(self.crits())));
+        }
+        if self.best_streak() > 1 {
+            lines.push(tr(Msg::ReportBestStreak(self.best_streak())));
+        }
+        lines.push(tr(Msg::ReportMeanAbsError(self.mean_abs_error())));
+        lines.push(tr(Msg::ReportBias { bias: self.bias(), early, late }));
+        lines.push(tr(Msg::ReportMeanTime(self.mean_elapsed_ms())));
+        lines.push(tr(Msg::ReportSpread(self.error_std_dev_ms())));
+        if self.rejected_stops() > 0 {
+            lines.push(tr(Msg::ReportRejected(self.rejected_stops())));
+        }
+        if self.is_suspicious() {
+            lines.push(tr(Msg::ReportSuspicious));
+        }
+        let mut out = lines.join("\n") + "\n";
+
+        // Scale the bars so that the largest bucket fills the histogram width.
+        let counts = self.histogram();
+        let max = counts.iter().copied().max().unwrap_or(0).max(1);
+        for ((low, high), count) in HISTOGRAM_BUCKETS.iter().zip(counts) {
+            let label = if low == high {
+                format!("{}", low)
+            } else {
+                format!("{:+}..{:+}", low, high)
+            };
+            let bar = "#".repeat((count * HISTOGRAM_WIDTH).div_ceil(max));
+            out.push_str(&format!("{:>8} | {:<width$} {}\n", label, bar, count, width = HISTOGRAM_WIDTH));
+        }
+        out
+    }
+
+    /// Averages a value over every objective, or returns 0 without objectives.
+    fn mean(&self, value: impl Fn(&ObjectiveStat) -> f64) -> f64 {
+        if self.objectives.is_empty() {
+            0.0
+        } else {
+            self.objectives.iter().map(value).sum::<f64>() / self.objectives.len() as f64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stat(goal: u8, counter: u8) -> ObjectiveStat {
+        let res = PlayerRes { counter, miss: 0, elapsed_ms: counter as u64 * 50, rejected: 0 };
+        ObjectiveStat::new(goal, &res, 50, 0)
+    }
+
+    #[test]
+    fn test_objective_stat_new() {
+        let s = stat(50, 47);
+        assert_eq!(s.offset, -3);
+        assert_eq!(s.tier, 2);
+        assert_eq!(s.elapsed_ms, 2350);
+        assert!(!s.is_perfect());
+        // Stopping on the goal after the counter wrapped is not perfect.
+        assert!(stat(50, 50).is_perfect());
+        assert!(!ObjectiveStat { miss: 1, ..stat(50, 50) }.is_perfect());
+    }
+
+    #[test]
+    fn test_player_stats_summary() {
+        let mut stats = PlayerStats::default();
+        stats.record(stat(50, 50));
+        stats.record(stat(50, 46));
+        stats.record(stat(20, 28));
+        assert_eq!(stats.perfect_hits(), 1);
+        assert_eq!(stats.early_late(), (1, 1));
+        assert!((stats.mean_abs_error() - 4.0).abs() < 1e-9);
+        assert!((stats.bias() - 4.0 / 3.0).abs() < 1e-9);
+        assert_eq!(stats.histogram(), [0, 0, 1, 1, 0, 1, 0]);
+        assert!(!stats.report("Alice").contains("Critical hits"));
+        stats.record(ObjectiveStat { miss: 1, ..stat(50, 50) });
+        assert_eq!(stats.perfect_hits(), 1);
+    }
+
+    #[test]
+    fn test_crits_and_streaks() {
+        let mut stats = PlayerStats::default();
+        for (streak, crit) in [(1, false), (2, true), (0, false), (1, true)] {
+            stats.record(ObjectiveStat { streak, crit, ..stat(50, if streak > 0 { 50 } else { 40 }) });
+        }
+        assert_eq!((stats.crits(), stats.best_streak()), (2, 2));
+        let report = stats.report("Alice");
+        assert!(report.contains("Critical hits: 2"));
+        assert!(report.contains("Best perfect streak: 2"));
+    }
+
+    #[test]
+    fn test_suspicious_consistency() {
+        // Perfect stops at the very same point of the tick every time look like a macro.
+        let mut stats = PlayerStats::default();
+        for goal in 0..BOT_MIN_OBJECTIVES as u8 {
+            stats.record(stat(goal * 10, goal * 10));
+        }
+        assert!(stats.is_suspicious());
+        assert!(stats.report("Bot").contains("possible bot/macro"));
+
+        // A human spreads the stops around the goal.
+        let mut stats = PlayerStats::default();
+        for (i, goal) in (0..BOT_MIN_OBJECTIVES as u8).enumerate() {
+            let counter = goal * 10 + [0, 2, 1, 3][i % 4];
+            stats.record(stat(goal * 10, counter));
+        }
+        assert!(!stats.is_suspicious());
+    }
+
+    #[test]
+    fn test_empty_report() {
+        let stats = PlayerStats::default();
+        assert_eq!(stats.mean_abs_error(), 0.0);
+        assert!(stats.report("Alice").contains("No objective played."));
+    }
+}
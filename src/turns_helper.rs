@@ -1,81 +1,544 @@
-use std::time::Duration;
+#[cfg(not(test))]
+use std::time::{Duration, Instant};
+#[cfg(not(test))]
 use std::thread;
+#[cfg(not(test))]
 use std::io::{self, Write};
+#[cfg(not(test))]
 use std::sync::{Arc, Mutex, atomic::{AtomicBool, AtomicU32, Ordering},};
 
-use crate::classes::{Player, PlayerRes};
-use crate::helper::{compute_score, create_goals};
+use rand::Rng;
+
+use crate::bot::{bot_objective, bot_penalty, bot_stake};
+use crate::campaign::{ai_card, ai_objective, ai_penalty, ai_retry};
+use crate::classes::{Controller, Player, PlayerRes, Stat};
+use crate::crits::CritRules;
+use crate::defense::Defense;
+use crate::error::GameError;
+use crate::events::{GameEvent, MatchEvents};
+use crate::i18n::{say, Msg};
+use crate::luck::luck;
+use crate::helper::{average_score, create_goals, round_outcome, Effect, Penalty, RoundOutcome};
+use crate::modifiers::{draw_modifier, Modifier, RoundHook, RoundRules};
+use crate::ruleset::Ruleset;
+use crate::stamina::{spend, StaminaRules};
+use crate::stats::{ObjectiveStat, PlayerStats};
+use crate::wager::{ai_stake, Wagering};
+#[cfg(not(test))]
+use crate::autosave::{is_interrupted, set_counter_running};
+#[cfg(not(test))]
+use crate::i18n::{is_yes, tr};
+#[cfg(not(test))]
+use crate::input::{prompt, read_line};
+
+/// Minimum delay between starting and stopping the counter for a stop to be accepted.
+/// Faster stops can only come from input typed ahead of time or from a macro.
+pub const MIN_STOP_DELAY_MS: u64 = 150;
+
+/// Whether a stop arriving `elapsed_ms` after the start is plausible for a human.
+pub fn is_human_stop(elapsed_ms: u64) -> bool {
+    elapsed_ms >= MIN_STOP_DELAY_MS
+}
+
+/// Result of a round, as returned by `play_round`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RoundReport {
+    /// Statistics of every objective played by each player.
+    pub stats: [PlayerStats; 2],
+    /// Index of the round's winner (0 or 1), or `None` for a tie.
+    pub winner: Option<usize>,
+    /// Vitality each player took from the other with the damage of the round:
+    /// the winner's hit, and what the loser reflected. Stakes and miss costs
+    /// are not damage.
+    pub damage_dealt: [u32; 2],
+}
 
 /// Executes a complete round between two players using helper functions.
-pub fn play_round(p1: &mut Player, p2: &mut Player, goal_count: u8) {
-    println!("----- New Round -----");
+/// Objectives are drawn from `rng`, so a seeded generator replays the same goals.
+/// The events of the round are emitted through `events`.
+pub fn play_round<R: Rng + ?Sized>(
+    p1: &mut Player,
+    p2: &mut Player,
+    rules: &Ruleset,
+    events: &MatchEvents,
+    rng: &mut R,
+) -> Result<RoundReport, GameError> {
+    play_round_with(p1, p2, rules, &mut (), events, rng)
+}
+
+/// Executes a round like `play_round`, after letting `hook` change the rules of the round.
+/// The modifier drawn from the ruleset's pool, if any, is added to the hook's.
+pub fn play_round_with<R: Rng + ?Sized>(
+    p1: &mut Player,
+    p2: &mut Player,
+    rules: &Ruleset,
+    hook: &mut dyn RoundHook,
+    events: &MatchEvents,
+    rng: &mut R,
+) -> Result<RoundReport, GameError> {
+    say(Msg::NewRound);
+    events.emit(GameEvent::RoundStarted);
+
+    // Set the rules of the round and announce what changes.
+    let mut round = RoundRules::default();
+    hook.start_round([p1, p2], &mut round);
+    if let Some(modifier) = draw_modifier(&rules.modifier_pool, rng) {
+        round.add(modifier);
+    }
+    if !round.modifiers.is_empty() {
+        say(Msg::RoundModifiers(&round.modifiers));
+    }
+
+    // Players regenerate stamina, and those with a deck draw an ability card.
+    if let Some(stamina) = &rules.stamina {
+        stamina.regenerate(p1);
+        stamina.regenerate(p2);
+    }
+    draw_card(p1, rng);
+    draw_card(p2, rng);
+
+    // Under betting rules, both players stake vitality on the round.
+    let stakes = match &rules.wagering {
+        Some(wagering) => [place_stake(p1, p2, wagering, events)?, place_stake(p2, p1, wagering, events)?],
+        None => [0, 0],
+    };
 
     // Execute each player's turn and calculate their average score.
-    let avg_score1 = play_turn(p1, goal_count);
-    let avg_score2 = play_turn(p2, goal_count);
+    let (avg_score1, stats1) = play_turn(p1, p2, rules, &round, events, rng)?;
+    let (avg_score2, stats2) = play_turn(p2, p1, rules, &round, events, rng)?;
 
     // Compare average scores and update vitality accordingly.
-    if avg_score1 == avg_score2 {
-        println!("The round is tied; no change in vitality.");
-    } else if avg_score1 > avg_score2 {
-        let diff = avg_score1 - avg_score2;
-        println!("{} wins the round!", p1.name);
-        println!("{} loses {} vitality points.", p2.name, diff);
-        p2.vitality = p2.vitality.saturating_sub(diff);
-        apply_pusnishment(p2);
+    let outcome = round_outcome([avg_score1, avg_score2]);
+    let vitality = [p1.vitality(), p2.vitality()];
+    let damage_dealt = match outcome {
+        RoundOutcome::Tie => {
+            say(Msg::RoundTied);
+            events.emit(GameEvent::RoundTied);
+            if stakes != [0, 0] {
+                say(Msg::StakesRefunded);
+                pay_stake(p1, stakes[0], events);
+                pay_stake(p2, stakes[1], events);
+            }
+            [0, 0]
+        }
+        RoundOutcome::Won { winner: 0, damage } => {
+            say(Msg::RoundWon { winner: &p1.name });
+            let damage = critical_damage(p1, &stats1, rules.crits.as_ref(), round.damage(damage));
+            let damage = defend(p2, damage, rules.defense.as_ref(), &round, events, rng)?;
+            let taken = inflict_damage(p1, p2, damage, events);
+            reflect_damage(p1, round.reflected_damage(1, taken), events);
+            let dealt = [vitality[1].saturating_sub(p2.vitality()), vitality[0].saturating_sub(p1.vitality())];
+            settle_stakes(p1, p2, stakes, rules.wagering.as_ref(), events);
+            apply_pusnishment(p1, p2, events)?;
+            dealt
+        }
+        RoundOutcome::Won { damage, .. } => {
+            say(Msg::RoundWon { winner: &p2.name });
+            let damage = critical_damage(p2, &stats2, rules.crits.as_ref(), round.damage(damage));
+            let damage = defend(p1, damage, rules.defense.as_ref(), &round, events, rng)?;
+            let taken = inflict_damage(p2, p1, damage, events);
+            reflect_damage(p2, round.reflected_damage(0, taken), events);
+            let dealt = [vitality[1].saturating_sub(p2.vitality()), vitality[0].saturating_sub(p1.vitality())];
+            settle_stakes(p2, p1, [stakes[1], stakes[0]], rules.wagering.as_ref(), events);
+            apply_pusnishment(p2, p1, events)?;
+            dealt
+        }
+    };
+
+    // Stat modifiers count down, then display final player statistics at the end of the round.
+    wear_off_modifiers(p1);
+    wear_off_modifiers(p2);
+    say(Msg::EndOfRound);
+    if !round.modifiers.is_empty() {
+        say(Msg::RoundModifiers(&round.modifiers));
+    }
+    say(player_summary(p1));
+    say(player_summary(p2));
+
+    Ok(RoundReport { stats: [stats1, stats2], winner: outcome.winner(), damage_dealt })
+}
+
+/// Raises the damage of a round won by `winner` for each critical hit of their turn.
+fn critical_damage(winner: &Player, stats: &PlayerStats, crits: Option<&CritRules>, damage: u32) -> u32 {
+    let Some(rules) = crits else {
+        return damage;
+    };
+    let count = stats.crits();
+    if count == 0 || damage == 0 {
+        return damage;
+    }
+    let damage = rules.damage(damage, count as u32);
+    say(Msg::CriticalDamage { name: &winner.name, crits: count, damage });
+    damage
+}
+
+/// Counts down the stat modifiers of a player at the end of a round, and announces
+/// those that wear off.
+fn wear_off_modifiers(player: &mut Player) {
+    for modifier in player.stats.tick() {
+        say(Msg::ModifierExpired { name: &player.name, stat: &modifier.stat, source: &modifier.source });
+    }
+}
+
+/// Builds the summary line of a player's attributes.
+pub fn player_summary(player: &Player) -> Msg<'_> {
+    Msg::PlayerSummary {
+        name: &player.name,
+        stats: &player.stats,
+    }
+}
+
+/// Removes `damage` vitality points from the loser of a round, unless a shield absorbs them.
+/// Returns the damage actually taken.
+fn inflict_damage(winner: &Player, loser: &mut Player, damage: u32, events: &MatchEvents) -> u32 {
+    let damage = if loser.status.shield {
+        loser.status.shield = false;
+        say(Msg::ShieldAbsorbs { name: &loser.name, amount: damage });
+        0
+    } else {
+        damage
+    };
+    say(Msg::VitalityLost { name: &loser.name, amount: damage });
+    loser.lower_stat(Stat::Vitality, damage);
+    events.emit(GameEvent::RoundWon {
+        winner: winner.name.clone(),
+        loser: loser.name.clone(),
+        damage,
+    });
+    events.emit(GameEvent::VitalityChanged {
+        player: loser.name.clone(),
+        vitality: loser.vitality(),
+    });
+    damage
+}
+
+/// Lets the player stop a counter incrementing every `speed` ms on `goal`: a human
+/// at the terminal, or the bot or computer opponent playing for them.
+fn stop_counter<R: Rng + ?Sized>(
+    player: &Player,
+    goal: u8,
+    speed: u32,
+    round: &RoundRules,
+    events: &MatchEvents,
+    rng: &mut R,
+) -> Result<PlayerRes, GameError> {
+    Ok(match &player.controller {
+        Controller::Human => run_one_turn(goal, speed, round, events)?,
+        Controller::Bot(bot) => bot_objective(bot, player, goal, speed, round),
+        Controller::Ai(skill) => ai_objective(skill, goal, speed, round, rng),
+    })
+}
+
+/// Lets the loser of a round try to block part of its damage with one counter stop
+/// on a random goal, if the rules allow it and the player can pay the stamina.
+/// A human chooses whether to try; bots and computer opponents always do.
+/// Returns the damage left to take.
+fn defend<R: Rng + ?Sized>(
+    victim: &mut Player,
+    damage: u32,
+    defense: Option<&Defense>,
+    round: &RoundRules,
+    events: &MatchEvents,
+    rng: &mut R,
+) -> Result<u32, GameError> {
+    let Some(defense) = defense else {
+        return Ok(damage);
+    };
+    // A shield already absorbs the damage: there is nothing to block.
+    if damage == 0 || victim.status.shield || victim.stamina < defense.block_cost {
+        return Ok(damage);
+    }
+    let attempt = match &victim.controller {
+        Controller::Human => ask_block(victim, defense.block_cost)?,
+        Controller::Bot(_) | Controller::Ai(_) => true,
+    };
+    if !attempt || !spend(victim, defense.block_cost) {
+        return Ok(damage);
+    }
+
+    let goal = rng.gen_range(0..=100);
+    say(Msg::BlockAttempt { name: &victim.name, goal });
+    let result = stop_counter(victim, goal, round.counter_speed(victim.speed()), round, events, rng)?;
+    let blocked = defense.blocked(damage, goal, &result);
+    if blocked > 0 {
+        say(Msg::Blocked { name: &victim.name, blocked, damage });
     } else {
-        let diff = avg_score2 - avg_score1;
-        println!("{} wins the round!", p2.name);
-        println!("{} loses {} vitality points.", p1.name, diff);
-        p1.vitality = p1.vitality.saturating_sub(diff);
-        apply_pusnishment(p1);
+        say(Msg::BlockFailed { name: &victim.name });
+    }
+    events.emit(GameEvent::BlockAttempted {
+        player: victim.name.clone(),
+        goal,
+        blocked,
+    });
+    Ok(damage - blocked)
+}
+
+/// Lets a player stake vitality on the round: a human is asked at the terminal,
+/// a bot through its protocol, and a computer opponent decides by itself.
+/// The stake is taken from the player's vitality until the round is settled.
+fn place_stake(player: &mut Player, opponent: &Player, wagering: &Wagering, events: &MatchEvents) -> Result<u32, GameError> {
+    let stake = match &player.controller {
+        Controller::Human => ask_stake(player, wagering)?,
+        Controller::Bot(bot) => bot_stake(bot, player, opponent, wagering),
+        Controller::Ai(_) => ai_stake(wagering, player.vitality()),
+    };
+    if stake > 0 {
+        say(Msg::StakePlaced { name: &player.name, stake });
+        player.lower_stat(Stat::Vitality, stake);
+        events.emit(GameEvent::StakePlaced {
+            player: player.name.clone(),
+            stake,
+        });
+        events.emit(GameEvent::VitalityChanged {
+            player: player.name.clone(),
+            vitality: player.vitality(),
+        });
+    }
+    Ok(stake)
+}
+
+/// Settles the stakes of a won round: the winner gets the payout of their stake,
+/// and the loser's stake is lost. `stakes` holds the winner's stake first.
+fn settle_stakes(winner: &mut Player, loser: &Player, stakes: [u32; 2], wagering: Option<&Wagering>, events: &MatchEvents) {
+    let Some(wagering) = wagering else {
+        return;
+    };
+    if stakes[0] > 0 {
+        let payout = wagering.payout(stakes[0]);
+        say(Msg::StakeWon { name: &winner.name, amount: payout });
+        pay_stake(winner, payout, events);
+    }
+    if stakes[1] > 0 {
+        say(Msg::StakeLost { name: &loser.name, stake: stakes[1] });
+    }
+}
+
+/// Gives vitality back to a player when the stakes are settled.
+fn pay_stake(player: &mut Player, amount: u32, events: &MatchEvents) {
+    if amount == 0 {
+        return;
+    }
+    player.raise_stat(Stat::Vitality, amount);
+    events.emit(GameEvent::VitalityChanged {
+        player: player.name.clone(),
+        vitality: player.vitality(),
+    });
+}
+
+/// Lets a player with a deck draw an ability card.
+fn draw_card<R: Rng + ?Sized>(player: &mut Player, rng: &mut R) {
+    if let Some(card) = player.deck.draw(rng) {
+        say(Msg::CardDrawn { name: &player.name, card });
+    }
+}
+
+/// What a player does before an objective.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    /// Plays the ability card at this index of the hand.
+    PlayCard(usize),
+    /// Slows the counter of the objective down.
+    SlowCounter,
+}
+
+/// Lets a player act before an objective: play one of the ability cards in their
+/// hand against `opponent`, or, under stamina rules, slow the counter down. Without
+/// stamina rules, cards are free and the counter cannot be slowed.
+/// A human is asked at the terminal and a computer opponent only plays its cards.
+/// Bots do neither, as the bot protocol has no request for them.
+fn take_action(player: &mut Player, opponent: &mut Player, stamina: Option<&StaminaRules>, events: &MatchEvents) -> Result<(), GameError> {
+    let can_play_card = !player.deck.hand.is_empty() && player.stamina >= stamina.map_or(0, |s| s.card_cost);
+    let can_slow = stamina.is_some_and(|s| player.stamina >= s.slow_cost);
+    if !can_play_card && !can_slow {
+        return Ok(());
     }
+    let action = match &player.controller {
+        Controller::Human => ask_action(player, stamina, can_play_card, can_slow)?,
+        // The bot protocol has no actions, and bots are never given a deck.
+        Controller::Bot(_) => None,
+        Controller::Ai(_) if can_play_card => ai_card(&player.deck.hand).map(Action::PlayCard),
+        Controller::Ai(_) => None,
+    };
+    if let Some(action) = action {
+        perform_action(player, opponent, stamina, action, events);
+    }
+    Ok(())
+}
 
-    // Display final player statistics at the end of the round.
-    println!("\n--- End of Round ---");
-    println!(
-        "{}: Vitality: {}, Speed: {}, Strength: {}",
-        p1.name, p1.vitality, p1.speed, p1.strength
-    );
-    println!(
-        "{}: Vitality: {}, Speed: {}, Strength: {}",
-        p2.name, p2.vitality, p2.speed, p2.strength
-    );
+/// Pays for an action and applies it.
+fn perform_action(player: &mut Player, opponent: &mut Player, stamina: Option<&StaminaRules>, action: Action, events: &MatchEvents) {
+    match action {
+        Action::PlayCard(index) => {
+            let Some(card) = player.deck.play(index) else {
+                return;
+            };
+            spend(player, stamina.map_or(0, |s| s.card_cost));
+            say(Msg::CardPlayed { name: &player.name, card: &card });
+            card.effect.apply(player, opponent);
+            events.emit(GameEvent::CardPlayed {
+                player: player.name.clone(),
+                card: card.name,
+            });
+        }
+        Action::SlowCounter => {
+            let Some(stamina) = stamina else {
+                return;
+            };
+            spend(player, stamina.slow_cost);
+            say(Msg::CounterSlowed { name: &player.name, percent: stamina.slow_percent });
+            Effect::SlowCounter { percent: stamina.slow_percent }.apply(player, opponent);
+        }
+    }
+}
+
+/// Whether a player pays `cost` stamina to play an objective again after `result`:
+/// a human is asked at the terminal and a computer opponent decides by itself.
+/// Bots keep their stops, as the bot protocol has no request for retries.
+fn wants_retry(player: &Player, goal: u8, result: &PlayerRes, cost: u32) -> Result<bool, GameError> {
+    Ok(match &player.controller {
+        Controller::Human => ask_retry(player, cost)?,
+        Controller::Bot(_) => false,
+        // Free retries would let a computer opponent retry forever.
+        Controller::Ai(_) => cost > 0 && ai_retry(goal, result),
+    })
+}
+
+/// Removes the vitality a player loses at once for missing a goal.
+fn inflict_miss_cost(player: &mut Player, cost: u32, events: &MatchEvents) {
+    if cost == 0 {
+        return;
+    }
+    say(Msg::MissCost { name: &player.name, amount: cost });
+    player.lower_stat(Stat::Vitality, cost);
+    events.emit(GameEvent::VitalityChanged {
+        player: player.name.clone(),
+        vitality: player.vitality(),
+    });
+}
+
+/// Removes the damage sent back by the loser of a round from the winner's vitality.
+fn reflect_damage(winner: &mut Player, damage: u32, events: &MatchEvents) {
+    if damage == 0 {
+        return;
+    }
+    say(Msg::DamageReflected { name: &winner.name, amount: damage });
+    winner.lower_stat(Stat::Vitality, damage);
+    events.emit(GameEvent::VitalityChanged {
+        player: winner.name.clone(),
+        vitality: winner.vitality(),
+    });
 }
 
 /// Runs a single turn for a player by generating goals, executing the objective for each goal,
 /// computing scores, and then calculating the average score for the turn.
-fn play_turn(player: &mut Player, goal_count: u8) -> u32 {
-    println!(
-        "\nIt's {}'s turn (Vitality: {}, Speed: {}, Strength: {})",
-        player.name, player.vitality, player.speed, player.strength
-    );
-    let goals = create_goals(goal_count);
-    println!("Goals: {:?}", goals);
-    let total_score: u32 = goals
-        .iter()
-        .enumerate()
-        .map(|(i, target)| {
-            println!(
-                "\n--- {} - Objective {}: Target {} ---",
-                player.name,
-                i + 1,
-                target
-            );
-            // Run the objective turn for the current goal.
-            let result = run_one_turn(player.speed);
-            let score = compute_score(*target, &result, player.strength);
-            println!("Score for this objective: {}", score);
-            score
-        })
-        .sum();
-    let avg_score = if goal_count == 0 {
-        0
+/// The counter follows the rules of the round. The player may spend stamina before each
+/// objective, on an ability card against `opponent` or on a slower counter, and after
+/// an imperfect stop, to play the objective again. Only the last attempt counts.
+/// Also returns the statistics of every objective of the turn.
+fn play_turn<R: Rng + ?Sized>(
+    player: &mut Player,
+    opponent: &mut Player,
+    rules: &Ruleset,
+    round: &RoundRules,
+    events: &MatchEvents,
+    rng: &mut R,
+) -> Result<(u32, PlayerStats), GameError> {
+    say(Msg::TurnStarted {
+        name: &player.name,
+        stats: &player.stats,
+    });
+    // Luck pulls the goals toward friendlier values.
+    let mut goals = create_goals(rules.goal_count, rng);
+    rules.luck.pull_goals(luck(player), &mut goals);
+    if round.has(Modifier::HiddenGoals) {
+        say(Msg::GoalsHidden);
     } else {
-        (total_score as f32 / goal_count as f32).ceil() as u32
-    };
-    println!("{}'s average score: {}", player.name, avg_score);
-    avg_score
+        say(Msg::Goals(&goals));
+    }
+    // Hidden goals stay out of the event feed too: each one is only sent as its objective starts.
+    events.emit(GameEvent::TurnStarted {
+        player: player.name.clone(),
+        goals: if round.has(Modifier::HiddenGoals) { Vec::new() } else { goals.clone() },
+    });
+    let mut stats = PlayerStats::default();
+    let mut total_score = 0;
+    let mut streak = 0;
+    for (i, goal) in goals.iter().enumerate() {
+        say(Msg::ObjectiveHeader { name: &player.name, index: i + 1, goal: *goal });
+
+        // Spend stamina on an action, then apply the effects waiting for this objective.
+        take_action(player, opponent, rules.stamina.as_ref(), events)?;
+        let mut target = *goal;
+        if player.status.reroll_goal {
+            player.status.reroll_goal = false;
+            target = rng.gen_range(0..=100);
+            say(Msg::GoalRerolled { goal: target });
+        }
+        let slow_percent = std::mem::take(&mut player.status.slow_percent);
+        let speed = round.counter_speed(player.speed()) * (100 + slow_percent) / 100;
+
+        events.emit(GameEvent::ObjectiveStarted {
+            player: player.name.clone(),
+            index: i,
+            goal: target,
+        });
+        // Run the objective turn for the current goal, or let the bot or computer play it.
+        let mut result = stop_counter(player, target, speed, round, events, rng)?;
+        // Short of a perfect stop, the player may pay stamina to play the objective again.
+        while let Some(cost) = rules.stamina.map(|s| s.retry_cost)
+            && !result.is_perfect(target)
+            && player.stamina >= cost
+            && wants_retry(player, target, &result, cost)?
+        {
+            spend(player, cost);
+            say(Msg::ObjectiveRetried { name: &player.name, stamina: player.stamina });
+            result = stop_counter(player, target, speed, round, events, rng)?;
+        }
+        if rules.luck.forgive_miss(luck(player), &mut result, rng) {
+            say(Msg::MissForgiven { name: &player.name });
+        }
+        let mut score = round.score(rules, target, &result, player.strength());
+        let mut stat = ObjectiveStat::new(target, &result, speed, score);
+        // Perfect stops in a row build a streak, and may be critical hits under the rules.
+        streak = if stat.is_perfect() { streak + 1 } else { 0 };
+        stat.streak = streak;
+        if let Some(crits) = &rules.crits {
+            score = crits.streak_score(score, streak);
+            stat.score = score;
+            stat.crit = crits.roll(stat.is_perfect(), rules.luck.crit_bonus(luck(player)), rng);
+        }
+        events.emit(GameEvent::ObjectiveStopped {
+            player: player.name.clone(),
+            goal: target,
+            counter: result.counter,
+            miss: result.miss,
+            score,
+        });
+        say(Msg::ObjectiveScore {
+            score,
+            offset: stat.offset,
+            tier: stat.tier,
+            elapsed_ms: stat.elapsed_ms,
+        });
+        if let Some(crits) = &rules.crits
+            && streak > 1
+        {
+            say(Msg::PerfectStreak { streak, bonus: crits.streak_bonus(streak) });
+        }
+        if stat.crit {
+            say(Msg::CriticalHit { name: &player.name });
+        }
+        stats.record(stat);
+        total_score += score;
+        inflict_miss_cost(player, round.miss_cost(result.miss), events);
+    }
+    let avg_score = average_score(total_score, rules.goal_count);
+    say(Msg::AverageScore { name: &player.name, score: avg_score });
+    events.emit(GameEvent::TurnEnded {
+        player: player.name.clone(),
+        avg_score,
+    });
+    Ok((avg_score, stats))
 }
 
 //
@@ -86,11 +549,10 @@ fn play_turn(player: &mut Player, goal_count: u8) -> u32 {
 // For tests, we override them with dummy versions.
 
 #[cfg(not(test))]
-fn run_one_turn(speed: u32) -> PlayerRes {
+fn run_one_turn(_target: u8, speed: u32, round: &RoundRules, events: &MatchEvents) -> Result<PlayerRes, GameError> {
     // Wait for the user to initiate the objective.
-    println!("Press ENTER to start this objective...");
-    let mut input = String::new();
-    io::stdin().read_line(&mut input).unwrap();
+    println!("{}", tr(Msg::PressEnterToStart));
+    read_line()?;
 
     // Shared variables for the counter and miss count between threads.
     let counter = Arc::new(Mutex::new(0u8));
@@ -101,10 +563,16 @@ fn run_one_turn(speed: u32) -> PlayerRes {
     let counter_thread = Arc::clone(&counter);
     let miss_thread = Arc::clone(&miss);
     let stop_flag_thread = Arc::clone(&stop_flag);
+    let round_thread = round.clone();
+    let events_thread = events.clone();
 
-    // Spawn a thread that increments the counter continuously.
-    let handle = thread::spawn(move || {
-        while !stop_flag_thread.load(Ordering::Relaxed) {
+    // Spawn a thread that increments the counter continuously. It is marked as running
+    // before it starts, so that a Ctrl-C pressed right away waits for it to stop.
+    let started = Instant::now();
+    set_counter_running(true);
+    let handle = thread::spawn(move || -> io::Result<()> {
+        // Ctrl-C stops the counter too, so that the terminal is left on a clean line.
+        while !stop_flag_thread.load(Ordering::Relaxed) && !is_interrupted() {
             thread::sleep(Duration::from_millis(speed as u64));
             let mut count = counter_thread.lock().unwrap();
             *count += 1;
@@ -113,71 +581,201 @@ fn run_one_turn(speed: u32) -> PlayerRes {
                 *count = 0;
                 miss_thread.fetch_add(1, Ordering::Relaxed);
             }
+            events_thread.emit(GameEvent::CounterTick {
+                counter: round_thread.shown_counter(*count),
+                miss: miss_thread.load(Ordering::Relaxed),
+            });
             // Display the current counter value and miss count.
             print!(
-                "\rCounter: {} | Misses: {}",
-                *count,
-                miss_thread.load(Ordering::Relaxed)
+                "{}",
+                tr(Msg::Counter {
+                    counter: round_thread.shown_counter(*count),
+                    miss: miss_thread.load(Ordering::Relaxed),
+                })
             );
-            io::stdout().flush().unwrap();
+            if let Err(e) = io::stdout().flush() {
+                set_counter_running(false);
+                return Err(e);
+            }
         }
+        set_counter_running(false);
+        Ok(())
     });
 
     // Wait for the user to signal stopping the counter.
-    println!("\nPress ENTER to stop the counter...");
-    input.clear();
-    io::stdin().read_line(&mut input).unwrap();
+    // Stops arriving faster than a human could react are ignored.
+    let mut rejected = 0;
+    let stop = loop {
+        println!("{}", tr(Msg::PressEnterToStop));
+        if let Err(e) = read_line() {
+            break Err(e);
+        }
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        if is_human_stop(elapsed_ms) {
+            break Ok(elapsed_ms);
+        }
+        rejected += 1;
+        println!("{}", tr(Msg::StopIgnored { elapsed_ms }));
+    };
 
-    // Signal the counting thread to stop and wait for it to finish.
+    // Signal the counting thread to stop and wait for it to finish,
+    // even if reading the stop failed, so that it never outlives the objective.
     stop_flag.store(true, Ordering::Relaxed);
-    handle.join().unwrap();
+    handle.join().map_err(|_| GameError::CounterThread)??;
+    let elapsed_ms = stop?;
 
     // Retrieve the final counter value and miss count.
-    let final_counter = *counter.lock().unwrap();
+    let final_counter = round.shown_counter(*counter.lock().unwrap());
     let final_miss = miss.load(Ordering::Relaxed);
 
-    println!("\nObjective stopped at: {} (Misses: {})", final_counter, final_miss);
-    PlayerRes {
+    println!("{}", tr(Msg::ObjectiveStopped { counter: final_counter, miss: final_miss }));
+    Ok(PlayerRes {
         counter: final_counter,
         miss: final_miss,
-    }
+        elapsed_ms,
+        rejected,
+    })
 }
 
 #[cfg(test)]
-fn run_one_turn(_speed: u32) -> PlayerRes {
-    // Dummy implementation for tests.
-    PlayerRes {
-        counter: 50,
+fn run_one_turn(target: u8, speed: u32, round: &RoundRules, _events: &MatchEvents) -> Result<PlayerRes, GameError> {
+    // Dummy implementation for tests: always a perfect stop.
+    Ok(PlayerRes {
+        counter: target,
         miss: 0,
-    }
+        elapsed_ms: round.shown_counter(target) as u64 * speed as u64,
+        rejected: 0,
+    })
+}
+
+/// Lets the winner of a round choose the penalty applied to the loser:
+/// a human is asked at the terminal, a bot through its protocol, and a
+/// computer opponent decides by itself.
+fn apply_pusnishment(winner: &mut Player, victim: &mut Player, events: &MatchEvents) -> Result<(), GameError> {
+    let penalty = match &winner.controller {
+        Controller::Human => ask_penalty(victim)?,
+        Controller::Bot(bot) => Some(bot_penalty(bot, winner, victim)),
+        Controller::Ai(_) => Some(ai_penalty(victim)),
+    };
+    let Some(penalty) = penalty else {
+        return Ok(());
+    };
+    say(Msg::PenaltyApplied { name: &victim.name, penalty });
+    penalty.apply(winner, victim);
+    events.emit(GameEvent::PenaltyApplied {
+        player: victim.name.clone(),
+        penalty,
+    });
+    Ok(())
 }
 
 #[cfg(not(test))]
-fn apply_pusnishment(victim: &mut Player) {
+fn ask_penalty(victim: &Player) -> Result<Option<Penalty>, GameError> {
     // Prompt the user to choose a penalty for the opponent.
-    println!("Choose a penalty to apply to {}:", victim.name);
-    println!("1: -5 to speed");
-    println!("2: -5 to strength");
-    print!("Your choice (1 or 2): ");
-    io::stdout().flush().unwrap();
-    let mut choice = String::new();
-    io::stdin().read_line(&mut choice).unwrap();
+    println!("{}", tr(Msg::ChoosePenalty { name: &victim.name }));
+    println!("{}", tr(Msg::PenaltyOption { choice: 1, penalty: Penalty::Speed }));
+    println!("{}", tr(Msg::PenaltyOption { choice: 2, penalty: Penalty::Strength }));
+    let choice = prompt(&tr(Msg::PenaltyPrompt))?;
     match choice.trim() {
-        "1" => {
-            victim.speed = victim.speed.saturating_sub(5);
-            println!("{} loses 5 speed.", victim.name);
+        "1" => Ok(Some(Penalty::Speed)),
+        "2" => Ok(Some(Penalty::Strength)),
+        _ => {
+            println!("{}", tr(Msg::InvalidPenaltyChoice));
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+fn ask_penalty(_victim: &Player) -> Result<Option<Penalty>, GameError> {
+    // Dummy version for tests: no penalty is chosen.
+    Ok(None)
+}
+
+#[cfg(not(test))]
+fn ask_block(victim: &Player, cost: u32) -> Result<bool, GameError> {
+    // Ask the loser whether to spend stamina on a block.
+    let answer = prompt(&tr(Msg::BlockPrompt { name: &victim.name, cost, stamina: victim.stamina }))?;
+    Ok(is_yes(&answer))
+}
+
+#[cfg(test)]
+fn ask_block(_victim: &Player, _cost: u32) -> Result<bool, GameError> {
+    // Dummy version for tests: no block is attempted.
+    Ok(false)
+}
+
+#[cfg(not(test))]
+fn ask_stake(player: &Player, wagering: &Wagering) -> Result<u32, GameError> {
+    // Ask until the stake is a number the player may risk; ENTER stakes nothing.
+    loop {
+        let max = wagering.max_stake(player.vitality());
+        let answer = prompt(&tr(Msg::StakePrompt { name: &player.name, vitality: player.vitality(), max }))?;
+        let answer = answer.trim();
+        if answer.is_empty() {
+            return Ok(0);
         }
-        "2" => {
-            victim.strength = victim.strength.saturating_sub(5);
-            println!("{} loses 5 strength.", victim.name);
+        match answer.parse::<u32>() {
+            Ok(stake) => match wagering.validate(stake, player.vitality()) {
+                Ok(stake) => return Ok(stake),
+                Err(e) => println!("{}", e),
+            },
+            Err(_) => println!("{}", tr(Msg::WizardInvalidNumber)),
         }
-        _ => println!("Invalid choice. No penalty applied."),
     }
 }
 
 #[cfg(test)]
-fn apply_pusnishment(_victim: &mut Player) {
-    // Dummy version for tests: do nothing.
+fn ask_stake(_player: &Player, _wagering: &Wagering) -> Result<u32, GameError> {
+    // Dummy version for tests: nothing is staked.
+    Ok(0)
+}
+
+#[cfg(not(test))]
+fn ask_action(player: &Player, stamina: Option<&StaminaRules>, can_play_card: bool, can_slow: bool) -> Result<Option<Action>, GameError> {
+    // Show the affordable actions and let the user pick one, or none.
+    if stamina.is_some() {
+        println!("{}", tr(Msg::StaminaLeft { name: &player.name, stamina: player.stamina }));
+    }
+    let cards = if can_play_card { player.deck.hand.len() } else { 0 };
+    for (i, card) in player.deck.hand.iter().take(cards).enumerate() {
+        println!("{}", tr(Msg::CardOption { choice: i + 1, card, cost: stamina.map(|s| s.card_cost) }));
+    }
+    if let Some(stamina) = stamina.filter(|_| can_slow) {
+        println!("{}", tr(Msg::SlowOption { choice: cards + 1, percent: stamina.slow_percent, cost: stamina.slow_cost }));
+    }
+    let choice = prompt(&tr(Msg::ActionPrompt))?;
+    let choice = choice.trim();
+    if choice.is_empty() {
+        return Ok(None);
+    }
+    match choice.parse::<usize>() {
+        Ok(n) if (1..=cards).contains(&n) => Ok(Some(Action::PlayCard(n - 1))),
+        Ok(n) if can_slow && n == cards + 1 => Ok(Some(Action::SlowCounter)),
+        _ => {
+            println!("{}", tr(Msg::InvalidActionChoice));
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+fn ask_action(_player: &Player, _stamina: Option<&StaminaRules>, _can_play_card: bool, _can_slow: bool) -> Result<Option<Action>, GameError> {
+    // Dummy version for tests: no action is taken.
+    Ok(None)
+}
+
+#[cfg(not(test))]
+fn ask_retry(player: &Player, cost: u32) -> Result<bool, GameError> {
+    // Ask whether to spend stamina on another attempt at the objective.
+    let answer = prompt(&tr(Msg::RetryPrompt { cost, stamina: player.stamina }))?;
+    Ok(is_yes(&answer))
+}
+
+#[cfg(test)]
+fn ask_retry(_player: &Player, _cost: u32) -> Result<bool, GameError> {
+    // Dummy version for tests: the objective is never retried.
+    Ok(false)
 }
 
 //
@@ -186,33 +784,54 @@ fn apply_pusnishment(_victim: &mut Player) {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::classes::Player;
+    use crate::classes::{Player, DEFAULT_STAMINA};
+    use crate::cards::Deck;
+    use crate::events::{EventBus, MatchEvent};
+    use crate::modifiers::WeightedModifier;
+    use crate::wager::Wagering;
+    use crate::stat_map::StatKey;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use std::sync::{Arc, Mutex};
 
     #[test]
     fn test_play_turn_zero_goals() {
         // With 0 goals, the average score should be 0.
         let mut player = Player::new("TestPlayer".to_string(), 50, 50, 10);
-        let avg = play_turn(&mut player, 0);
+        let mut opponent = Player::new("Opponent".to_string(), 50, 50, 10);
+        let rules = Ruleset::with_goal_count(0);
+        let (avg, stats) = play_turn(&mut player, &mut opponent, &rules, &RoundRules::default(), &MatchEvents::default(), &mut ChaCha8Rng::seed_from_u64(0)).unwrap();
         assert_eq!(avg, 0);
+        assert!(stats.objectives.is_empty());
     }
 
     #[test]
     fn test_run_one_turn_dummy() {
         // In test mode, run_one_turn should return the dummy value.
-        let res = run_one_turn(50);
+        let res = run_one_turn(50, 50, &RoundRules::default(), &MatchEvents::default()).unwrap();
         assert_eq!(res.counter, 50);
         assert_eq!(res.miss, 0);
     }
 
+    #[test]
+    fn test_is_human_stop() {
+        // Pre-typed ENTERs stop the counter within a few milliseconds.
+        assert!(!is_human_stop(2));
+        assert!(!is_human_stop(MIN_STOP_DELAY_MS - 1));
+        assert!(is_human_stop(MIN_STOP_DELAY_MS));
+        assert!(is_human_stop(2500));
+    }
+
     #[test]
     fn test_apply_pusnishment_dummy() {
-        // The dummy version of apply_pusnishment should not change the player's attributes.
+        // The dummy penalty prompt should not change the player's attributes.
+        let mut winner = Player::new("Winner".to_string(), 50, 50, 10);
         let mut player = Player::new("TestPlayer".to_string(), 50, 50, 10);
-        let orig_speed = player.speed;
-        let orig_strength = player.strength;
-        apply_pusnishment(&mut player);
-        assert_eq!(player.speed, orig_speed);
-        assert_eq!(player.strength, orig_strength);
+        let orig_speed = player.speed();
+        let orig_strength = player.strength();
+        apply_pusnishment(&mut winner, &mut player, &MatchEvents::default()).unwrap();
+        assert_eq!(player.speed(), orig_speed);
+        assert_eq!(player.strength(), orig_strength);
     }
 
     #[test]
@@ -220,10 +839,14 @@ mod tests {
         // Create two players with identical attributes so that they tie.
         let mut player1 = Player::new("Alice".to_string(), 50, 50, 10);
         let mut player2 = Player::new("Bob".to_string(), 50, 50, 10);
-        play_round(&mut player1, &mut player2, 3);
+        let report = play_round(&mut player1, &mut player2, &Ruleset::with_goal_count(3), &MatchEvents::default(), &mut ChaCha8Rng::seed_from_u64(0)).unwrap();
+        // Every objective is recorded, and the dummy stops are all perfect.
+        assert_eq!(report.stats[0].objectives.len(), 3);
+        assert_eq!(report.stats[1].perfect_hits(), 3);
+        assert_eq!((report.winner, report.damage_dealt), (None, [0, 0]));
         // In a tie, neither player's vitality should change.
-        assert_eq!(player1.vitality, 50);
-        assert_eq!(player2.vitality, 50);
+        assert_eq!(player1.vitality(), 50);
+        assert_eq!(player2.vitality(), 50);
     }
 
     #[test]
@@ -235,8 +858,240 @@ mod tests {
         // Thus, player2 should lose (120 - 110) = 10 vitality.
         let mut player1 = Player::new("Alice".to_string(), 50, 50, 20);
         let mut player2 = Player::new("Bob".to_string(), 50, 50, 10);
-        play_round(&mut player1, &mut player2, 3);
-        assert_eq!(player1.vitality, 50);
-        assert_eq!(player2.vitality, 40);
+        play_round(&mut player1, &mut player2, &Ruleset::with_goal_count(3), &MatchEvents::default(), &mut ChaCha8Rng::seed_from_u64(0)).unwrap();
+        assert_eq!(player1.vitality(), 50);
+        assert_eq!(player2.vitality(), 40);
+    }
+
+    #[test]
+    fn test_play_round_with_reflection() {
+        // The boss loses 10 vitality and sends half of it back to the winner.
+        use crate::boss::{BossFight, Phase};
+        let mut player1 = Player::new("Alice".to_string(), 50, 50, 20);
+        let mut player2 = Player::new("Boss".to_string(), 50, 50, 10);
+        let phases = vec![Phase { threshold_percent: 100, modifiers: vec![Modifier::DoubleSpeed], reflect_percent: 50 }];
+        let mut fight = BossFight::new(1, 50, phases);
+        play_round_with(&mut player1, &mut player2, &Ruleset::with_goal_count(3), &mut fight, &MatchEvents::default(), &mut ChaCha8Rng::seed_from_u64(0)).unwrap();
+        assert_eq!(player2.vitality(), 40);
+        assert_eq!(player1.vitality(), 45);
+    }
+
+    #[test]
+    fn test_play_round_drawn_modifier() {
+        // The only modifier of the pool doubles the 10 points of damage.
+        let mut player1 = Player::new("Alice".to_string(), 50, 50, 20);
+        let mut player2 = Player::new("Bob".to_string(), 50, 50, 10);
+        let rules = Ruleset {
+            modifier_pool: vec![WeightedModifier { modifier: Some(Modifier::DoubleDamage), weight: 1 }],
+            ..Ruleset::with_goal_count(3)
+        };
+        play_round(&mut player1, &mut player2, &rules, &MatchEvents::default(), &mut ChaCha8Rng::seed_from_u64(0)).unwrap();
+        assert_eq!(player2.vitality(), 30);
+    }
+
+    #[test]
+    fn test_play_turn_backwards_misses() {
+        // A computer opponent overshooting a backwards counter by a cycle pays for its miss.
+        let skill = crate::classes::AiSkill { sigma_ms: 0, bias_ms: 101 * 50 };
+        let mut player = Player::new("Robot".to_string(), 50, 50, 10).with_ai(skill);
+        let mut opponent = Player::new("Opponent".to_string(), 50, 50, 10);
+        let round = RoundRules {
+            modifiers: vec![Modifier::CounterBackwards, Modifier::MissesCostVitality],
+            ..RoundRules::default()
+        };
+        let (_, stats) = play_turn(&mut player, &mut opponent, &Ruleset::with_goal_count(2), &round, &MatchEvents::default(), &mut ChaCha8Rng::seed_from_u64(0)).unwrap();
+        assert!(stats.objectives.iter().all(|o| o.miss == 1 && o.counter == o.goal));
+        assert_eq!(player.vitality(), 50 - 2 * crate::modifiers::MISS_VITALITY_COST);
+    }
+
+    #[test]
+    fn test_play_turn_forgives_lucky_misses() {
+        // The same overshooting robot, lucky enough to always be forgiven, misses for free.
+        let skill = crate::classes::AiSkill { sigma_ms: 0, bias_ms: 101 * 50 };
+        let luck = StatKey::Extra(crate::luck::LUCK.to_string());
+        let mut player = Player::new("Robot".to_string(), 50, 50, 10).with_ai(skill).with_stat(luck, 50);
+        let mut opponent = Player::new("Opponent".to_string(), 50, 50, 10);
+        let round = RoundRules {
+            modifiers: vec![Modifier::CounterBackwards, Modifier::MissesCostVitality],
+            ..RoundRules::default()
+        };
+        let mut rules = Ruleset::with_goal_count(2);
+        rules.luck.max_forgive_percent = 100;
+        let (_, stats) = play_turn(&mut player, &mut opponent, &rules, &round, &MatchEvents::default(), &mut ChaCha8Rng::seed_from_u64(0)).unwrap();
+        assert!(stats.objectives.iter().all(|o| o.miss == 0));
+        assert_eq!(player.vitality(), 50);
+    }
+
+    #[test]
+    fn test_play_round_with_cards() {
+        // The computer opponent draws and plays its only card, a shield that absorbs the
+        // damage of the round it loses; the human keeps their cards.
+        let deck: Deck = serde_json::from_str(r#"{"cards": [{"name": "Shield", "effect": "shield"}]}"#).unwrap();
+        let skill = crate::classes::AiSkill { sigma_ms: 0, bias_ms: 6 * 50 };
+        let mut human = Player::new("Alice".to_string(), 50, 50, 20).with_deck(deck.clone());
+        let mut robot = Player::new("Robot".to_string(), 50, 50, 10).with_ai(skill).with_deck(deck);
+        play_round(&mut human, &mut robot, &Ruleset::with_goal_count(3), &MatchEvents::default(), &mut ChaCha8Rng::seed_from_u64(0)).unwrap();
+        assert_eq!(human.deck.hand.len(), 1);
+        assert!(robot.deck.is_empty());
+        assert!(!robot.status.shield);
+        assert_eq!(robot.vitality(), 50);
+    }
+
+    #[test]
+    fn test_play_round_wagers() {
+        let rules = Ruleset {
+            wagering: Some(Wagering::default()),
+            ..Ruleset::with_goal_count(3)
+        };
+        let perfect = crate::classes::AiSkill { sigma_ms: 0, bias_ms: 0 };
+
+        // Identical computer opponents tie and get their stake of a tenth of their vitality back.
+        let mut robot1 = Player::new("Robot1".to_string(), 50, 50, 10).with_ai(perfect);
+        let mut robot2 = Player::new("Robot2".to_string(), 40, 50, 10).with_ai(perfect);
+        play_round(&mut robot1, &mut robot2, &rules, &MatchEvents::default(), &mut ChaCha8Rng::seed_from_u64(0)).unwrap();
+        assert_eq!((robot1.vitality(), robot2.vitality()), (50, 40));
+
+        // The stronger one wins 10 points of damage and doubles its stake of 5;
+        // the loser loses its stake of 4 on top of the damage.
+        let mut robot1 = Player::new("Robot1".to_string(), 50, 50, 20).with_ai(perfect);
+        let report = play_round(&mut robot1, &mut robot2, &rules, &MatchEvents::default(), &mut ChaCha8Rng::seed_from_u64(0)).unwrap();
+        assert_eq!(robot1.vitality(), 55);
+        assert_eq!(robot2.vitality(), 40 - 4 - 10);
+        // Only the damage counts as dealt, not the stake won.
+        assert_eq!((report.winner, report.damage_dealt), (Some(0), [10, 0]));
+    }
+
+    #[test]
+    fn test_play_round_crits() {
+        // The human stops perfectly three times in a row, each stop a critical hit:
+        // scores of 120, 132 and 144 against 90, and 30% more damage per critical hit.
+        let rules = Ruleset {
+            crits: Some(CritRules { chance_percent: 100, damage_bonus_percent: 30, ..CritRules::default() }),
+            ..Ruleset::with_goal_count(3)
+        };
+        let skill = crate::classes::AiSkill { sigma_ms: 0, bias_ms: 5 * 50 };
+        let mut human = Player::new("Alice".to_string(), 50, 50, 20);
+        let mut robot = Player::new("Robot".to_string(), 100, 50, 10).with_ai(skill);
+        let [stats, _] = play_round(&mut human, &mut robot, &rules, &MatchEvents::default(), &mut ChaCha8Rng::seed_from_u64(0)).unwrap().stats;
+        let streaks: Vec<(u32, u32, bool)> = stats.objectives.iter().map(|o| (o.streak, o.score, o.crit)).collect();
+        assert_eq!(streaks, [(1, 120, true), (2, 132, true), (3, 144, true)]);
+        assert_eq!(robot.vitality(), 100 - 42 * 190 / 100);
+    }
+
+    #[test]
+    fn test_play_turn_retries() {
+        // A computer opponent stopping 20 away from every goal retries while it has
+        // stamina, and only its last attempt counts.
+        let skill = crate::classes::AiSkill { sigma_ms: 0, bias_ms: 20 * 50 };
+        let mut player = Player::new("Robot".to_string(), 50, 50, 10).with_ai(skill);
+        let mut opponent = Player::new("Opponent".to_string(), 50, 50, 10);
+        // Without stamina rules, nothing is retried.
+        let rules = Ruleset::with_goal_count(2);
+        play_turn(&mut player, &mut opponent, &rules, &RoundRules::default(), &MatchEvents::default(), &mut ChaCha8Rng::seed_from_u64(0)).unwrap();
+        assert_eq!(player.stamina, DEFAULT_STAMINA);
+        let rules = Ruleset { stamina: Some(StaminaRules::default()), ..rules };
+        let (_, stats) = play_turn(&mut player, &mut opponent, &rules, &RoundRules::default(), &MatchEvents::default(), &mut ChaCha8Rng::seed_from_u64(0)).unwrap();
+        assert_eq!(player.stamina, 0);
+        assert_eq!(stats.objectives.len(), 2);
+        // Without stamina to pay for a retry, the first stop stands.
+        let (_, stats) = play_turn(&mut player, &mut opponent, &rules, &RoundRules::default(), &MatchEvents::default(), &mut ChaCha8Rng::seed_from_u64(0)).unwrap();
+        assert_eq!(stats.objectives.len(), 2);
+        // Stamina comes back at the start of each round.
+        play_round(&mut opponent, &mut player, &rules, &MatchEvents::default(), &mut ChaCha8Rng::seed_from_u64(0)).unwrap();
+        assert_eq!(player.stamina, 0);
+        let stingy = Ruleset { stamina: Some(StaminaRules { regen_per_round: 2, retry_cost: 5, ..StaminaRules::default() }), ..rules };
+        play_round(&mut opponent, &mut player, &stingy, &MatchEvents::default(), &mut ChaCha8Rng::seed_from_u64(0)).unwrap();
+        assert_eq!(player.stamina, 2);
+    }
+
+    #[test]
+    fn test_play_card_costs_stamina() {
+        // A computer opponent out of stamina keeps its card.
+        let deck: Deck = serde_json::from_str(r#"{"cards": [{"name": "Shield", "effect": "shield"}]}"#).unwrap();
+        let mut robot = Player::new("Robot".to_string(), 50, 50, 10).with_ai(crate::classes::AiSkill { sigma_ms: 0, bias_ms: 0 }).with_deck(deck.clone());
+        let mut opponent = Player::new("Opponent".to_string(), 50, 50, 10);
+        robot.deck.draw(&mut ChaCha8Rng::seed_from_u64(0));
+        robot.stamina = 0;
+        take_action(&mut robot, &mut opponent, Some(&StaminaRules::default()), &MatchEvents::default()).unwrap();
+        assert_eq!(robot.deck.hand.len(), 1);
+        robot.stamina = 1;
+        take_action(&mut robot, &mut opponent, Some(&StaminaRules::default()), &MatchEvents::default()).unwrap();
+        assert!(robot.deck.hand.is_empty() && robot.status.shield);
+        assert_eq!(robot.stamina, 0);
+        // Without stamina rules, cards are free.
+        robot.deck = deck;
+        robot.deck.draw(&mut ChaCha8Rng::seed_from_u64(0));
+        take_action(&mut robot, &mut opponent, None, &MatchEvents::default()).unwrap();
+        assert!(robot.deck.hand.is_empty());
+    }
+
+    #[test]
+    fn test_slow_counter_action() {
+        let mut player = Player::new("Alice".to_string(), 50, 50, 10);
+        let mut opponent = Player::new("Bob".to_string(), 50, 50, 10);
+        let stamina = StaminaRules { slow_cost: 2, slow_percent: 40, ..StaminaRules::default() };
+        perform_action(&mut player, &mut opponent, Some(&stamina), Action::SlowCounter, &MatchEvents::default());
+        assert_eq!(player.status.slow_percent, 40);
+        assert_eq!(player.stamina, DEFAULT_STAMINA - 2);
+    }
+
+    #[test]
+    fn test_play_round_blocks() {
+        // The computer opponent loses each round by 10 points of damage, and its perfect
+        // blocks take all of it off until its stamina runs out (without stamina rules,
+        // it does not come back).
+        let rules = Ruleset {
+            defense: Some(Defense::default()),
+            ..Ruleset::with_goal_count(3)
+        };
+        let perfect = crate::classes::AiSkill { sigma_ms: 0, bias_ms: 0 };
+        let mut human = Player::new("Alice".to_string(), 50, 50, 20);
+        let mut robot = Player::new("Robot".to_string(), 50, 50, 10).with_ai(perfect);
+        robot.stamina = 1;
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        play_round(&mut human, &mut robot, &rules, &MatchEvents::default(), &mut rng).unwrap();
+        assert_eq!((robot.vitality(), robot.stamina), (50, 0));
+        play_round(&mut human, &mut robot, &rules, &MatchEvents::default(), &mut rng).unwrap();
+        assert_eq!(robot.vitality(), 40);
+        // The human never chooses to block in tests.
+        let mut robot = Player::new("Robot".to_string(), 50, 50, 30).with_ai(perfect);
+        play_round(&mut human, &mut robot, &rules, &MatchEvents::default(), &mut rng).unwrap();
+        assert_eq!((human.vitality(), human.stamina), (40, DEFAULT_STAMINA));
+    }
+
+    #[test]
+    fn test_hidden_goals_are_not_emitted() {
+        let bus = EventBus::default();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&received);
+        bus.subscribe(Arc::new(move |event: &MatchEvent| {
+            sink.lock().unwrap().push(event.event.clone());
+        }));
+        let mut player = Player::new("Alice".to_string(), 50, 50, 10);
+        let mut opponent = Player::new("Bob".to_string(), 50, 50, 10);
+        let round = RoundRules { modifiers: vec![Modifier::HiddenGoals], ..RoundRules::default() };
+        play_turn(&mut player, &mut opponent, &Ruleset::with_goal_count(3), &round, &bus.for_match(1), &mut ChaCha8Rng::seed_from_u64(0)).unwrap();
+        let events = received.lock().unwrap();
+        assert!(matches!(&events[0], GameEvent::TurnStarted { goals, .. } if goals.is_empty()));
+        // Each goal is only revealed as its objective starts.
+        let started = events.iter().filter(|e| matches!(e, GameEvent::ObjectiveStarted { .. })).count();
+        assert_eq!(started, 3);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_play_round_with_failing_bot() {
+        // A bot that exits at once stops far from every goal after a full counter
+        // cycle, so the human opponent wins the round.
+        let bot = crate::bot::BotProcess::spawn("true", std::time::Duration::from_secs(5)).unwrap();
+        let mut human = Player::new("Alice".to_string(), 50, 50, 10);
+        let mut robot = Player::new("Robot".to_string(), 50, 50, 10).with_bot(bot);
+        // Its stops are never perfect, so they are never critical hits either.
+        let rules = Ruleset { crits: Some(CritRules { chance_percent: 100, ..CritRules::default() }), ..Ruleset::with_goal_count(2) };
+        let stats = play_round(&mut human, &mut robot, &rules, &MatchEvents::default(), &mut ChaCha8Rng::seed_from_u64(0)).unwrap().stats;
+        assert!(stats[1].objectives.iter().all(|o| o.miss >= 1 && o.tier == 5));
+        assert!(stats[1].objectives.iter().all(|o| !o.crit && o.streak == 0));
+        assert_eq!(human.vitality(), 50);
+        assert!(robot.vitality() < 50);
     }
 }
@@ -0,0 +1,99 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::{tr, Msg};
+
+/// Rules of the betting phase played before each round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Wagering {
+    /// Share of their stake the winner of a round gets back, in percent:
+    /// 200 gives back twice the stake.
+    pub multiplier_percent: u32,
+    /// Largest share of their current vitality a player may stake, in percent.
+    pub max_stake_percent: u32,
+}
+
+impl Default for Wagering {
+    /// Default wagering rules: the winner doubles their stake, and players may stake
+    /// up to half of their vitality.
+    fn default() -> Self {
+        Wagering {
+            multiplier_percent: 200,
+            max_stake_percent: 50,
+        }
+    }
+}
+
+impl Wagering {
+    /// Largest stake allowed for a player with the given vitality.
+    pub fn max_stake(&self, vitality: u32) -> u32 {
+        (vitality as u64 * self.max_stake_percent as u64 / 100) as u32
+    }
+
+    /// Checks a stake against the vitality of the player placing it.
+    pub fn validate(&self, stake: u32, vitality: u32) -> Result<u32, WagerError> {
+        let max = self.max_stake(vitality);
+        if stake > max {
+            Err(WagerError::StakeTooHigh { stake, max })
+        } else {
+            Ok(stake)
+        }
+    }
+
+    /// Vitality paid back to the winner of a round for their stake.
+    pub fn payout(&self, stake: u32) -> u32 {
+        (stake as u64 * self.multiplier_percent as u64 / 100) as u32
+    }
+}
+
+/// Reasons a stake is refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WagerError {
+    /// The stake is more than the player may risk.
+    StakeTooHigh { stake: u32, max: u32 },
+}
+
+impl fmt::Display for WagerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WagerError::StakeTooHigh { stake, max } => write!(f, "{}", tr(Msg::ErrStakeTooHigh { stake: *stake, max: *max })),
+        }
+    }
+}
+
+impl std::error::Error for WagerError {}
+
+/// Stake a computer opponent places: a tenth of its vitality, within the allowed maximum.
+pub fn ai_stake(wagering: &Wagering, vitality: u32) -> u32 {
+    (vitality / 10).min(wagering.max_stake(vitality))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_stake() {
+        let wagering = Wagering::default();
+        assert_eq!(wagering.max_stake(41), 20);
+        assert_eq!(wagering.validate(20, 41), Ok(20));
+        assert_eq!(wagering.validate(0, 0), Ok(0));
+        assert_eq!(wagering.validate(21, 41), Err(WagerError::StakeTooHigh { stake: 21, max: 20 }));
+    }
+
+    #[test]
+    fn test_payout() {
+        let wagering = Wagering { multiplier_percent: 150, ..Wagering::default() };
+        assert_eq!(wagering.payout(10), 15);
+        assert_eq!(Wagering::default().payout(7), 14);
+    }
+
+    #[test]
+    fn test_ai_stake() {
+        let wagering = Wagering { max_stake_percent: 5, ..Wagering::default() };
+        assert_eq!(ai_stake(&Wagering::default(), 55), 5);
+        assert_eq!(ai_stake(&wagering, 100), 5);
+        assert_eq!(ai_stake(&wagering, 0), 0);
+    }
+}
@@ -0,0 +1,136 @@
+use std::io::{self, BufRead, Write};
+
+use crate::classes::{Player, PlayerBuilder, PlayerError, Stat};
+use crate::error::GameError;
+use crate::i18n::{tr, Msg};
+use crate::input::read_line_from;
+
+/// Completes a player interactively: asks for the name and the stats that
+/// `builder` is missing, within its limits and point budget.
+/// A complete builder is built without asking anything.
+pub fn create_player(index: usize, builder: PlayerBuilder) -> Result<Player, GameError> {
+    if builder.is_complete() {
+        return Ok(builder.build()?);
+    }
+    run_wizard(index, builder, &mut io::stdin().lock())
+}
+
+/// Asks for the name of a player whose stats come from elsewhere, such as a profile.
+pub fn ask_name(index: usize) -> Result<String, GameError> {
+    read_name(index, &mut io::stdin().lock())
+}
+
+/// Asks for a player's name until it is not empty.
+fn read_name<R: BufRead>(index: usize, input: &mut R) -> Result<String, GameError> {
+    loop {
+        let name = ask(input, &tr(Msg::WizardName { index }))?;
+        if !name.trim().is_empty() {
+            return Ok(name.trim().to_string());
+        }
+        println!("{}", PlayerError::EmptyName);
+    }
+}
+
+/// Asks for the missing fields of a player, reading the answers from `input`.
+fn run_wizard<R: BufRead>(index: usize, mut builder: PlayerBuilder, input: &mut R) -> Result<Player, GameError> {
+    let limits = *builder.get_limits();
+    // Reject the values given on the command line before asking anything.
+    for stat in Stat::ALL {
+        if let Some(value) = builder.get_stat(stat)
+            && !limits.range(stat).contains(value)
+        {
+            return Err(PlayerError::OutOfRange { stat, value, range: limits.range(stat) }.into());
+        }
+    }
+    println!("{}", tr(Msg::WizardTitle { index, budget: limits.budget }));
+
+    if builder.get_name().is_none() {
+        builder = builder.name(read_name(index, input)?);
+    }
+
+    // Ask for each missing stat, keeping enough points for the minimum of the following ones.
+    let missing: Vec<Stat> = Stat::ALL.into_iter().filter(|stat| builder.get_stat(*stat).is_none()).collect();
+    let mut spent: u32 = Stat::ALL.iter().filter_map(|stat| builder.get_stat(*stat)).sum();
+    for (i, stat) in missing.iter().enumerate() {
+        let range = limits.range(*stat);
+        let reserved: u32 = missing[i + 1..].iter().map(|next| limits.range(*next).min).sum();
+        let default = Player::default().stat(*stat).clamp(range.min, range.max);
+        loop {
+            let left = limits.budget.saturating_sub(spent);
+            let answer = ask(input, &tr(Msg::WizardStat { stat: *stat, min: range.min, max: range.max, left, default }))?;
+            let value = match answer.trim() {
+                "" => default,
+                text => match text.parse::<u32>() {
+                    Ok(value) => value,
+                    Err(_) => {
+                        println!("{}", tr(Msg::WizardInvalidNumber));
+                        continue;
+                    }
+                },
+            };
+            let total = spent.saturating_add(value).saturating_add(reserved);
+            if !range.contains(value) {
+                println!("{}", PlayerError::OutOfRange { stat: *stat, value, range });
+            } else if total > limits.budget {
+                println!("{}", PlayerError::OverBudget { total, budget: limits.budget });
+            } else {
+                spent += value;
+                builder = builder.stat(*stat, value);
+                break;
+            }
+        }
+    }
+
+    Ok(builder.build()?)
+}
+
+/// Displays a question and reads the answer from `input`.
+fn ask<R: BufRead>(input: &mut R, question: &str) -> Result<String, GameError> {
+    print!("{}", question);
+    io::stdout().flush()?;
+    read_line_from(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classes::PlayerLimits;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_wizard_asks_missing_fields() {
+        // Empty name, then a name; invalid number, out of range, then valid vitality;
+        // default speed; strength too high for the budget, then valid.
+        let mut input = Cursor::new("\nAlice\nabc\n0\n80\n\n90\n70\n");
+        let player = run_wizard(1, PlayerBuilder::new(), &mut input).unwrap();
+        assert_eq!(player, Player::new("Alice".to_string(), 80, 50, 70));
+    }
+
+    #[test]
+    fn test_wizard_keeps_given_fields() {
+        let builder = PlayerBuilder::new().name("Bob").speed(30).strength(40);
+        let player = run_wizard(2, builder, &mut Cursor::new("60\n")).unwrap();
+        assert_eq!(player, Player::new("Bob".to_string(), 60, 30, 40));
+    }
+
+    #[test]
+    fn test_wizard_reserves_points_for_later_stats() {
+        // With 30 points, vitality cannot take the speed's minimum of 10.
+        let limits = PlayerLimits { budget: 30, ..PlayerLimits::default() };
+        let builder = PlayerBuilder::new().name("Carol").strength(0).limits(limits);
+        let player = run_wizard(1, builder, &mut Cursor::new("25\n20\n10\n")).unwrap();
+        assert_eq!((player.vitality(), player.speed()), (20, 10));
+    }
+
+    #[test]
+    fn test_wizard_rejects_given_values_first() {
+        let result = run_wizard(1, PlayerBuilder::new().speed(0), &mut Cursor::new(""));
+        assert!(matches!(result, Err(GameError::InvalidPlayer(PlayerError::OutOfRange { stat: Stat::Speed, .. }))));
+    }
+
+    #[test]
+    fn test_wizard_input_closed() {
+        let result = run_wizard(1, PlayerBuilder::new(), &mut Cursor::new(""));
+        assert!(matches!(result, Err(GameError::InputClosed)));
+    }
+}